@@ -6,6 +6,7 @@ pub struct EnvVars;
 
 impl EnvVars {
     pub const PATH: &'static str = "PATH";
+    pub const TERM: &'static str = "TERM";
 
     pub const SKIP: &'static str = "SKIP";
 
@@ -15,7 +16,20 @@ impl EnvVars {
     pub const PREK_SKIP: &'static str = "PREK_SKIP";
     pub const PREK_ALLOW_NO_CONFIG: &'static str = "PREK_ALLOW_NO_CONFIG";
     pub const PREK_NO_CONCURRENCY: &'static str = "PREK_NO_CONCURRENCY";
+    pub const PREK_JOBS: &'static str = "PREK_JOBS";
     pub const PREK_NO_FAST_PATH: &'static str = "PREK_NO_FAST_PATH";
+    pub const PREK_OFFLINE: &'static str = "PREK_OFFLINE";
+    pub const PREK_STRICT: &'static str = "PREK_STRICT";
+    pub const PREK_FOLLOW_SYMLINKS: &'static str = "PREK_FOLLOW_SYMLINKS";
+    pub const PREK_INCLUDE_IGNORED: &'static str = "PREK_INCLUDE_IGNORED";
+    pub const PREK_SKIP_INVALID_CONFIGS: &'static str = "PREK_SKIP_INVALID_CONFIGS";
+    pub const PREK_WORKSPACE_ORDER: &'static str = "PREK_WORKSPACE_ORDER";
+    pub const PREK_CONCURRENCY: &'static str = "PREK_CONCURRENCY";
+    pub const PREK_CLONE_CONCURRENCY: &'static str = "PREK_CLONE_CONCURRENCY";
+    pub const PREK_CLONE_RETRIES: &'static str = "PREK_CLONE_RETRIES";
+    pub const PREK_CLONE_RETRY_BACKOFF: &'static str = "PREK_CLONE_RETRY_BACKOFF";
+    pub const PREK_TREAT_UNKNOWN_LANGUAGE_AS_ERROR: &'static str =
+        "PREK_TREAT_UNKNOWN_LANGUAGE_AS_ERROR";
 
     // PREK internal environment variables
     pub const PREK_INTERNAL__TEST_DIR: &'static str = "PREK_INTERNAL__TEST_DIR";
@@ -39,6 +53,11 @@ impl EnvVars {
     pub const GOROOT: &'static str = "GOROOT";
     pub const GOPATH: &'static str = "GOPATH";
     pub const GOBIN: &'static str = "GOBIN";
+
+    // .NET related
+    pub const DOTNET_ROOT: &'static str = "DOTNET_ROOT";
+    pub const DOTNET_CLI_TELEMETRY_OPTOUT: &'static str = "DOTNET_CLI_TELEMETRY_OPTOUT";
+    pub const DOTNET_NOLOGO: &'static str = "DOTNET_NOLOGO";
 }
 
 impl EnvVars {
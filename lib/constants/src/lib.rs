@@ -3,3 +3,5 @@ pub mod env_vars;
 pub const CONFIG_FILE: &str = ".pre-commit-config.yaml";
 pub const ALT_CONFIG_FILE: &str = ".pre-commit-config.yml";
 pub const MANIFEST_FILE: &str = ".pre-commit-hooks.yaml";
+pub const WORKSPACE_MANIFEST_FILE: &str = ".prek-workspace.yaml";
+pub const PREKIGNORE_FILE: &str = ".prekignore";
@@ -1,5 +1,6 @@
 use std::io::Write;
 use std::path::Path;
+use std::sync::Arc;
 
 use anyhow::Result;
 use fancy_regex::Regex;
@@ -8,6 +9,7 @@ use itertools::Itertools;
 use crate::cli::run::{CollectOptions, FileFilter, collect_files};
 use crate::config::{self, HookOptions, Language};
 use crate::hook::Hook;
+use crate::run;
 use crate::store::STORE;
 use crate::workspace::Project;
 
@@ -21,10 +23,16 @@ use crate::workspace::Project;
 /// Ensures that the configured hooks apply to at least one file in the repository.
 pub(crate) async fn check_hooks_apply(hook: &Hook, filenames: &[&Path]) -> Result<(i32, Vec<u8>)> {
     let store = STORE.as_ref()?;
+    let stage = run::hook_stage();
 
     let relative_path = hook.project().relative_path();
     // Collect all files in the project
-    let input = collect_files(hook.work_dir(), CollectOptions::all_files()).await?;
+    let input = collect_files(
+        hook.project().git_root(),
+        hook.work_dir(),
+        CollectOptions::all_files(),
+    )
+    .await?;
     // Prepend the project relative path to each input file
     let input: Vec<_> = input.into_iter().map(|f| relative_path.join(f)).collect();
 
@@ -33,16 +41,21 @@ pub(crate) async fn check_hooks_apply(hook: &Hook, filenames: &[&Path]) -> Resul
 
     for filename in filenames {
         let path = relative_path.join(filename);
-        let mut project = Project::from_config_file(path.into(), None)?;
+        let mut project = Project::from_config_file(path.into(), None, hook.project().git_root())?;
         project.with_relative_path(relative_path.to_path_buf());
+        let mut project = Arc::new(project);
 
         let project_hooks = project.init_hooks(store, None).await?;
-        let filter = FileFilter::for_project(input.iter(), &project);
+        let filter = FileFilter::for_project(input.iter(), &project, &[]);
 
         for project_hook in project_hooks {
             if project_hook.always_run || matches!(project_hook.language, Language::Fail) {
                 continue;
             }
+            // Hooks of other stages are not run now, whether they match files or not.
+            if !project_hook.stages.contains(stage) {
+                continue;
+            }
 
             let filenames = filter.for_hook(&project_hook);
 
@@ -50,8 +63,9 @@ pub(crate) async fn check_hooks_apply(hook: &Hook, filenames: &[&Path]) -> Resul
                 code = 1;
                 writeln!(
                     &mut output,
-                    "{} does not apply to this repository",
-                    project_hook.id
+                    "{} (repo: {}) does not apply to this repository",
+                    project_hook.id,
+                    project_hook.repo()
                 )?;
             }
         }
@@ -95,7 +109,12 @@ pub(crate) async fn check_useless_excludes(
     filenames: &[&Path],
 ) -> Result<(i32, Vec<u8>)> {
     let relative_path = hook.project().relative_path();
-    let input = collect_files(hook.work_dir(), CollectOptions::all_files()).await?;
+    let input = collect_files(
+        hook.project().git_root(),
+        hook.work_dir(),
+        CollectOptions::all_files(),
+    )
+    .await?;
     let input: Vec<_> = input.into_iter().map(|f| relative_path.join(f)).collect();
 
     let mut code = 0;
@@ -103,7 +122,7 @@ pub(crate) async fn check_useless_excludes(
 
     for filename in filenames {
         let path = relative_path.join(filename);
-        let mut project = Project::from_config_file(path.into(), None)?;
+        let mut project = Project::from_config_file(path.into(), None, hook.project().git_root())?;
         project.with_relative_path(relative_path.to_path_buf());
 
         let config = project.config();
@@ -116,11 +135,12 @@ pub(crate) async fn check_useless_excludes(
             )?;
         }
 
-        let filter = FileFilter::for_project(input.iter(), &project);
+        let filter = FileFilter::for_project(input.iter(), &project, &[]);
 
         for repo in &config.repos {
             let hooks_iter: Box<dyn Iterator<Item = (&String, &HookOptions)>> = match repo {
                 config::Repo::Remote(r) => Box::new(r.hooks.iter().map(|h| (&h.id, &h.options))),
+                config::Repo::Path(r) => Box::new(r.hooks.iter().map(|h| (&h.id, &h.options))),
                 config::Repo::Local(r) => Box::new(r.hooks.iter().map(|h| (&h.id, &h.options))),
                 config::Repo::Meta(r) => Box::new(r.hooks.iter().map(|h| (&h.0.id, &h.0.options))),
             };
@@ -6,7 +6,7 @@ use rustc_hash::FxHashSet;
 
 use crate::git::{get_lfs_files, get_staged_files};
 use crate::hook::Hook;
-use crate::run::CONCURRENCY;
+use crate::run::target_concurrency;
 
 enum FileFilter {
     NoFilter,
@@ -70,7 +70,7 @@ pub(crate) async fn check_added_large_files(
             anyhow::Ok(None)
         }
     })
-    .buffered(*CONCURRENCY);
+    .buffered(target_concurrency(hook));
 
     let mut code = 0;
     let mut output = Vec::new();
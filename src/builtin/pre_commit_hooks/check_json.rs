@@ -6,7 +6,7 @@ use rustc_hash::FxHashMap;
 use serde::{Deserialize, Deserializer};
 
 use crate::hook::Hook;
-use crate::run::CONCURRENCY;
+use crate::run::target_concurrency;
 
 #[derive(Debug)]
 enum JsonValue {
@@ -21,7 +21,7 @@ enum JsonValue {
 pub(crate) async fn check_json(hook: &Hook, filenames: &[&Path]) -> Result<(i32, Vec<u8>)> {
     let mut tasks = futures::stream::iter(filenames)
         .map(async |filename| check_file(hook.project().relative_path(), filename).await)
-        .buffered(*CONCURRENCY);
+        .buffered(target_concurrency(hook));
 
     let mut code = 0;
     let mut output = Vec::new();
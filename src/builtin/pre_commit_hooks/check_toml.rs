@@ -4,12 +4,12 @@ use anyhow::Result;
 use futures::StreamExt;
 
 use crate::hook::Hook;
-use crate::run::CONCURRENCY;
+use crate::run::target_concurrency;
 
 pub(crate) async fn check_toml(hook: &Hook, filenames: &[&Path]) -> Result<(i32, Vec<u8>)> {
     let mut tasks = futures::stream::iter(filenames)
         .map(async |filename| check_file(hook.project().relative_path(), filename).await)
-        .buffered(*CONCURRENCY);
+        .buffered(target_concurrency(hook));
 
     let mut code = 0;
     let mut output = Vec::new();
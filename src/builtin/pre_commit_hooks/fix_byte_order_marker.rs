@@ -5,7 +5,7 @@ use futures::StreamExt;
 use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
 
 use crate::hook::Hook;
-use crate::run::CONCURRENCY;
+use crate::run::target_concurrency;
 use crate::store::STORE;
 
 const UTF8_BOM: &[u8] = b"\xef\xbb\xbf";
@@ -17,7 +17,7 @@ pub(crate) async fn fix_byte_order_marker(
 ) -> Result<(i32, Vec<u8>)> {
     let mut tasks = futures::stream::iter(filenames)
         .map(async |filename| fix_file(hook.project().relative_path(), filename).await)
-        .buffered(*CONCURRENCY);
+        .buffered(target_concurrency(hook));
 
     let mut code = 0;
     let mut output = Vec::new();
@@ -5,12 +5,12 @@ use futures::StreamExt;
 use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt, AsyncWriteExt, SeekFrom};
 
 use crate::hook::Hook;
-use crate::run::CONCURRENCY;
+use crate::run::target_concurrency;
 
 pub(crate) async fn fix_end_of_file(hook: &Hook, filenames: &[&Path]) -> Result<(i32, Vec<u8>)> {
     let mut tasks = futures::stream::iter(filenames)
         .map(async |filename| fix_file(hook.project().relative_path(), filename).await)
-        .buffered(*CONCURRENCY);
+        .buffered(target_concurrency(hook));
 
     let mut code = 0;
     let mut output = Vec::new();
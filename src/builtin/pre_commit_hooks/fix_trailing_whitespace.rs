@@ -10,7 +10,7 @@ use tempfile::NamedTempFile;
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, BufWriter};
 
 use crate::hook::Hook;
-use crate::run::CONCURRENCY;
+use crate::run::target_concurrency;
 
 const MARKDOWN_LINE_BREAK: &[u8] = b"  ";
 const BUFFER_SIZE_THRESHOLD: usize = 64 * 1024; // 64KB
@@ -95,7 +95,7 @@ pub(crate) async fn fix_trailing_whitespace(
             )
             .await
         })
-        .buffered(*CONCURRENCY);
+        .buffered(target_concurrency(hook));
 
     let mut code = 0;
     let mut output = Vec::new();
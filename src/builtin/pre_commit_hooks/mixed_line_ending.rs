@@ -6,7 +6,7 @@ use futures::StreamExt;
 use rustc_hash::FxHashMap;
 
 use crate::hook::Hook;
-use crate::run::CONCURRENCY;
+use crate::run::target_concurrency;
 
 const CRLF: &[u8] = b"\r\n";
 const LF: &[u8] = b"\n";
@@ -28,7 +28,7 @@ pub(crate) async fn mixed_line_ending(hook: &Hook, filenames: &[&Path]) -> Resul
 
     let mut results = futures::stream::iter(filenames)
         .map(|filename| fix_file(hook.project().relative_path(), filename, &fix_mode))
-        .buffered(*CONCURRENCY);
+        .buffered(target_concurrency(hook));
 
     let mut exit_code = 0;
     let mut output = Vec::new();
@@ -19,6 +19,7 @@ use crate::cli::ExitStatus;
 use crate::cli::reporter::AutoUpdateReporter;
 use crate::config::{RemoteRepo, Repo};
 use crate::fs::CWD;
+use crate::git::GIT_ROOT;
 use crate::printer::Printer;
 use crate::run::CONCURRENCY;
 use crate::workspace::{Project, Workspace};
@@ -44,12 +45,12 @@ pub(crate) async fn auto_update(
         remote_index: usize,
     }
 
-    let workspace_root = Workspace::find_root(config.as_deref(), &CWD)?;
+    let git_root = GIT_ROOT.as_ref()?;
+    let workspace_root = Workspace::find_root(git_root, config.as_deref(), &CWD)?;
     // TODO: support selectors?
-    let workspace = Workspace::discover(workspace_root, config, None, true)?;
+    let workspace = Workspace::discover(git_root, workspace_root, config, None, true)?;
 
     // Collect repos and deduplicate by RemoteRepo
-    #[allow(clippy::mutable_key_type)]
     let mut repo_updates: FxHashMap<&RemoteRepo, Vec<RepoInfo>> = FxHashMap::default();
 
     for project in workspace.projects() {
@@ -1,37 +1,233 @@
 use std::fmt::Write;
-use std::io;
-use std::path::Path;
+use std::io::{self, IsTerminal};
+use std::path::{Path, PathBuf};
 
 use anyhow::Result;
 use owo_colors::OwoColorize;
-use tracing::error;
+use path_clean::PathClean;
+use rustc_hash::FxHashSet;
+use tracing::{debug, error};
 
 use crate::cli::ExitStatus;
-use crate::fs::Simplified;
+use crate::fs::{CWD, Simplified};
+use crate::git::GIT_ROOT;
+use crate::hook::InstallInfo;
 use crate::printer::Printer;
 use crate::store::{CacheBucket, STORE};
+use crate::workspace::Workspace;
 
-pub(crate) fn clean(printer: Printer) -> Result<ExitStatus> {
+/// Remove the whole store, unlike [`gc`] which only removes what is not used anymore.
+pub(crate) async fn clean(yes: bool, printer: Printer) -> Result<ExitStatus> {
     let store = STORE.as_ref()?;
     if !store.path().exists() {
         writeln!(printer.stdout(), "Nothing to clean")?;
         return Ok(ExitStatus::Success);
     }
 
+    // Guard against a misconfigured `PREK_HOME` wiping out unrelated files.
+    let path = std::path::absolute(store.path())?.clean();
+    let home = etcetera::home_dir().ok();
+    if path.parent().is_none() || home.is_some_and(|home| home.clean() == path) {
+        anyhow::bail!(
+            "Refusing to remove `{}`, it does not look like a prek cache directory",
+            path.display().cyan()
+        );
+    }
+
+    if !yes && !confirm(store.path(), printer)? {
+        writeln!(printer.stderr(), "Aborted")?;
+        return Ok(ExitStatus::Failure);
+    }
+
+    // Wait for other prek processes using the store, like gc does.
+    let lock = store.lock_async().await?;
+
     if let Err(e) = fix_permissions(store.cache_path(CacheBucket::Go)) {
         error!("Failed to fix permissions: {}", e);
     }
 
-    fs_err::remove_dir_all(store.path())?;
+    let freed = dir_size(store.path());
+    let lock_path = store.path().join(".lock");
+    for entry in fs_err::read_dir(store.path())? {
+        let path = entry?.path();
+        if path == lock_path {
+            continue;
+        }
+        if path.is_dir() && !path.is_symlink() {
+            fs_err::remove_dir_all(&path)?;
+        } else {
+            fs_err::remove_file(&path)?;
+        }
+    }
+    // A held lock file can't be removed on Windows, the empty store is left in place then.
+    // The store directory itself is only removed if no other process started using it.
+    if let Err(e) = fs_err::remove_file(&lock_path) {
+        debug!("Failed to remove the store lock: {e}");
+    }
+    drop(lock);
+    if let Err(e) = fs_err::remove_dir(store.path()) {
+        debug!("Failed to remove the store directory: {e}");
+    }
+    writeln!(
+        printer.stdout(),
+        "Cleaned `{}`, freed {}",
+        store.path().user_display().cyan(),
+        human_readable_bytes(freed).cyan()
+    )?;
+
+    Ok(ExitStatus::Success)
+}
+
+/// Ask whether to remove the store, which requires a terminal.
+fn confirm(path: &Path, printer: Printer) -> Result<bool> {
+    if !io::stdin().is_terminal() {
+        anyhow::bail!(
+            "Refusing to remove `{}` without confirmation, pass `{}` to remove it",
+            path.user_display().cyan(),
+            "--yes".cyan()
+        );
+    }
+
+    write!(
+        printer.stderr(),
+        "Remove `{}` with all cached repos and hook environments? [y/N] ",
+        path.user_display().cyan()
+    )?;
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
+
+/// The `repo` and `rev` of a cloned repo, read from its `.prek-repo.json`.
+#[derive(serde::Deserialize)]
+struct CachedRepo {
+    repo: String,
+    rev: String,
+}
+
+/// Remove the cached repos that are not used by the current workspace,
+/// and the hook environments installed from them.
+///
+/// Only the repos that the workspace uses at another `rev` are considered,
+/// unless `all` is set, in which case every repo the workspace doesn't use is removed.
+pub(crate) async fn gc(config: Option<PathBuf>, all: bool, printer: Printer) -> Result<ExitStatus> {
+    let git_root = GIT_ROOT.as_ref()?;
+    let workspace_root = Workspace::find_root(git_root, config.as_deref(), &CWD)?;
+    let workspace = Workspace::discover(git_root, workspace_root, config, None, false)?;
+
+    let mut used = FxHashSet::default();
+    for project in workspace.projects() {
+        for repo in project.config().remote_repos() {
+            used.insert((repo.repo.clone(), repo.rev.clone()));
+        }
+    }
+    let used_urls: FxHashSet<_> = used.iter().map(|(repo, _)| repo.as_str()).collect();
+
+    let store = STORE.as_ref()?;
+    let _lock = store.lock_async().await?;
+
+    // Hook environments depend on their repo as `<repo>@<rev>`.
+    let mut removed_repos = FxHashSet::default();
+    let mut freed = 0;
+    for entry in fs_err::read_dir(store.repos_dir()).into_iter().flatten() {
+        let path = entry?.path();
+        let Some(repo) = fs_err::read(path.join(".prek-repo.json"))
+            .ok()
+            .and_then(|content| serde_json::from_slice::<CachedRepo>(&content).ok())
+        else {
+            continue;
+        };
+        if used.contains(&(repo.repo.clone(), repo.rev.clone()))
+            || (!all && !used_urls.contains(repo.repo.as_str()))
+        {
+            continue;
+        }
+
+        debug!("Removing unused repo `{}@{}`", repo.repo, repo.rev);
+        freed += dir_size(&path);
+        fs_err::remove_dir_all(&path)?;
+        removed_repos.insert(format!("{}@{}", repo.repo, repo.rev));
+    }
+
+    let mut removed_envs = 0;
+    for entry in fs_err::read_dir(store.hooks_dir()).into_iter().flatten() {
+        let path = entry?.path();
+        let Some(info) = fs_err::read(path.join(".prek-hook.json"))
+            .ok()
+            .and_then(|content| serde_json::from_slice::<InstallInfo>(&content).ok())
+        else {
+            continue;
+        };
+        if !info
+            .dependencies
+            .iter()
+            .any(|dep| removed_repos.contains(dep))
+        {
+            continue;
+        }
+
+        debug!("Removing unused environment `{}`", path.display());
+        freed += dir_size(&path);
+        fs_err::remove_dir_all(&path)?;
+        removed_envs += 1;
+    }
+
+    if removed_repos.is_empty() && removed_envs == 0 {
+        writeln!(printer.stdout(), "Nothing to clean")?;
+        return Ok(ExitStatus::Success);
+    }
+
     writeln!(
         printer.stdout(),
-        "Cleaned `{}`",
-        store.path().user_display().cyan()
+        "Removed {} and {}, freed {}",
+        plural(removed_repos.len(), "repo"),
+        plural(removed_envs, "environment"),
+        human_readable_bytes(freed).cyan()
     )?;
 
     Ok(ExitStatus::Success)
 }
 
+fn plural(count: usize, noun: &str) -> String {
+    if count == 1 {
+        format!("1 {noun}")
+    } else {
+        format!("{count} {noun}s")
+    }
+}
+
+/// The total size of the files in a directory, without following symlinks.
+fn dir_size(path: &Path) -> u64 {
+    let Ok(metadata) = fs_err::symlink_metadata(path) else {
+        return 0;
+    };
+    if !metadata.is_dir() {
+        return metadata.len();
+    }
+    fs_err::read_dir(path)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| dir_size(&entry.path()))
+        .sum()
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn human_readable_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{size:.1} {}", UNITS[unit])
+    }
+}
+
 /// Add write permission to GOMODCACHE directory recursively.
 /// Go sets the permissions to read-only by default.
 #[cfg(not(windows))]
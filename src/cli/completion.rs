@@ -7,6 +7,7 @@ use clap_complete::CompletionCandidate;
 
 use crate::config;
 use crate::fs::CWD;
+use crate::git::GIT_ROOT;
 use crate::workspace::{Project, Workspace};
 
 /// Provide completion candidates for `include` and `skip` selectors.
@@ -15,8 +16,11 @@ pub(crate) fn selector_completer(current: &OsStr) -> Vec<CompletionCandidate> {
         return vec![];
     };
 
-    let Ok(workspace) = Workspace::find_root(None, &CWD)
-        .and_then(|root| Workspace::discover(root, None, None, false))
+    let Ok(git_root) = GIT_ROOT.as_ref() else {
+        return vec![];
+    };
+    let Ok(workspace) = Workspace::find_root(git_root, None, &CWD)
+        .and_then(|root| Workspace::discover(git_root, root, None, None, false))
     else {
         return vec![];
     };
@@ -159,6 +163,11 @@ fn all_hooks(proj: &Project) -> Vec<(String, Option<String>)> {
                     out.push((h.id.clone(), h.name.as_ref().map(ToString::to_string)));
                 }
             }
+            config::Repo::Path(cfg) => {
+                for h in &cfg.hooks {
+                    out.push((h.id.clone(), h.name.as_ref().map(ToString::to_string)));
+                }
+            }
             config::Repo::Local(cfg) => {
                 for h in &cfg.hooks {
                     out.push((h.id.clone(), Some(h.name.clone())));
@@ -8,9 +8,11 @@ use owo_colors::OwoColorize;
 
 use constants::env_vars::EnvVars;
 
+use crate::cli::run::RunOptions;
 use crate::cli::{self, ExitStatus, RunArgs};
 use crate::config::HookType;
 use crate::fs::CWD;
+use crate::git::GIT_ROOT;
 use crate::printer::Printer;
 use crate::workspace;
 use crate::workspace::Project;
@@ -69,7 +71,8 @@ pub(crate) async fn hook_impl(
         }
     } else {
         // Try to discover a project from current directory (after `--cd`)
-        match Project::discover(config.as_deref(), &CWD) {
+        let git_root = GIT_ROOT.as_ref()?;
+        match Project::discover(git_root, config.as_deref(), &CWD) {
             Err(e) if matches!(e, workspace::Error::MissingPreCommitConfig) => {
                 return if allow_missing_config {
                     Ok(ExitStatus::Success)
@@ -95,21 +98,15 @@ pub(crate) async fn hook_impl(
     };
 
     cli::run(
-        config,
-        includes,
-        skips,
-        hook_type.into(),
-        run_args.from_ref,
-        run_args.to_ref,
-        run_args.all_files,
-        vec![],
-        vec![],
-        false, // last_commit is always false in hook implementation context
-        false,
-        false,
-        false,
-        run_args.extra,
-        false,
+        GIT_ROOT.as_ref()?,
+        &CWD,
+        RunOptions {
+            includes,
+            skips,
+            hook_stage: hook_type.into(),
+            hook_repeat: 1,
+            ..RunOptions::new(run_args, config, false, false)
+        },
         printer,
     )
     .await
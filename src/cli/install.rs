@@ -13,7 +13,7 @@ use crate::cli::run;
 use crate::cli::run::{SelectorSource, Selectors};
 use crate::cli::{ExitStatus, HookType};
 use crate::fs::{CWD, Simplified};
-use crate::git::git_cmd;
+use crate::git::{GIT_ROOT, git_cmd};
 use crate::printer::Printer;
 use crate::store::STORE;
 use crate::workspace::{Project, Workspace};
@@ -32,6 +32,9 @@ pub(crate) async fn install(
     printer: Printer,
     git_dir: Option<&Path>,
 ) -> Result<ExitStatus> {
+    if git_dir.is_none() && GIT_ROOT.is_err() {
+        anyhow::bail!("Cannot install hooks outside of a git repository");
+    }
     if git_dir.is_none() && git::has_hooks_path_set().await? {
         anyhow::bail!(
             "Cowardly refusing to install hooks with `core.hooksPath` set.\nhint: `{}` to fix this",
@@ -39,7 +42,10 @@ pub(crate) async fn install(
         );
     }
 
-    let project = Project::discover(config.as_deref(), &CWD).ok();
+    let project = GIT_ROOT
+        .as_ref()
+        .ok()
+        .and_then(|git_root| Project::discover(git_root, config.as_deref(), &CWD).ok());
     let hook_types = get_hook_types(project.as_ref(), hook_types);
 
     let hooks_path = if let Some(dir) = git_dir {
@@ -84,9 +90,11 @@ pub(crate) async fn install_hooks(
     refresh: bool,
     printer: Printer,
 ) -> Result<ExitStatus> {
-    let workspace_root = Workspace::find_root(config.as_deref(), &CWD)?;
+    let git_root = GIT_ROOT.as_ref()?;
+    let workspace_root = Workspace::find_root(git_root, config.as_deref(), &CWD)?;
     let selectors = Selectors::load(&includes, &skips, &workspace_root)?;
-    let mut workspace = Workspace::discover(workspace_root, config, Some(&selectors), refresh)?;
+    let mut workspace =
+        Workspace::discover(git_root, workspace_root, config, Some(&selectors), refresh)?;
 
     let store = STORE.as_ref()?;
     let reporter = HookInitReporter::from(printer);
@@ -282,7 +290,13 @@ pub(crate) async fn uninstall(
     hook_types: Vec<HookType>,
     printer: Printer,
 ) -> Result<ExitStatus> {
-    let project = Project::discover(config.as_deref(), &CWD).ok();
+    if GIT_ROOT.is_err() {
+        anyhow::bail!("Cannot uninstall hooks outside of a git repository");
+    }
+    let project = GIT_ROOT
+        .as_ref()
+        .ok()
+        .and_then(|git_root| Project::discover(git_root, config.as_deref(), &CWD).ok());
 
     for hook_type in get_hook_types(project.as_ref(), hook_types) {
         let hooks_path = git::get_git_common_dir().await?.join("hooks");
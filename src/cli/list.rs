@@ -2,15 +2,18 @@ use std::fmt::Write;
 use std::path::PathBuf;
 
 use clap::ValueEnum;
+use itertools::Itertools;
 use owo_colors::OwoColorize;
 use serde::Serialize;
+use unicode_width::UnicodeWidthStr;
 
 use crate::cli::reporter::HookInitReporter;
 use crate::cli::run::Selectors;
 use crate::cli::{ExitStatus, ListOutputFormat};
 use crate::config::{Language, Stage};
 use crate::fs::CWD;
-use crate::hook;
+use crate::git::GIT_ROOT;
+use crate::hook::{self, Hook, Repo};
 use crate::printer::Printer;
 use crate::store::STORE;
 use crate::workspace::Workspace;
@@ -24,6 +27,157 @@ struct SerializableHook {
     language: Language,
     description: Option<String>,
     stages: Vec<Stage>,
+    project: String,
+    /// The url or path of the repo, `local` or `meta`.
+    repo: String,
+    rev: Option<String>,
+}
+
+/// The url or path of the repo of the hook, and the rev of remote repos.
+fn repo_source(hook: &Hook) -> (String, Option<String>) {
+    match hook.repo() {
+        Repo::Remote { url, rev, .. } => (url.clone(), Some(rev.clone())),
+        Repo::Path { path, .. } => (path.display().to_string(), None),
+        Repo::Local { .. } => ("local".to_string(), None),
+        Repo::Meta { .. } => ("meta".to_string(), None),
+    }
+}
+
+#[derive(Serialize)]
+struct SerializableProject {
+    /// The path relative to the workspace root, `.` for the root.
+    path: String,
+    depth: usize,
+    /// The config file, relative to the workspace root.
+    config: String,
+    /// The ids of the hooks the project would run.
+    hooks: Vec<String>,
+}
+
+/// Print the rows aligned under their headers.
+fn write_table<const N: usize>(
+    headers: [&str; N],
+    rows: &[[String; N]],
+    printer: Printer,
+) -> anyhow::Result<()> {
+    let mut widths = headers.map(str::width);
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.width());
+        }
+    }
+    let format_row = |cells: [&str; N]| {
+        cells
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{cell}{}", " ".repeat(width - cell.width())))
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+
+    writeln!(printer.stdout(), "{}", format_row(headers).bold())?;
+    for row in rows {
+        writeln!(
+            printer.stdout(),
+            "{}",
+            format_row(row.each_ref().map(String::as_str))
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Print the projects in the order they run, with the hooks each of them would run.
+///
+/// Projects without any selected hook are left out, as they would not run either.
+fn write_projects(
+    workspace: &Workspace,
+    hooks: &[Hook],
+    output_format: ListOutputFormat,
+    printer: Printer,
+) -> anyhow::Result<()> {
+    let projects: Vec<_> = workspace
+        .projects()
+        .iter()
+        .filter_map(|project| {
+            let hooks: Vec<_> = hooks
+                .iter()
+                .filter(|hook| hook.project().idx() == project.idx())
+                .map(|hook| hook.id.clone())
+                .collect();
+            if hooks.is_empty() {
+                return None;
+            }
+            let config = project.config_file();
+            let config = config.strip_prefix(workspace.root()).unwrap_or(config);
+            Some(SerializableProject {
+                path: project.to_string(),
+                depth: project.depth(),
+                config: config.display().to_string(),
+                hooks,
+            })
+        })
+        .collect();
+
+    match output_format {
+        ListOutputFormat::Text | ListOutputFormat::Table => {
+            let rows: Vec<[String; 4]> = projects
+                .into_iter()
+                .map(|project| {
+                    [
+                        project.path,
+                        project.depth.to_string(),
+                        project.config,
+                        project.hooks.join(", "),
+                    ]
+                })
+                .collect();
+            write_table(["PROJECT", "DEPTH", "CONFIG", "HOOKS"], &rows, printer)?;
+        }
+        ListOutputFormat::Json => {
+            let json_output = serde_json::to_string_pretty(&projects)?;
+            writeln!(printer.stdout(), "{json_output}")?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Print the hooks of each project as a table.
+fn write_tables(hooks: &[Hook], printer: Printer) -> anyhow::Result<()> {
+    const HEADERS: [&str; 5] = ["ID", "ALIAS", "LANGUAGE", "STAGES", "REPO"];
+
+    let chunks = hooks.iter().chunk_by(|hook| hook.project().to_string());
+    for (idx, (project, hooks)) in chunks.into_iter().enumerate() {
+        let rows: Vec<[String; 5]> = hooks
+            .map(|hook| {
+                [
+                    hook.id.clone(),
+                    if hook.alias.is_empty() || hook.alias == hook.id {
+                        "-".to_string()
+                    } else {
+                        hook.alias.clone()
+                    },
+                    hook.language.as_str().to_string(),
+                    hook.stages.to_string(),
+                    hook.repo().to_string(),
+                ]
+            })
+            .collect();
+
+        if idx > 0 {
+            writeln!(printer.stdout())?;
+        }
+        writeln!(
+            printer.stdout(),
+            "{}",
+            format!("Project `{project}`:").bold()
+        )?;
+        write_table(HEADERS, &rows, printer)?;
+    }
+
+    Ok(())
 }
 
 pub(crate) async fn list(
@@ -33,13 +187,16 @@ pub(crate) async fn list(
     hook_stage: Option<Stage>,
     language: Option<Language>,
     output_format: ListOutputFormat,
+    projects: bool,
     refresh: bool,
     verbose: bool,
     printer: Printer,
 ) -> anyhow::Result<ExitStatus> {
-    let workspace_root = Workspace::find_root(config.as_deref(), &CWD)?;
+    let git_root = GIT_ROOT.as_ref()?;
+    let workspace_root = Workspace::find_root(git_root, config.as_deref(), &CWD)?;
     let selectors = Selectors::load(&includes, &skips, &workspace_root)?;
-    let mut workspace = Workspace::discover(workspace_root, config, Some(&selectors), refresh)?;
+    let mut workspace =
+        Workspace::discover(git_root, workspace_root, config, Some(&selectors), refresh)?;
 
     let store = STORE.as_ref()?;
     let reporter = HookInitReporter::from(printer);
@@ -57,6 +214,11 @@ pub(crate) async fn list(
 
     selectors.report_unused();
 
+    if projects {
+        write_projects(&workspace, &filtered_hooks, output_format, printer)?;
+        return Ok(ExitStatus::Success);
+    }
+
     match output_format {
         ListOutputFormat::Text => {
             if verbose {
@@ -108,12 +270,15 @@ pub(crate) async fn list(
                 }
             }
         }
+        ListOutputFormat::Table => write_tables(&filtered_hooks, printer)?,
         ListOutputFormat::Json => {
             let serializable_hooks: Vec<_> = filtered_hooks
                 .into_iter()
                 .map(|h| {
                     let id = h.id.to_string();
                     let full_id = h.full_id();
+                    let project = h.project().to_string();
+                    let (repo, rev) = repo_source(&h);
                     let stages = match h.stages {
                         hook::Stages::All => Stage::value_variants().to_vec(),
                         hook::Stages::Some(s) => s.into_iter().collect(),
@@ -126,6 +291,9 @@ pub(crate) async fn list(
                         language: h.language,
                         description: h.description,
                         stages,
+                        project,
+                        repo,
+                        rev,
                     }
                 })
                 .collect();
@@ -1,9 +1,10 @@
 use std::ffi::OsString;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::ExitCode;
+use std::time::Duration;
 
+use clap::builder::Styles;
 use clap::builder::styling::{AnsiColor, Effects};
-use clap::builder::{ArgPredicate, Styles};
 use clap::{ArgAction, Args, Parser, Subcommand, ValueHint};
 use clap_complete::engine::ArgValueCompleter;
 use serde::{Deserialize, Serialize};
@@ -12,6 +13,7 @@ use constants::CONFIG_FILE;
 use constants::env_vars::EnvVars;
 
 use crate::config::{HookType, Language, Stage};
+use crate::workspace::WorkspaceOrder;
 
 mod auto_update;
 mod clean;
@@ -24,10 +26,11 @@ pub mod run;
 mod sample_config;
 #[cfg(feature = "self-update")]
 mod self_update;
+mod try_repo;
 mod validate;
 
 pub(crate) use auto_update::auto_update;
-pub(crate) use clean::clean;
+pub(crate) use clean::{clean, gc};
 use completion::selector_completer;
 pub(crate) use hook_impl::hook_impl;
 pub(crate) use install::{init_template_dir, install, install_hooks, uninstall};
@@ -36,6 +39,7 @@ pub(crate) use run::run;
 pub(crate) use sample_config::sample_config;
 #[cfg(feature = "self-update")]
 pub(crate) use self_update::self_update;
+pub(crate) use try_repo::try_repo;
 pub(crate) use validate::{validate_configs, validate_manifest};
 
 #[derive(Copy, Clone)]
@@ -80,6 +84,21 @@ pub enum ColorChoice {
     Never,
 }
 
+impl ColorChoice {
+    /// Resolve the color choice for the current environment.
+    ///
+    /// `anstream` handles `NO_COLOR`, `CLICOLOR` and terminal detection for `auto`, but still
+    /// enables colors in CI even if the terminal is dumb, so `TERM=dumb` is checked here.
+    pub(crate) fn resolve(self) -> anstream::ColorChoice {
+        match self {
+            Self::Auto if EnvVars::var_os(EnvVars::TERM).is_some_and(|term| term == "dumb") => {
+                anstream::ColorChoice::Never
+            }
+            choice => choice.into(),
+        }
+    }
+}
+
 impl From<ColorChoice> for anstream::ColorChoice {
     fn from(value: ColorChoice) -> Self {
         match value {
@@ -126,10 +145,16 @@ pub(crate) struct Cli {
 #[allow(clippy::struct_excessive_bools)]
 pub(crate) struct GlobalArgs {
     /// Path to alternate config file.
+    ///
+    /// Use `-` to read the configuration from stdin.
     #[arg(global = true, short, long)]
     pub(crate) config: Option<PathBuf>,
 
     /// Change to directory before running.
+    ///
+    /// The project or workspace is discovered from this directory, but relative `--config`,
+    /// `--files` and `--directory` paths are still resolved from the current directory.
+    /// With `--config`, the config is used as is and `--cd` only selects the git repository.
     #[arg(
         global = true,
         short = 'C',
@@ -153,6 +178,54 @@ pub(crate) struct GlobalArgs {
     #[arg(global = true, long)]
     pub(crate) refresh: bool,
 
+    /// Disable network access when cloning repos.
+    ///
+    /// Only repos that are already cloned in the store can be used.
+    /// Can also be enabled by setting the `PREK_OFFLINE` environment variable.
+    #[arg(global = true, long)]
+    pub(crate) offline: bool,
+
+    /// Reject unknown keys in config files instead of ignoring them.
+    ///
+    /// Can also be enabled by setting the `PREK_STRICT` environment variable.
+    #[arg(global = true, long)]
+    pub(crate) strict: bool,
+
+    /// Follow symlinked directories when discovering the projects of a workspace.
+    ///
+    /// Can also be enabled by setting the `PREK_FOLLOW_SYMLINKS` environment variable,
+    /// or `follow_symlinks: true` in `.prek-workspace.yaml`.
+    #[arg(global = true, long)]
+    pub(crate) follow_symlinks: bool,
+
+    /// Also discover the projects of a workspace in directories ignored by git.
+    ///
+    /// By default, directories matched by `.gitignore`, `.git/info/exclude` or the global git
+    /// excludes are not searched. Can also be enabled by setting the `PREK_INCLUDE_IGNORED`
+    /// environment variable.
+    #[arg(global = true, long)]
+    pub(crate) include_ignored: bool,
+
+    /// Skip the projects of a workspace whose config fails to parse, instead of failing.
+    ///
+    /// A warning names each skipped config and its error, the other projects still run.
+    /// Can also be enabled by setting the `PREK_SKIP_INVALID_CONFIGS` environment variable.
+    #[arg(global = true, long)]
+    pub(crate) skip_invalid_configs: bool,
+
+    /// The order in which the projects of a workspace run.
+    ///
+    /// `deepest` runs nested projects before their parents, `root` runs the workspace root first.
+    /// Projects at the same depth are ordered by their path.
+    #[arg(
+        global = true,
+        long,
+        value_enum,
+        env = EnvVars::PREK_WORKSPACE_ORDER,
+        default_value_t = WorkspaceOrder::Deepest,
+    )]
+    pub(crate) workspace_order: WorkspaceOrder,
+
     /// Display the concise help for this command.
     #[arg(global = true, short, long, action = ArgAction::HelpShort)]
     help: (),
@@ -205,15 +278,16 @@ pub(crate) enum Command {
     /// Auto-update pre-commit config to the latest repos' versions.
     #[command(alias = "autoupdate")]
     AutoUpdate(AutoUpdateArgs),
-    /// Clean unused cached repos.
-    GC,
-    /// Clean out pre-commit files.
-    Clean,
+    /// Clean unused cached repos, keeping the ones still in use.
+    #[command(name = "gc")]
+    Gc(GcArgs),
+    /// Remove the whole cache: all cached repos and hook environments.
+    Clean(CleanArgs),
     /// Install hook script in a directory intended for use with `git config init.templateDir`.
     #[command(alias = "init-templatedir")]
     InitTemplateDir(InitTemplateDirArgs),
-    /// Try the pre-commit hooks in the current repo.
-    TryRepo(Box<RunArgs>),
+    /// Try the hooks of a repository without adding it to the configuration.
+    TryRepo(Box<TryRepoArgs>),
     /// The implementation of the `pre-commit` hook.
     #[command(hide = true)]
     HookImpl(HookImplArgs),
@@ -260,7 +334,7 @@ pub(crate) struct InstallArgs {
     pub(crate) skips: Vec<String>,
 
     /// Overwrite existing hooks.
-    #[arg(short = 'f', long)]
+    #[arg(short = 'f', long, visible_alias = "force")]
     pub(crate) overwrite: bool,
 
     /// Create hook environments for all hooks used in the config file.
@@ -379,6 +453,12 @@ pub(crate) struct RunArgs {
     #[arg(long = "skip", value_name = "HOOK|PROJECT", add = ArgValueCompleter::new(selector_completer))]
     pub(crate) skips: Vec<String>,
 
+    /// Only run the hooks of the project at the given path, relative to the workspace root.
+    ///
+    /// Can be specified multiple times. Use `.` for the project at the workspace root.
+    #[arg(long = "project", value_name = "PATH", value_hint = ValueHint::DirPath)]
+    pub(crate) projects: Vec<String>,
+
     /// Run on all files in the repo.
     #[arg(short, long, conflicts_with_all = ["files", "from_ref", "to_ref"])]
     pub(crate) all_files: bool,
@@ -403,19 +483,25 @@ pub(crate) struct RunArgs {
     pub(crate) directory: Vec<String>,
 
     /// The original ref in a `<from_ref>...<to_ref>` diff expression.
-    /// Files changed in this diff will be run through the hooks.
-    #[arg(short = 's', long, alias = "source", value_hint = ValueHint::Other)]
+    /// Files changed in this diff will be run through the hooks, files deleted in it are excluded.
+    /// Must be specified together with `--to-ref`.
+    #[arg(
+        short = 's',
+        long,
+        alias = "source",
+        requires = "to_ref",
+        value_hint = ValueHint::Other
+    )]
     pub(crate) from_ref: Option<String>,
 
     /// The destination ref in a `from_ref...to_ref` diff expression.
-    /// Defaults to `HEAD` if `from_ref` is specified.
+    /// Must be specified together with `--from-ref`.
     #[arg(
         short = 'o',
         long,
         alias = "origin",
         requires = "from_ref",
-        value_hint = ValueHint::Other,
-        default_value_if("from_ref", ArgPredicate::IsPresent, "HEAD")
+        value_hint = ValueHint::Other
     )]
     pub(crate) to_ref: Option<String>,
 
@@ -428,23 +514,373 @@ pub(crate) struct RunArgs {
     pub(crate) hook_stage: Stage,
 
     /// When hooks fail, run `git diff` directly afterward.
+    ///
+    /// Only the changes made by the hooks are shown, and nothing is shown if the hooks
+    /// didn't change any file.
     #[arg(long)]
     pub(crate) show_diff_on_failure: bool,
 
     /// Do not run the hooks, but print the hooks that would have been run.
+    ///
+    /// Shows how many files each hook would receive, and lists them with `--verbose`.
+    /// Hook environments are not installed, and the exit code is always zero.
     #[arg(long)]
     pub(crate) dry_run: bool,
 
+    /// Always run the hooks, instead of reusing the results of hooks
+    /// that already passed on the same files.
+    #[arg(long)]
+    pub(crate) no_cache: bool,
+
+    /// Run the selected hook this many times on the same files, and fail if the
+    /// results differ between runs.
+    ///
+    /// Useful to detect non-deterministic hooks. Requires selecting a single hook.
+    #[arg(long, value_name = "N", default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    pub(crate) hook_repeat: u32,
+
+    /// After running, list the files that no hook was run on, grouped by extension.
+    ///
+    /// Useful to find file types that no hook covers. With `--output-format json`, the files
+    /// are listed in the `unmatched_files` of the summary instead.
+    #[arg(long)]
+    pub(crate) report_unmatched_files: bool,
+
+    /// Also pass the files of nested projects to the hooks of their parent projects.
+    ///
+    /// By default, each file is only passed to the hooks of the deepest project containing it.
+    #[arg(long)]
+    pub(crate) include_nested_project_files: bool,
+
+    /// Stop the run once it has taken longer than this duration, for example `10m`.
+    ///
+    /// Hooks that are still running are terminated, the remaining hooks are not run,
+    /// and the run fails. Supports `ms`, `s`, `m` and `h` units, and defaults to seconds.
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+    pub(crate) max_total_duration: Option<Duration>,
+
+    /// Write the pass/fail status of each project to the given file as JSON.
+    ///
+    /// Projects without any hooks to run are reported as `empty`, and projects
+    /// that were not run because of `fail_fast` are reported as `skipped`.
+    #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+    pub(crate) per_project_status: Option<PathBuf>,
+
+    /// Run the projects in the order listed in the given file, one project per line.
+    ///
+    /// Projects are identified by their path relative to the workspace root, or `.` for the root.
+    /// Projects that are not listed run afterward, in the `--workspace-order` order.
+    #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+    pub(crate) project_order: Option<PathBuf>,
+
+    /// Re-read all configuration files, ignoring the cached workspace discovery results.
+    ///
+    /// Unlike `--refresh`, this does not refresh any other cached data.
+    #[arg(long)]
+    pub(crate) refresh_configs: bool,
+
+    /// Remove the environments of the hooks to run and install them again.
+    ///
+    /// Pass hook ids, like `--refresh-hooks=black`, to only refresh the environments of these hooks.
+    /// Useful when an environment got corrupted, as environments are otherwise reused
+    /// as long as the repo rev, language, language version and dependencies are unchanged.
+    #[arg(long, value_name = "HOOK", num_args = 0..=1, require_equals = true)]
+    pub(crate) refresh_hooks: Option<Vec<String>>,
+
+    /// Warn instead of failing when a configuration file has unstaged changes.
+    ///
+    /// Hooks are loaded from the configuration files on disk, including the unstaged changes.
+    #[arg(long)]
+    pub(crate) allow_unstaged_config: bool,
+
+    /// Stash the unstaged changes while the hooks run, so they only see the staged content.
+    ///
+    /// This is always done when running on the staged files, as the `pre-commit` git hook does.
+    /// The flag also does it with `--all-files`, `--files` and `--directory`.
+    /// The changes are restored when prek exits, including on failure and on Ctrl-C.
+    #[arg(long)]
+    pub(crate) stash_unstaged: bool,
+
+    /// Run hooks in the given git repository instead of the current one.
+    ///
+    /// Can be specified multiple times. Each repository is processed independently, from its
+    /// own git root, and the exit code is the worst of all runs.
+    #[arg(
+        long = "repo",
+        value_name = "DIR",
+        value_hint = ValueHint::DirPath,
+        conflicts_with_all = ["watch", "on_file", "print_resolved_revs"]
+    )]
+    pub(crate) repos: Vec<PathBuf>,
+
+    /// Collect the files matching each hook's `output_artifacts` glob into this directory.
+    ///
+    /// Files are copied to `<DIR>/<project>/<hook-id>/` after each hook runs.
+    #[arg(long, value_name = "DIR", value_hint = ValueHint::DirPath)]
+    pub(crate) output_dir: Option<PathBuf>,
+
+    /// The output format.
+    ///
+    /// With `json`, the result of each hook is printed to stdout as a JSON object
+    /// on its own line, as soon as the hook completes, followed by a line with a `summary`
+    /// object counting the results once all hooks have run. The `stdout` of a result is the
+    /// output of the hook, with stderr merged into it. With `junit`, a JUnit XML report is
+    /// written to the `--output` file, in addition to the text output.
+    #[arg(long, value_enum, default_value_t = RunOutputFormat::Text)]
+    pub(crate) output_format: RunOutputFormat,
+
+    /// The file to write the report to, required with `--output-format junit`.
+    #[arg(
+        long,
+        value_name = "FILE",
+        value_hint = ValueHint::FilePath,
+        required_if_eq("output_format", "junit")
+    )]
+    pub(crate) output: Option<PathBuf>,
+
+    /// Append the full output of each hook to this file, whether or not it is verbose.
+    ///
+    /// Each entry starts with a header naming the project, the hook, its exit status and
+    /// its duration. The terminal output is not changed.
+    #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+    pub(crate) log_file: Option<PathBuf>,
+
+    /// Only print a single JSON object summarizing the run once all hooks have run.
+    ///
+    /// The summary has the number of passed, failed and skipped hooks, the duration
+    /// of the run and the counts of each project. No other output is printed to stdout.
+    #[arg(long, conflicts_with_all = ["output_format", "output"])]
+    pub(crate) summary_json_only: bool,
+
+    /// Fail if a hook uses a `language` that prek does not support yet,
+    /// instead of skipping the hook.
+    ///
+    /// Can also be enabled by setting the `PREK_TREAT_UNKNOWN_LANGUAGE_AS_ERROR` environment variable.
+    #[arg(long)]
+    pub(crate) treat_unknown_language_as_error: bool,
+
+    /// Run hooks with a minimal environment, containing only `PATH` and the `PRE_COMMIT_*` variables.
+    #[arg(long)]
+    pub(crate) clean_env: bool,
+
+    /// Environment variables to pass through to hooks, separated by commas.
+    ///
+    /// Implies `--clean-env`: hooks receive only `PATH`, the `PRE_COMMIT_*` variables
+    /// and the listed variables.
+    #[arg(long, value_name = "VARS", value_delimiter = ',')]
+    pub(crate) hook_env_passthrough: Vec<String>,
+
+    /// Set an environment variable for all hooks, as `<KEY>=<VALUE>`.
+    ///
+    /// Takes precedence over the `env` of the hooks' configuration.
+    /// This option may be specified multiple times.
+    #[arg(long = "env", value_name = "KEY=VALUE", value_parser = parse_env_var)]
+    pub(crate) env: Vec<(String, String)>,
+
+    /// Run each hook in a sandbox without network access, where only the project
+    /// directory and the temporary directory are writable.
+    ///
+    /// Requires `bwrap` (bubblewrap) on Linux. If only `unshare` is available,
+    /// hooks are cut off from the network but can still write anywhere.
+    #[arg(long)]
+    pub(crate) sandbox: bool,
+
+    /// The maximum number of hook processes to run at the same time.
+    ///
+    /// Hooks run one after another, and each hook runs its files in up to this many
+    /// processes. Defaults to the number of logical CPUs. With `--jobs 1`, each hook runs
+    /// all its files in a single process, like hooks with `require_serial: true` always do.
+    /// Same as setting `PREK_JOBS`.
+    #[arg(short = 'j', long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+    pub(crate) jobs: Option<u64>,
+
+    /// Limit how many invocations of a hook run at the same time, as `<HOOK_ID>=<N>`.
+    ///
+    /// Overrides the default concurrency for the hook, other hooks are not affected.
+    /// Hooks with `require_serial: true` always run one invocation at a time.
+    /// This option may be specified multiple times.
+    #[arg(long, value_name = "HOOK_ID=N", value_parser = parse_hook_concurrency)]
+    pub(crate) hook_concurrency: Vec<(String, usize)>,
+
+    /// Kill a hook and mark it as failed if it runs longer than this duration.
+    ///
+    /// Applies to hooks without a `timeout` in their configuration. Supports `ms`, `s`,
+    /// `m` and `h` units, and defaults to seconds.
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+    pub(crate) timeout: Option<Duration>,
+
+    /// Clone remote repos one at a time instead of in parallel.
+    ///
+    /// Useful for attributing network failures to a specific repo.
+    /// Same as setting `PREK_CLONE_CONCURRENCY=1`.
+    #[arg(long)]
+    pub(crate) no_parallel_clone: bool,
+
+    /// Retry cloning a remote repo up to this many times when it fails with a network error.
+    ///
+    /// Retries wait longer each time, and `0` disables them. Errors like a missing
+    /// repository or rev are never retried. Same as setting `PREK_CLONE_RETRIES`.
+    #[arg(long, value_name = "N")]
+    pub(crate) clone_retries: Option<u32>,
+
+    /// Print the commit that each remote repo's `rev` resolved to, instead of running hooks.
+    ///
+    /// Use with `--output-format json` for machine-readable output.
+    #[arg(long)]
+    pub(crate) print_resolved_revs: bool,
+
+    /// Run the hook with the given id interactively, connected directly to the terminal.
+    ///
+    /// Same as setting `interactive: true` on the hook, can be specified multiple times.
+    #[arg(long = "interactive-hook", value_name = "HOOK")]
+    pub(crate) interactive_hooks: Vec<String>,
+
+    /// Stream the output of hooks to the terminal as they run, instead of printing it
+    /// under each hook once it completes.
+    ///
+    /// Useful for debugging slow or hanging hooks. Like `--interactive-hook`, the output is
+    /// not captured, so it is missing from reports and from `--log-file`.
+    #[arg(long)]
+    pub(crate) no_buffer: bool,
+
+    /// Run all hooks except the hook with the given id, can be specified multiple times.
+    ///
+    /// Excluded hooks are reported as skipped. Unlike `--skip`, this only accepts hook ids
+    /// and applies to the hooks of all selected projects.
+    #[arg(long = "exclude-hook", value_name = "HOOK")]
+    pub(crate) exclude_hooks: Vec<String>,
+
+    /// Keep running after the hooks finish, and run them again when files change.
+    ///
+    /// Only the changed files are checked, by the hooks of the projects containing them.
+    /// Files ignored by git are not watched. Press Ctrl-C to stop.
+    #[arg(long, conflicts_with_all = ["print_resolved_revs", "repos"])]
+    pub(crate) watch: bool,
+
+    /// Run only the hook with the given id or alias, on the file given by `--on-file`.
+    ///
+    /// Hook selection and the `files`, `exclude` and `types` filters are bypassed,
+    /// with a warning if the hook would not normally run on the file. The hook is run
+    /// in the project containing the file, and its output is streamed to the terminal.
+    #[arg(
+        long,
+        value_name = "HOOK",
+        requires = "on_file",
+        conflicts_with_all = ["watch", "repos", "print_resolved_revs"]
+    )]
+    pub(crate) hook: Option<String>,
+
+    /// The file to run the hook given by `--hook` on.
+    #[arg(long, value_name = "PATH", value_hint = ValueHint::FilePath, requires = "hook")]
+    pub(crate) on_file: Option<PathBuf>,
+
     #[command(flatten)]
     pub(crate) extra: RunExtraArgs,
 }
 
+impl RunArgs {
+    /// Resolve the relative `--files` and `--directory` paths against `base`.
+    ///
+    /// `--cd` changes the directory prek runs in, but paths given on the command line
+    /// stay relative to the directory prek was invoked from.
+    pub(crate) fn resolve_paths(&mut self, base: &Path) {
+        for path in self.files.iter_mut().chain(self.directory.iter_mut()) {
+            resolve_path(base, path);
+        }
+    }
+}
+
+/// Make a relative path absolute against `base`, paths that can't be represented as UTF-8 are
+/// left as they are.
+fn resolve_path(base: &Path, path: &mut String) {
+    if Path::new(path.as_str()).is_absolute() {
+        return;
+    }
+    if let Some(resolved) = base.join(path.as_str()).to_str() {
+        *path = resolved.to_string();
+    }
+}
+
 #[derive(Debug, Clone, Copy, clap::ValueEnum, Default, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
-pub(crate) enum ListOutputFormat {
+pub(crate) enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+/// Parse a duration like `500ms`, `90s`, `5m` or `1h`, a number without unit is in seconds.
+pub(crate) fn parse_duration(value: &str) -> Result<Duration, String> {
+    let split = value
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("invalid duration `{value}`"))?;
+    let secs = match unit {
+        "ms" => number / 1000.0,
+        "" | "s" => number,
+        "m" => number * 60.0,
+        "h" => number * 60.0 * 60.0,
+        _ => {
+            return Err(format!(
+                "invalid duration unit `{unit}`, expected `ms`, `s`, `m` or `h`"
+            ));
+        }
+    };
+    Duration::try_from_secs_f64(secs).map_err(|err| err.to_string())
+}
+
+/// Parse a `<HOOK_ID>=<N>` concurrency limit.
+fn parse_hook_concurrency(value: &str) -> Result<(String, usize), String> {
+    let (hook_id, limit) = value
+        .rsplit_once('=')
+        .ok_or_else(|| format!("expected `<HOOK_ID>=<N>`, got `{value}`"))?;
+    let limit = limit
+        .parse::<usize>()
+        .ok()
+        .filter(|limit| *limit > 0)
+        .ok_or_else(|| format!("invalid concurrency `{limit}`, expected a positive integer"))?;
+    Ok((hook_id.to_string(), limit))
+}
+
+fn parse_env_var(value: &str) -> Result<(String, String), String> {
+    let (key, value) = value
+        .split_once('=')
+        .ok_or_else(|| format!("expected `<KEY>=<VALUE>`, got `{value}`"))?;
+    if key.is_empty() {
+        return Err("environment variable name must not be empty".to_string());
+    }
+    Ok((key.to_string(), value.to_string()))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum, Default)]
+pub(crate) enum RunOutputFormat {
     #[default]
     Text,
     Json,
+    /// A JUnit XML report, for CI systems.
+    Junit,
+    /// A single JSON object summarizing the run, set by `--summary-json-only`.
+    #[value(skip)]
+    SummaryJson,
+}
+
+impl RunOutputFormat {
+    /// Whether stdout is reserved for JSON, so no human-readable output may be printed.
+    pub(crate) fn is_json(self) -> bool {
+        matches!(self, Self::Json | Self::SummaryJson)
+    }
+}
+
+impl From<RunOutputFormat> for OutputFormat {
+    fn from(format: RunOutputFormat) -> Self {
+        match format {
+            RunOutputFormat::Text | RunOutputFormat::Junit => Self::Text,
+            RunOutputFormat::Json | RunOutputFormat::SummaryJson => Self::Json,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Default, Args)]
@@ -482,19 +918,41 @@ pub(crate) struct ListArgs {
     pub(crate) skips: Vec<String>,
 
     /// Show only hooks that has the specified stage.
-    #[arg(long, value_enum)]
+    #[arg(long, value_enum, visible_alias = "stage")]
     pub(crate) hook_stage: Option<Stage>,
     /// Show only hooks that are implemented in the specified language.
     #[arg(long, value_enum)]
     pub(crate) language: Option<Language>,
     /// The output format.
+    ///
+    /// With `table`, the hooks of each project are printed as a table of their id, alias,
+    /// language, stages and source repo.
     #[arg(long, value_enum, default_value_t = ListOutputFormat::Text)]
     pub(crate) output_format: ListOutputFormat,
+    /// Print the hooks as JSON, same as `--output-format json`.
+    #[arg(long, conflicts_with = "output_format")]
+    pub(crate) json: bool,
+    /// List the projects of the workspace instead of the hooks.
+    ///
+    /// Each project is printed with its path, depth, config file and the ids of the hooks it
+    /// would run, in the order the projects run.
+    #[arg(long)]
+    pub(crate) projects: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum, Default)]
+pub(crate) enum ListOutputFormat {
+    #[default]
+    Text,
+    Table,
+    Json,
 }
 
 #[derive(Debug, Args)]
 pub(crate) struct ValidateConfigArgs {
     /// The path to the configuration file.
+    ///
+    /// Defaults to the `--config` file, or the configuration file of the current directory.
     #[arg(value_name = "CONFIG")]
     pub(crate) configs: Vec<PathBuf>,
 }
@@ -506,16 +964,54 @@ pub(crate) struct ValidateManifestArgs {
     pub(crate) manifests: Vec<PathBuf>,
 }
 
+#[derive(Debug, Args)]
+pub(crate) struct CleanArgs {
+    /// Remove the cache without asking for confirmation.
+    ///
+    /// Required when stdin is not a terminal.
+    #[arg(short, long)]
+    pub(crate) yes: bool,
+}
+
+#[derive(Debug, Args)]
+pub(crate) struct GcArgs {
+    /// Remove all cached repos that the current workspace doesn't use, along with
+    /// their hook environments.
+    ///
+    /// By default, only the repos that the workspace uses at a different `rev` are removed,
+    /// so the repos of other workspaces are kept.
+    #[arg(long)]
+    pub(crate) all: bool,
+}
+
+#[derive(Debug, Args)]
+pub(crate) struct TryRepoArgs {
+    /// The repository to source hooks from, a URL or a local path.
+    #[arg(value_name = "REPO")]
+    pub(crate) repository: String,
+
+    /// The revision to use, instead of the `HEAD` of the repository.
+    #[arg(long, alias = "ref")]
+    pub(crate) rev: Option<String>,
+
+    #[command(flatten)]
+    pub(crate) run_args: RunArgs,
+}
+
 #[derive(Debug, Args)]
 pub(crate) struct SampleConfigArgs {
     /// Write the sample config to a file (`.pre-commit-config.yaml` by default).
     #[arg(
         short,
         long,
+        visible_alias = "write",
         num_args = 0..=1,
         default_missing_value = CONFIG_FILE,
     )]
     pub(crate) file: Option<PathBuf>,
+    /// Overwrite the file if it already exists.
+    #[arg(long, requires = "file")]
+    pub(crate) force: bool,
 }
 
 #[derive(Debug, Args)]
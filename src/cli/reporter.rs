@@ -16,6 +16,8 @@ struct BarState {
     bars: FxHashMap<usize, ProgressBar>,
     /// A monotonic counter for bar IDs.
     id: usize,
+    /// Whether the bars showing bytes know their total, by ID.
+    byte_bars: FxHashMap<usize, bool>,
 }
 
 impl BarState {
@@ -59,9 +61,47 @@ impl ProgressReporter {
         id
     }
 
+    /// Show the bytes received for a task, as a bar when the total is known, or a spinner.
+    fn on_bytes(&self, id: usize, received: u64, total: Option<u64>) {
+        let mut state = self.state.lock().unwrap();
+        let restyle = state.byte_bars.insert(id, total.is_some()) != Some(total.is_some());
+        let Some(progress) = state.bars.get(&id) else {
+            return;
+        };
+
+        if restyle {
+            let template = if total.is_some() {
+                "{msg} {bar:20.cyan/dim} {binary_bytes}/{binary_total_bytes} {binary_bytes_per_sec:.dim} {eta:.dim}"
+            } else {
+                "{msg} {spinner:.white} {binary_bytes} {binary_bytes_per_sec:.dim}"
+            };
+            progress.set_style(ProgressStyle::with_template(template).unwrap());
+            if total.is_none() {
+                progress.unset_length();
+                progress.enable_steady_tick(Duration::from_millis(200));
+            }
+        }
+        if let Some(total) = total {
+            progress.set_length(total);
+        }
+        progress.set_position(received);
+    }
+
+    /// Start a task over with a new message, dropping its progress so far.
+    fn on_restart(&self, id: usize, msg: impl Into<Cow<'static, str>>) {
+        let mut state = self.state.lock().unwrap();
+        state.byte_bars.remove(&id);
+        let Some(progress) = state.bars.get(&id) else {
+            return;
+        };
+        progress.set_style(ProgressStyle::with_template("{wide_msg}").unwrap());
+        progress.set_message(msg);
+    }
+
     fn on_progress(&self, id: usize) {
         let progress = {
             let mut state = self.state.lock().unwrap();
+            state.byte_bars.remove(&id);
             state.bars.remove(&id).unwrap()
         };
 
@@ -105,6 +145,22 @@ impl workspace::HookInitReporter for HookInitReporter {
             .on_start(format!("{} {}", "Cloning".bold().cyan(), repo.dimmed()))
     }
 
+    fn on_clone_retry(&self, id: usize, repo: &str, attempt: u32, attempts: u32) {
+        self.reporter.on_restart(
+            id,
+            format!(
+                "{} {} {}",
+                "Cloning".bold().cyan(),
+                repo.dimmed(),
+                format!("(attempt {attempt}/{attempts})").yellow()
+            ),
+        );
+    }
+
+    fn on_clone_progress(&self, id: usize, received_bytes: u64, total_bytes: Option<u64>) {
+        self.reporter.on_bytes(id, received_bytes, total_bytes);
+    }
+
     fn on_clone_complete(&self, id: usize) {
         self.reporter.on_progress(id);
     }
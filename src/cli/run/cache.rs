@@ -0,0 +1,147 @@
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use tracing::trace;
+
+use constants::env_vars::EnvVars;
+
+use crate::hook::{InstalledHook, Repo};
+use crate::run;
+use crate::store::{CacheBucket, Store};
+
+/// Remembers the inputs of hooks that passed, so they are not run again on unchanged files.
+pub(crate) struct ResultCache {
+    dir: PathBuf,
+}
+
+/// Everything about a hook that can change its result, besides its input files.
+#[derive(Serialize)]
+struct HookKey<'a> {
+    version: &'a str,
+    project: &'a Path,
+    repo: String,
+    id: &'a str,
+    entry: &'a str,
+    language: &'a str,
+    language_version: &'a str,
+    args: &'a [String],
+    additional_dependencies: Vec<&'a str>,
+    env: Vec<(&'a str, &'a str)>,
+    env_path: Option<&'a Path>,
+    /// The variables kept with `--clean-env` or `--hook-env-passthrough`, with their values.
+    env_passthrough: Option<Vec<(&'a str, Option<String>)>>,
+    path: Option<String>,
+    sandbox: bool,
+}
+
+/// The cached result of a hook for a set of input files.
+pub(crate) struct CacheEntry {
+    path: PathBuf,
+    inputs: String,
+}
+
+impl ResultCache {
+    pub(crate) fn new(store: &Store) -> Self {
+        Self {
+            dir: store.cache_path(CacheBucket::Prek).join("results"),
+        }
+    }
+
+    /// Get the cache entry of a hook run on the given files.
+    ///
+    /// Returns `None` if the result of the hook may depend on anything other than its
+    /// configuration and the content of the files, in which case it must always run.
+    pub(crate) fn entry(&self, hook: &InstalledHook, filenames: &[&Path]) -> Option<CacheEntry> {
+        // Only remote repos are pinned to an immutable `rev`, local and path repos
+        // may run scripts from the working tree, and meta hooks read the config files.
+        let Repo::Remote { .. } = hook.repo() else {
+            return None;
+        };
+        if !hook.pass_filenames || hook.always_run || hook.interactive || filenames.is_empty() {
+            return None;
+        }
+
+        let mut additional_dependencies: Vec<_> = hook
+            .additional_dependencies
+            .iter()
+            .map(String::as_str)
+            .collect();
+        additional_dependencies.sort_unstable();
+
+        let key = HookKey {
+            version: env!("CARGO_PKG_VERSION"),
+            project: hook.project().path(),
+            repo: hook.repo().to_string(),
+            id: &hook.id,
+            entry: hook.entry.raw(),
+            language: hook.language.as_str(),
+            language_version: &hook.language_version,
+            args: &hook.args,
+            additional_dependencies,
+            env: run::hook_env(hook),
+            env_path: hook.env_path(),
+            env_passthrough: run::hook_env_passthrough().map(|vars| {
+                vars.iter()
+                    .map(|var| (var.as_str(), env_value(var)))
+                    .collect()
+            }),
+            path: env_value(EnvVars::PATH),
+            sandbox: run::hook_sandboxed(),
+        };
+        let key = match serde_json::to_vec(&key) {
+            Ok(key) => key,
+            Err(err) => {
+                trace!("Not caching hook `{hook}`: {err}");
+                return None;
+            }
+        };
+
+        let mut filenames = filenames.to_vec();
+        filenames.sort_unstable();
+        let mut hasher = Sha256::new();
+        for filename in filenames {
+            // Files that can't be read make the result uncertain.
+            let content = match fs_err::read(hook.work_dir().join(filename)) {
+                Ok(content) => content,
+                Err(err) => {
+                    trace!("Not caching hook `{hook}`: {err}");
+                    return None;
+                }
+            };
+            update(&mut hasher, filename.as_os_str().as_encoded_bytes());
+            update(&mut hasher, &content);
+        }
+
+        Some(CacheEntry {
+            path: self.dir.join(hex::encode(Sha256::digest(key))),
+            inputs: hex::encode(hasher.finalize()),
+        })
+    }
+}
+
+/// The value of an environment variable, `None` if it is unset.
+fn env_value(name: &str) -> Option<String> {
+    EnvVars::var_os(name).map(|value| value.to_string_lossy().into_owned())
+}
+
+/// Hash the data with its length, so consecutive values can't be confused with each other.
+fn update(hasher: &mut Sha256, data: &[u8]) {
+    hasher.update((data.len() as u64).to_le_bytes());
+    hasher.update(data);
+}
+
+impl CacheEntry {
+    /// Whether the hook already passed on the same files.
+    pub(crate) fn is_fresh(&self) -> bool {
+        fs_err::read_to_string(&self.path).is_ok_and(|inputs| inputs == self.inputs)
+    }
+
+    /// Record that the hook passed on the files.
+    pub(crate) fn save(&self) -> std::io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs_err::create_dir_all(parent)?;
+        }
+        fs_err::write(&self.path, &self.inputs)
+    }
+}
@@ -3,6 +3,8 @@ use std::path::{Path, PathBuf};
 use anyhow::Result;
 use fancy_regex::Regex;
 use itertools::{Either, Itertools};
+use owo_colors::OwoColorize;
+use path_clean::PathClean;
 use rayon::iter::{IntoParallelRefIterator, ParallelBridge, ParallelIterator};
 use rustc_hash::FxHashSet;
 use tracing::{debug, error};
@@ -11,7 +13,6 @@ use constants::env_vars::EnvVars;
 
 use crate::config::Stage;
 use crate::fs::normalize_path;
-use crate::git::GIT_ROOT;
 use crate::hook::Hook;
 use crate::identify::tags_from_path;
 use crate::workspace::Project;
@@ -29,16 +30,15 @@ impl<'a> FilenameFilter<'a> {
     }
 
     pub(crate) fn filter(&self, filename: &Path) -> bool {
-        let Some(filename) = filename.to_str() else {
-            return false;
-        };
+        // Match non-UTF-8 paths with the invalid bytes replaced, rather than dropping them.
+        let filename = filename.to_string_lossy();
         if let Some(re) = &self.include {
-            if !re.is_match(filename).unwrap_or(false) {
+            if !re.is_match(&filename).unwrap_or(false) {
                 return false;
             }
         }
         if let Some(re) = &self.exclude {
-            if re.is_match(filename).unwrap_or(false) {
+            if re.is_match(&filename).unwrap_or(false) {
                 return false;
             }
         }
@@ -95,7 +95,12 @@ pub(crate) struct FileFilter<'a> {
 
 impl<'a> FileFilter<'a> {
     // Here, `filenames` are paths relative to the workspace root.
-    pub(crate) fn for_project<I>(filenames: I, project: &'a Project) -> Self
+    /// Files inside `nested_projects` belong to those projects, and are left out.
+    pub(crate) fn for_project<I>(
+        filenames: I,
+        project: &'a Project,
+        nested_projects: &[&Path],
+    ) -> Self
     where
         I: Iterator<Item = &'a PathBuf> + Send,
     {
@@ -104,14 +109,23 @@ impl<'a> FileFilter<'a> {
             project.config().exclude.as_deref(),
         );
 
-        // TODO: support orphaned project, which does not share files with its parent project.
         let mut filenames = filenames
             .enumerate()
             .par_bridge()
             .map(|(i, p)| (i, p.as_path()))
-            .filter(|(_, filename)| filter.filter(filename))
             // Collect files that are inside the hook project directory.
             .filter(|(_, filename)| filename.starts_with(project.relative_path()))
+            .filter(|(_, filename)| {
+                !nested_projects
+                    .iter()
+                    .any(|nested| filename.starts_with(nested))
+            })
+            // The top-level `files` and `exclude` patterns are relative to the project root.
+            .filter(|(_, filename)| {
+                filename
+                    .strip_prefix(project.relative_path())
+                    .is_ok_and(|stripped| filter.filter(stripped))
+            })
             .collect::<Vec<_>>();
 
         // Keep filename order consistent
@@ -209,10 +223,14 @@ impl CollectOptions {
     }
 }
 
-/// Get all filenames to run hooks on.
+/// Get all filenames to run hooks on, in the git repository at `git_root`.
 /// Returns a list of file paths relative to the workspace root.
 #[allow(clippy::too_many_arguments)]
-pub(crate) async fn collect_files(root: &Path, opts: CollectOptions) -> Result<Vec<PathBuf>> {
+pub(crate) async fn collect_files(
+    git_root: &Path,
+    root: &Path,
+    opts: CollectOptions,
+) -> Result<Vec<PathBuf>> {
     let CollectOptions {
         hook_stage,
         from_ref,
@@ -223,11 +241,11 @@ pub(crate) async fn collect_files(root: &Path, opts: CollectOptions) -> Result<V
         commit_msg_filename,
     } = opts;
 
-    let git_root = GIT_ROOT.as_ref()?;
-
     // The workspace root relative to the git root.
     let relative_root = root.strip_prefix(git_root)?;
 
+    // Files given explicitly are expected to be checked, so skipping them is worth a warning.
+    let explicit = !files.is_empty() || !directories.is_empty();
     let filenames = collect_files_from_args(
         git_root,
         root,
@@ -242,17 +260,43 @@ pub(crate) async fn collect_files(root: &Path, opts: CollectOptions) -> Result<V
     .await?;
 
     // Convert filenames to be relative to the workspace root.
+    let mut outside = Vec::new();
     let mut filenames = filenames
         .into_iter()
         .filter_map(|filename| {
             // Only keep files under the workspace root.
-            filename
-                .strip_prefix(relative_root)
-                .map(|p| normalize_path(p.to_path_buf()))
-                .ok()
+            let Ok(relative) = filename.strip_prefix(relative_root) else {
+                debug!(
+                    "Skipping `{}`, it is outside of the workspace",
+                    filename.display()
+                );
+                outside.push(filename);
+                return None;
+            };
+            Some(normalize_path(relative.to_path_buf()))
         })
         .collect::<Vec<_>>();
 
+    // The workspace root is a project, so these files belong to no project.
+    if explicit && !outside.is_empty() {
+        outside.sort_unstable();
+        let outside = outside
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect::<Vec<_>>();
+        if outside.len() == 1 {
+            warn_user!(
+                "This file is not in any project of the workspace, it will be ignored: `{}`",
+                outside[0]
+            );
+        } else {
+            warn_user!(
+                "These files are not in any project of the workspace, they will be ignored: `{}`",
+                outside.join(", ")
+            );
+        }
+    }
+
     // Sort filenames if in tests to make the order consistent.
     if EnvVars::is_set(EnvVars::PREK_INTERNAL__SORT_FILENAMES) {
         filenames.sort_unstable();
@@ -262,7 +306,8 @@ pub(crate) async fn collect_files(root: &Path, opts: CollectOptions) -> Result<V
 }
 
 fn adjust_relative_path(path: &str, new_cwd: &Path) -> Result<PathBuf, std::io::Error> {
-    fs::relative_to(std::path::absolute(path)?, new_cwd)
+    // `absolute` keeps `..` components, which would hide that the path leaves `new_cwd`.
+    fs::relative_to(std::path::absolute(path)?.clean(), new_cwd)
 }
 
 /// Collect files to run hooks on.
@@ -290,7 +335,9 @@ async fn collect_files_from_args(
     }
 
     if let (Some(from_ref), Some(to_ref)) = (from_ref, to_ref) {
-        let files = git::get_changed_files(&from_ref, &to_ref, workspace_root).await?;
+        let mut files = git::get_changed_files(&from_ref, &to_ref, workspace_root).await?;
+        // Files deleted from the working tree since `to_ref` have nothing to check.
+        files.retain(|file| git_root.join(file).symlink_metadata().is_ok());
         debug!(
             "Files changed between {} and {}: {}",
             from_ref,
@@ -308,7 +355,7 @@ async fn collect_files_from_args(
 
         // Fun fact: if a hook specified `types: [directory]`, it won't run in `--all-files` mode.
 
-        let (exists, non_exists): (FxHashSet<_>, Vec<_>) =
+        let (exists_files, non_exists): (FxHashSet<_>, Vec<_>) =
             files.into_iter().partition_map(|filename| {
                 if std::fs::exists(&filename).unwrap_or(false) {
                     Either::Left(filename)
@@ -330,10 +377,18 @@ async fn collect_files_from_args(
             }
         }
 
-        let mut exists = exists
-            .into_iter()
-            .map(|filename| adjust_relative_path(&filename, git_root).map(normalize_path))
-            .collect::<Result<FxHashSet<_>, _>>()?;
+        let mut exists = FxHashSet::default();
+        for filename in exists_files {
+            let path = adjust_relative_path(&filename, git_root)?;
+            if path.starts_with("..") {
+                anyhow::bail!(
+                    "File `{}` is outside of the git repository `{}`",
+                    filename.cyan(),
+                    git_root.display().cyan()
+                );
+            }
+            exists.insert(normalize_path(path));
+        }
 
         for dir in directories {
             let dir = adjust_relative_path(&dir, git_root)?;
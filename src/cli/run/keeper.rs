@@ -186,7 +186,14 @@ impl WorkingTreeKeeper {
             );
 
             Self::checkout_working_tree(&self.root)?;
-            Self::git_apply(patch)?;
+            if Self::git_apply(patch).is_err() {
+                // Keep the patch, so the changes can still be recovered by hand.
+                anyhow::bail!(
+                    "The changes conflict with the current working tree, they are kept in `{}`, resolve the conflicts with `{}`",
+                    patch.user_display(),
+                    format!("git apply --3way {}", patch.user_display()).cyan()
+                );
+            }
         }
 
         eprintln!(
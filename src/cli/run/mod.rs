@@ -1,9 +1,18 @@
 pub(crate) use filter::{CollectOptions, FileFilter, collect_files};
-pub(crate) use run::{install_hooks, run};
+pub(crate) use on_file::run_on_file;
+pub(crate) use repos::run_repos;
+pub(crate) use revs::print_resolved_revs;
+pub(crate) use run::{RunOptions, install_hooks, run};
 pub(crate) use selector::{SelectorSource, Selectors};
+pub(crate) use watch::watch;
 
+mod cache;
 mod filter;
 mod keeper;
+mod on_file;
+mod repos;
+mod revs;
 #[allow(clippy::module_inception)]
 mod run;
 mod selector;
+mod watch;
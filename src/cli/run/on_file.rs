@@ -0,0 +1,164 @@
+use std::fmt::Write;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use owo_colors::OwoColorize;
+
+use crate::cli::reporter::{HookInitReporter, HookInstallReporter};
+use crate::cli::run::FileFilter;
+use crate::cli::run::run::{install_hooks, set_env_vars};
+use crate::cli::{ExitStatus, RunExtraArgs};
+use crate::config::Language;
+use crate::fs::{CWD, Simplified};
+use crate::git::GIT_ROOT;
+use crate::printer::Printer;
+use crate::store::STORE;
+use crate::warn_user;
+use crate::workspace::Workspace;
+
+/// Run a single hook on a single file, bypassing hook selection and file filters.
+///
+/// The hook is connected directly to the terminal, so its output is streamed as it runs.
+pub(crate) async fn run_on_file(
+    config: Option<PathBuf>,
+    hook_id: String,
+    file: PathBuf,
+    refresh: bool,
+    printer: Printer,
+) -> Result<ExitStatus> {
+    let git_root = GIT_ROOT.as_ref()?;
+    let workspace_root = Workspace::find_root(git_root, config.as_deref(), &CWD)?;
+    let mut workspace = Workspace::discover(git_root, workspace_root, config, None, refresh)?;
+
+    let path = dunce::canonicalize(CWD.join(&file))
+        .with_context(|| format!("File `{}` does not exist", file.user_display()))?;
+    let root = dunce::canonicalize(workspace.root())?;
+    let Ok(relative_path) = path.strip_prefix(&root) else {
+        anyhow::bail!(
+            "File `{}` is outside of the workspace `{}`",
+            file.user_display().cyan(),
+            workspace.root().user_display().cyan()
+        );
+    };
+    let relative_path = relative_path.to_path_buf();
+
+    // The file belongs to the deepest project containing it.
+    let project = workspace
+        .projects()
+        .iter()
+        .filter(|project| relative_path.starts_with(project.relative_path()))
+        .max_by_key(|project| project.depth())
+        .map(|project| project.idx())
+        .context("No project contains the file")?;
+
+    let store = STORE.as_ref()?;
+    let lock = store.lock_async().await?;
+    let reporter = HookInitReporter::from(printer);
+    let hooks = workspace.init_hooks(store, Some(&reporter)).await?;
+
+    let mut hooks: Vec<_> = hooks
+        .into_iter()
+        .filter(|hook| hook.project().idx() == project)
+        .filter(|hook| hook.id == hook_id || hook.alias == hook_id)
+        .collect();
+    let mut hook = match hooks.len() {
+        0 => anyhow::bail!(
+            "Hook `{}` not found in project `{}`, which contains `{}`",
+            hook_id.cyan(),
+            workspace.projects()[project].to_string().cyan(),
+            file.user_display().cyan()
+        ),
+        1 => hooks.remove(0),
+        n => anyhow::bail!(
+            "`{}` matches {n} hooks in project `{}`, give them an `alias` to select one",
+            hook_id.cyan(),
+            workspace.projects()[project].to_string().cyan()
+        ),
+    };
+    hook.interactive = true;
+
+    if !Language::supported(hook.language) {
+        anyhow::bail!(
+            "Hook `{}` uses the `{}` language, which is not supported yet",
+            hook.id.cyan(),
+            hook.language.cyan()
+        );
+    }
+
+    let project = &workspace.projects()[project];
+    let filenames = [relative_path];
+    if FileFilter::for_project(
+        filenames.iter(),
+        project,
+        &workspace.nested_projects(project),
+    )
+    .for_hook(&hook)
+    .is_empty()
+    {
+        warn_user!(
+            "`{}` does not match the files, types or excludes of hook `{}`, running it anyway",
+            file.user_display(),
+            hook.id
+        );
+    }
+
+    let reporter = HookInstallReporter::from(printer);
+    let hook = install_hooks(vec![Arc::new(hook)], store, &reporter)
+        .await?
+        .remove(0);
+    drop(lock);
+
+    // Files are passed to hooks relative to their project.
+    let filenames: Vec<_> = if hook.pass_filenames {
+        vec![
+            filenames[0]
+                .strip_prefix(project.relative_path())
+                .expect("File should be in the project")
+                .to_path_buf(),
+        ]
+    } else {
+        vec![]
+    };
+    let mut command = hook.entry.split()?;
+    command.extend(hook.args.iter().cloned());
+    command.extend(
+        filenames
+            .iter()
+            .map(|filename| filename.to_string_lossy().into_owned()),
+    );
+    let command = shlex::try_join(command.iter().map(String::as_str))?;
+    writeln!(
+        printer.stderr(),
+        "{}",
+        format!(
+            "Running hook `{}` in `{}`: {command}",
+            hook.id,
+            hook.work_dir().user_display()
+        )
+        .dimmed()
+    )?;
+
+    set_env_vars(None, None, &RunExtraArgs::default());
+    std::env::set_current_dir(workspace.root())?;
+    let (status, _) = hook
+        .language
+        .run(
+            &hook,
+            &filenames.iter().map(PathBuf::as_path).collect::<Vec<_>>(),
+            store,
+        )
+        .await
+        .with_context(|| format!("Failed to run hook `{hook}`"))?;
+
+    if status == 0 {
+        Ok(ExitStatus::Success)
+    } else {
+        writeln!(
+            printer.stderr(),
+            "{}",
+            format!("Hook `{}` failed with exit code {status}", hook.id).red()
+        )?;
+        Ok(ExitStatus::Failure)
+    }
+}
@@ -0,0 +1,85 @@
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use anstream::eprintln;
+use anyhow::{Context, Result};
+use owo_colors::OwoColorize;
+
+use crate::cli::ExitStatus;
+use crate::cli::run::{RunOptions, run};
+use crate::fs::{CWD, Simplified};
+use crate::git;
+use crate::printer::Printer;
+
+/// Run hooks in each of the given git repositories.
+///
+/// Each repository runs the full discovery and run from its own git root, independently of
+/// the others. An error in one repository is reported and the other repositories still run.
+/// The exit status is the worst status of all runs.
+pub(crate) async fn run_repos(
+    repos: Vec<PathBuf>,
+    options: RunOptions,
+    printer: Printer,
+) -> Result<ExitStatus> {
+    let repos = repos
+        .into_iter()
+        .map(|repo| {
+            let path = CWD.join(&repo);
+            if !path.is_dir() {
+                anyhow::bail!("Repository `{}` does not exist", repo.user_display());
+            }
+            Ok((repo, path))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut worst = 0;
+    for (idx, (repo, path)) in repos.iter().enumerate() {
+        writeln!(
+            printer.stdout(),
+            "{}{}:",
+            if idx == 0 { "" } else { "\n" },
+            format!("Running hooks in repo `{}`", repo.user_display().cyan()).bold()
+        )?;
+
+        let status = match run_repo(path, options.clone(), printer).await {
+            Ok(status) => status,
+            Err(err) => {
+                let mut causes = err.chain();
+                eprintln!("{}: {}", "error".red().bold(), causes.next().unwrap());
+                for err in causes {
+                    eprintln!("  {}: {}", "caused by".red().bold(), err);
+                }
+                ExitStatus::Error
+            }
+        };
+        worst = worst.max(exit_code(status));
+    }
+
+    Ok(match worst {
+        0 => ExitStatus::Success,
+        1 => ExitStatus::Failure,
+        2 => ExitStatus::Error,
+        code => ExitStatus::External(code),
+    })
+}
+
+/// Run hooks in the git repository containing `path`.
+async fn run_repo(path: &Path, options: RunOptions, printer: Printer) -> Result<ExitStatus> {
+    // Git commands run in the current directory, and a previous run may have changed it.
+    std::env::set_current_dir(path)
+        .with_context(|| format!("Failed to change directory to `{}`", path.display()))?;
+    let git_root = git::get_root()
+        .with_context(|| format!("`{}` is not in a git repository", path.user_display()))?;
+
+    run(&git_root, path, options, printer).await
+}
+
+fn exit_code(status: ExitStatus) -> u8 {
+    match status {
+        ExitStatus::Success => 0,
+        ExitStatus::Failure => 1,
+        ExitStatus::Error => 2,
+        ExitStatus::Interrupted => 130,
+        ExitStatus::External(code) => code,
+    }
+}
@@ -0,0 +1,93 @@
+use std::fmt::Write;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use owo_colors::OwoColorize;
+use rustc_hash::FxHashSet;
+use serde::Serialize;
+
+use crate::cli::reporter::HookInitReporter;
+use crate::cli::run::Selectors;
+use crate::cli::{ExitStatus, OutputFormat};
+use crate::fs::CWD;
+use crate::git;
+use crate::git::GIT_ROOT;
+use crate::hook::Repo;
+use crate::printer::Printer;
+use crate::store::STORE;
+use crate::workspace::Workspace;
+
+#[derive(Serialize)]
+struct RepoRevision<'a> {
+    repo: &'a str,
+    rev: &'a str,
+    resolved_rev: String,
+}
+
+/// Print the commit that each remote repo's `rev` resolved to, without running any hooks.
+pub(crate) async fn print_resolved_revs(
+    config: Option<PathBuf>,
+    includes: Vec<String>,
+    skips: Vec<String>,
+    output_format: OutputFormat,
+    refresh: bool,
+    printer: Printer,
+) -> Result<ExitStatus> {
+    let git_root = GIT_ROOT.as_ref()?;
+    let workspace_root = Workspace::find_root(git_root, config.as_deref(), &CWD)?;
+    let selectors = Selectors::load(&includes, &skips, &workspace_root)?;
+    let mut workspace =
+        Workspace::discover(git_root, workspace_root, config, Some(&selectors), refresh)?;
+
+    let store = STORE.as_ref()?;
+    let reporter = HookInitReporter::from(match output_format {
+        OutputFormat::Text => printer,
+        OutputFormat::Json => Printer::Quiet,
+    });
+    let lock = store.lock_async().await?;
+    let hooks = workspace.init_hooks(store, Some(&reporter)).await?;
+    drop(lock);
+
+    let hooks: Vec<_> = hooks
+        .into_iter()
+        .filter(|h| selectors.matches_hook(h))
+        .collect();
+    selectors.report_unused();
+
+    // Hooks from the same repo share one clone, report each clone once.
+    let mut seen = FxHashSet::default();
+    let mut resolved = Vec::new();
+    for hook in &hooks {
+        let Repo::Remote { path, url, rev, .. } = hook.repo() else {
+            continue;
+        };
+        if !seen.insert(path) {
+            continue;
+        }
+        resolved.push(RepoRevision {
+            repo: url,
+            rev,
+            resolved_rev: git::get_head_commit(path).await?,
+        });
+    }
+
+    match output_format {
+        OutputFormat::Text => {
+            for resolved in &resolved {
+                writeln!(
+                    printer.stdout(),
+                    "[{}] {} -> {}",
+                    resolved.repo.cyan(),
+                    resolved.rev,
+                    resolved.resolved_rev.bold()
+                )?;
+            }
+        }
+        OutputFormat::Json => {
+            let json_output = serde_json::to_string_pretty(&resolved)?;
+            writeln!(printer.stdout(), "{json_output}")?;
+        }
+    }
+
+    Ok(ExitStatus::Success)
+}
@@ -1,55 +1,180 @@
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt::Write as _;
-use std::io::Write;
-use std::path::PathBuf;
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
-use std::sync::{Arc, LazyLock};
+use std::sync::Arc;
+use std::time::Duration;
 
+use anstream::StripStream;
 use anyhow::{Context, Result};
 use futures::stream::{FuturesUnordered, StreamExt};
+use ignore::WalkBuilder;
 use owo_colors::{OwoColorize, Style};
 use rand::SeedableRng;
 use rand::prelude::{SliceRandom, StdRng};
-use rustc_hash::FxHashMap;
+use rustc_hash::{FxHashMap, FxHashSet};
 use tokio::io::AsyncWriteExt;
 use tokio::sync::Semaphore;
 use tracing::{debug, trace};
 use unicode_width::UnicodeWidthStr;
 
 use constants::env_vars::EnvVars;
+use constants::{ALT_CONFIG_FILE, CONFIG_FILE};
 
 use crate::cli::reporter::{HookInitReporter, HookInstallReporter};
+use crate::cli::run::cache::ResultCache;
 use crate::cli::run::keeper::WorkTreeKeeper;
 use crate::cli::run::{CollectOptions, FileFilter, Selectors, collect_files};
-use crate::cli::{ExitStatus, RunExtraArgs};
+use crate::cli::{ExitStatus, RunArgs, RunExtraArgs, RunOutputFormat};
 use crate::config::{Language, Stage};
-use crate::fs::CWD;
+use crate::fs::Simplified;
 use crate::git;
-use crate::git::GIT_ROOT;
-use crate::hook::{Hook, InstalledHook};
+use crate::hook::{Hook, InstalledHook, Stages};
 use crate::printer::{Printer, Stdout};
-use crate::run::{CONCURRENCY, USE_COLOR};
+use crate::run::{
+    BatchProgress, CONCURRENCY, USE_COLOR, hook_timeout, set_hook_stage, take_hook_output,
+};
 use crate::store::{STORE, Store};
+use crate::warn_user;
 use crate::workspace::{Project, Workspace};
 
-#[allow(clippy::too_many_arguments, clippy::fn_params_excessive_bools)]
+/// The options of a single run of the hooks.
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Debug, Clone)]
+pub(crate) struct RunOptions {
+    pub(crate) config: Option<PathBuf>,
+    pub(crate) includes: Vec<String>,
+    pub(crate) skips: Vec<String>,
+    pub(crate) projects: Vec<String>,
+    pub(crate) hook_stage: Stage,
+    pub(crate) from_ref: Option<String>,
+    pub(crate) to_ref: Option<String>,
+    pub(crate) all_files: bool,
+    pub(crate) files: Vec<String>,
+    pub(crate) directories: Vec<String>,
+    pub(crate) last_commit: bool,
+    pub(crate) show_diff_on_failure: bool,
+    pub(crate) dry_run: bool,
+    pub(crate) no_cache: bool,
+    pub(crate) hook_repeat: u32,
+    pub(crate) report_unmatched_files: bool,
+    pub(crate) include_nested_project_files: bool,
+    pub(crate) max_total_duration: Option<Duration>,
+    pub(crate) per_project_status: Option<PathBuf>,
+    pub(crate) project_order: Option<PathBuf>,
+    pub(crate) output_dir: Option<PathBuf>,
+    pub(crate) output_format: RunOutputFormat,
+    pub(crate) output: Option<PathBuf>,
+    pub(crate) log_file: Option<PathBuf>,
+    pub(crate) interactive_hooks: Vec<String>,
+    pub(crate) no_buffer: bool,
+    pub(crate) exclude_hooks: Vec<String>,
+    pub(crate) refresh_configs: bool,
+    pub(crate) refresh_hooks: Option<Vec<String>>,
+    pub(crate) allow_unstaged_config: bool,
+    pub(crate) stash_unstaged: bool,
+    pub(crate) refresh: bool,
+    pub(crate) extra_args: RunExtraArgs,
+    pub(crate) verbose: bool,
+}
+
+impl RunOptions {
+    pub(crate) fn new(
+        args: RunArgs,
+        config: Option<PathBuf>,
+        refresh: bool,
+        verbose: bool,
+    ) -> Self {
+        Self {
+            config,
+            includes: args.includes,
+            skips: args.skips,
+            projects: args.projects,
+            hook_stage: args.hook_stage,
+            from_ref: args.from_ref,
+            to_ref: args.to_ref,
+            all_files: args.all_files,
+            files: args.files,
+            directories: args.directory,
+            last_commit: args.last_commit,
+            show_diff_on_failure: args.show_diff_on_failure,
+            dry_run: args.dry_run,
+            no_cache: args.no_cache,
+            hook_repeat: args.hook_repeat,
+            report_unmatched_files: args.report_unmatched_files,
+            include_nested_project_files: args.include_nested_project_files,
+            max_total_duration: args.max_total_duration,
+            per_project_status: args.per_project_status,
+            project_order: args.project_order,
+            output_dir: args.output_dir,
+            output_format: if args.summary_json_only {
+                RunOutputFormat::SummaryJson
+            } else {
+                args.output_format
+            },
+            output: args.output,
+            log_file: args.log_file,
+            interactive_hooks: args.interactive_hooks,
+            no_buffer: args.no_buffer,
+            exclude_hooks: args.exclude_hooks,
+            refresh_configs: args.refresh_configs,
+            refresh_hooks: args.refresh_hooks,
+            allow_unstaged_config: args.allow_unstaged_config,
+            stash_unstaged: args.stash_unstaged,
+            refresh,
+            extra_args: args.extra,
+            verbose,
+        }
+    }
+}
+
+/// Run the hooks of the workspace found from `dir`, in the git repository at `git_root`.
+///
+/// Relative paths of the options are resolved against `dir`.
 pub(crate) async fn run(
-    config: Option<PathBuf>,
-    includes: Vec<String>,
-    skips: Vec<String>,
-    hook_stage: Stage,
-    from_ref: Option<String>,
-    to_ref: Option<String>,
-    all_files: bool,
-    files: Vec<String>,
-    directories: Vec<String>,
-    last_commit: bool,
-    show_diff_on_failure: bool,
-    dry_run: bool,
-    refresh: bool,
-    extra_args: RunExtraArgs,
-    verbose: bool,
+    git_root: &Path,
+    dir: &Path,
+    options: RunOptions,
     printer: Printer,
 ) -> Result<ExitStatus> {
+    let RunOptions {
+        config,
+        includes,
+        skips,
+        projects,
+        hook_stage,
+        from_ref,
+        to_ref,
+        all_files,
+        files,
+        directories,
+        last_commit,
+        show_diff_on_failure,
+        dry_run,
+        no_cache,
+        hook_repeat,
+        report_unmatched_files,
+        include_nested_project_files,
+        max_total_duration,
+        per_project_status,
+        project_order,
+        output_dir,
+        output_format,
+        output,
+        log_file,
+        interactive_hooks,
+        no_buffer,
+        exclude_hooks,
+        refresh_configs,
+        refresh_hooks,
+        allow_unstaged_config,
+        stash_unstaged,
+        refresh,
+        extra_args,
+        verbose,
+    } = options;
+
     // Convert `--last-commit` to `HEAD~1..HEAD`
     let (from_ref, to_ref) = if last_commit {
         (Some("HEAD~1".to_string()), Some("HEAD".to_string()))
@@ -57,6 +182,17 @@ pub(crate) async fn run(
         (from_ref, to_ref)
     };
 
+    if output.is_some() && output_format != RunOutputFormat::Junit {
+        anyhow::bail!(
+            "`{}` is only supported with `{}`",
+            "--output".cyan(),
+            "--output-format junit".cyan()
+        );
+    }
+
+    // Meta hooks only check the hooks of the stage being run.
+    set_hook_stage(hook_stage);
+
     // Prevent recursive post-checkout hooks.
     if hook_stage == Stage::PostCheckout
         && EnvVars::is_set(EnvVars::PREK_INTERNAL__SKIP_POST_CHECKOUT)
@@ -64,58 +200,171 @@ pub(crate) async fn run(
         return Ok(ExitStatus::Success);
     }
 
-    // Ensure we are in a git repository.
-    LazyLock::force(&GIT_ROOT).as_ref()?;
-
-    let should_stash = !all_files && files.is_empty() && directories.is_empty();
+    // Hooks running on the staged files must not see the unstaged changes.
+    let should_stash = stash_unstaged || (!all_files && files.is_empty() && directories.is_empty());
 
     // Check if we have unresolved merge conflict files and fail fast.
     if should_stash && git::has_unmerged_paths().await? {
         anyhow::bail!("You have unmerged paths. Resolve them before running prek");
     }
 
-    let workspace_root = Workspace::find_root(config.as_deref(), &CWD)?;
+    let workspace_root = Workspace::find_root(git_root, config.as_deref(), dir)?;
     let selectors = Selectors::load(&includes, &skips, &workspace_root)?;
-    let mut workspace = Workspace::discover(workspace_root, config, Some(&selectors), refresh)?;
+    let mut workspace = Workspace::discover(
+        git_root,
+        workspace_root,
+        config,
+        Some(&selectors),
+        refresh || refresh_configs,
+    )?;
+    if !projects.is_empty() {
+        workspace.retain_projects(&projects)?;
+    }
 
     if should_stash {
-        workspace.check_configs_staged().await?;
+        workspace
+            .check_configs_staged(allow_unstaged_config)
+            .await?;
     }
 
+    let project_order = project_order
+        .map(|path| read_project_order(&path, &workspace))
+        .transpose()?
+        .unwrap_or_default();
+
+    // Progress and hook status lines would corrupt the JSON document on stdout.
+    let status_printer = if output_format.is_json() {
+        Printer::Quiet
+    } else {
+        printer
+    };
+
     let store = STORE.as_ref()?;
-    let reporter = HookInitReporter::from(printer);
+    let reporter = HookInitReporter::from(status_printer);
     let lock = store.lock_async().await?;
 
     let hooks = workspace.init_hooks(store, Some(&reporter)).await?;
+
+    for hook_id in &exclude_hooks {
+        if !hooks
+            .iter()
+            .any(|h| &h.id == hook_id || &h.alias == hook_id)
+        {
+            warn_user!("hook `{hook_id}` passed to `--exclude-hook` does not exist");
+        }
+    }
+
+    // Listed by project when the selected hooks match nothing.
+    let mut available_hook_ids: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    for hook in &hooks {
+        available_hook_ids
+            .entry(hook.project().to_string())
+            .or_default()
+            .insert(hook.id.clone());
+    }
+
+    // Hooks skipped by id or excluded are still reported, but never installed or run.
+    let mut skipped_hooks = Vec::new();
     let filtered_hooks: Vec<_> = hooks
         .into_iter()
-        .filter(|h| selectors.matches_hook(h))
-        .map(Arc::new)
+        .map(|mut h| {
+            // Without buffering, every hook writes to the terminal directly.
+            if no_buffer || interactive_hooks.contains(&h.id) {
+                h.interactive = true;
+            }
+            h
+        })
+        .filter_map(|h| {
+            if !selectors.matches_hook(&h) {
+                if selectors.skips_hook_id(&h) {
+                    skipped_hooks.push((Arc::new(h), SkipReason::Selector));
+                }
+                None
+            } else if exclude_hooks.contains(&h.id) || exclude_hooks.contains(&h.alias) {
+                skipped_hooks.push((Arc::new(h), SkipReason::Excluded));
+                None
+            } else {
+                Some(Arc::new(h))
+            }
+        })
         .collect();
 
     selectors.report_unused();
 
-    if filtered_hooks.is_empty() {
+    if filtered_hooks.is_empty() && skipped_hooks.is_empty() {
         writeln!(
             printer.stderr(),
             "{}: No hooks found after filtering with the given selectors",
             "error".red().bold(),
         )?;
+        if !selectors.includes().is_empty() && !available_hook_ids.is_empty() {
+            let list = |ids: &BTreeSet<String>| {
+                ids.iter()
+                    .map(|id| format!("`{}`", id.cyan()))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+            if available_hook_ids.len() == 1 {
+                let ids = available_hook_ids.values().next().unwrap();
+                writeln!(
+                    printer.stderr(),
+                    "{}: Available hooks are: {}",
+                    "hint".bold().cyan(),
+                    list(ids)
+                )?;
+            } else {
+                writeln!(
+                    printer.stderr(),
+                    "{}: Available hooks are:",
+                    "hint".bold().cyan()
+                )?;
+                for (project, ids) in &available_hook_ids {
+                    writeln!(printer.stderr(), "  `{}`: {}", project.cyan(), list(ids))?;
+                }
+            }
+        }
         return Ok(ExitStatus::Failure);
     }
 
-    let filtered_hooks = filtered_hooks
+    // Hooks selected by id but not run in this stage, like `manual` hooks, are worth a hint.
+    let (filtered_hooks, other_stage_hooks): (Vec<_>, Vec<_>) = filtered_hooks
+        .into_iter()
+        .partition(|h| h.stages.contains(hook_stage));
+    let other_stage_hooks = other_stage_hooks
         .into_iter()
-        .filter(|h| h.stages.contains(hook_stage))
+        .filter(|h| selectors.selects_hook_id(h))
         .collect::<Vec<_>>();
+    skipped_hooks.retain(|(h, _)| h.stages.contains(hook_stage));
 
-    if filtered_hooks.is_empty() {
+    if filtered_hooks.is_empty() && skipped_hooks.is_empty() {
         writeln!(
             printer.stderr(),
             "{}: No hooks found for stage `{}` after filtering",
             "error".red().bold(),
             hook_stage.cyan()
         )?;
+        for hook in &other_stage_hooks {
+            writeln!(
+                printer.stderr(),
+                "{}: {}",
+                "hint".bold().cyan(),
+                stage_hint(hook)
+            )?;
+        }
+        return Ok(ExitStatus::Failure);
+    }
+    for hook in &other_stage_hooks {
+        warn_user!("{}", stage_hint(hook));
+    }
+
+    if hook_repeat > 1 && filtered_hooks.len() != 1 {
+        writeln!(
+            printer.stderr(),
+            "{}: `{}` requires selecting a single hook, but {} hooks were selected",
+            "error".red().bold(),
+            "--hook-repeat".cyan(),
+            filtered_hooks.len()
+        )?;
         return Ok(ExitStatus::Failure);
     }
 
@@ -123,8 +372,28 @@ pub(crate) async fn run(
         "Hooks going to run: {:?}",
         filtered_hooks.iter().map(|h| &h.id).collect::<Vec<_>>()
     );
-    let reporter = HookInstallReporter::from(printer);
-    let installed_hooks = install_hooks(filtered_hooks, store, &reporter).await?;
+    // A dry run never runs the hooks, so their environments are not needed.
+    let mut installed_hooks = if dry_run {
+        filtered_hooks
+            .into_iter()
+            .map(InstalledHook::NoNeedInstall)
+            .collect()
+    } else {
+        if let Some(refresh_hooks) = &refresh_hooks {
+            remove_hook_envs(&filtered_hooks, refresh_hooks, store)?;
+        }
+        let reporter = HookInstallReporter::from(status_printer);
+        install_hooks(filtered_hooks, store, &reporter).await?
+    };
+
+    let skipped_hooks: FxHashMap<_, _> = skipped_hooks
+        .into_iter()
+        .map(|(hook, reason)| {
+            let key = (hook.project().idx(), hook.idx);
+            installed_hooks.push(InstalledHook::NoNeedInstall(hook));
+            (key, reason)
+        })
+        .collect();
 
     // Release the store lock.
     drop(lock);
@@ -137,7 +406,9 @@ pub(crate) async fn run(
 
     set_env_vars(from_ref.as_ref(), to_ref.as_ref(), &extra_args);
 
+    let explicit_files = !files.is_empty();
     let filenames = collect_files(
+        workspace.git_root(),
         workspace.root(),
         CollectOptions {
             hook_stage,
@@ -151,6 +422,28 @@ pub(crate) async fn run(
     )
     .await?;
 
+    // Files given explicitly in projects that are not selected are not checked.
+    if explicit_files {
+        for filename in &filenames {
+            if !workspace
+                .projects()
+                .iter()
+                .any(|project| filename.starts_with(project.relative_path()))
+            {
+                debug!(
+                    "Skipping `{}`, it is not in any selected project",
+                    filename.display()
+                );
+            }
+        }
+    }
+
+    // Resolve the status file before changing the working directory.
+    let per_project_status = per_project_status.map(|path| dir.join(path));
+    let output_dir = output_dir.map(|path| dir.join(path));
+    let output = output.map(|path| dir.join(path));
+    let log_file = log_file.map(|path| dir.join(path));
+
     // Change to the workspace root directory.
     std::env::set_current_dir(workspace.root()).with_context(|| {
         format!(
@@ -159,21 +452,160 @@ pub(crate) async fn run(
         )
     })?;
 
-    run_hooks(
+    let unmatched_files = report_unmatched_files.then(|| {
+        find_unmatched_files(
+            &workspace,
+            &installed_hooks,
+            &skipped_hooks,
+            &filenames,
+            include_nested_project_files,
+        )
+    });
+
+    let status = run_hooks(
         &workspace,
         &installed_hooks,
+        &skipped_hooks,
         filenames,
+        hook_stage,
         store,
         show_diff_on_failure,
         dry_run,
+        no_cache,
+        hook_repeat,
+        include_nested_project_files,
+        max_total_duration,
+        per_project_status.as_deref(),
+        &project_order,
+        output_dir.as_deref(),
+        output_format,
+        output.as_deref(),
+        log_file.as_deref(),
+        unmatched_files.as_ref(),
         verbose,
         printer,
     )
-    .await
+    .await?;
+
+    // The JSON reporters include the unmatched files in their summary.
+    if let Some(unmatched_files) = unmatched_files
+        && !output_format.is_json()
+    {
+        print_unmatched_files(&unmatched_files, printer)?;
+    }
+
+    Ok(status)
+}
+
+/// Find the files that none of the hooks to run would be run on, grouped by extension.
+fn find_unmatched_files(
+    workspace: &Workspace,
+    hooks: &[InstalledHook],
+    skipped_hooks: &FxHashMap<(usize, usize), SkipReason>,
+    filenames: &[PathBuf],
+    include_nested_project_files: bool,
+) -> BTreeMap<String, Vec<PathBuf>> {
+    let mut matched = FxHashSet::default();
+    for project in workspace.projects() {
+        let nested_projects = if include_nested_project_files {
+            vec![]
+        } else {
+            workspace.nested_projects(project)
+        };
+        let filter = FileFilter::for_project(filenames.iter(), project, &nested_projects);
+        for hook in hooks {
+            if hook.project().idx() != project.idx()
+                || skipped_hooks.contains_key(&(project.idx(), hook.idx))
+            {
+                continue;
+            }
+            matched.extend(
+                filter
+                    .for_hook(hook)
+                    .into_iter()
+                    .map(|filename| project.relative_path().join(filename)),
+            );
+        }
+    }
+
+    let mut unmatched: BTreeMap<String, Vec<PathBuf>> = BTreeMap::new();
+    for filename in filenames {
+        if matched.contains(filename) {
+            continue;
+        }
+        let extension = filename
+            .extension()
+            .map(|ext| format!(".{}", ext.to_string_lossy()))
+            .unwrap_or_else(|| "(no extension)".to_string());
+        unmatched
+            .entry(extension)
+            .or_default()
+            .push(filename.clone());
+    }
+    unmatched
+}
+
+fn print_unmatched_files(
+    unmatched: &BTreeMap<String, Vec<PathBuf>>,
+    printer: Printer,
+) -> Result<()> {
+    if unmatched.is_empty() {
+        writeln!(
+            printer.stdout(),
+            "All files are matched by at least one hook."
+        )?;
+        return Ok(());
+    }
+
+    let count: usize = unmatched.values().map(Vec::len).sum();
+    writeln!(
+        printer.stdout(),
+        "{} not matched by any hook:",
+        if count == 1 {
+            "1 file".to_string()
+        } else {
+            format!("{count} files")
+        }
+        .yellow()
+        .bold()
+    )?;
+    for (extension, filenames) in unmatched {
+        writeln!(printer.stdout(), "  {}:", extension.cyan())?;
+        for filename in filenames {
+            writeln!(printer.stdout(), "    {}", filename.display())?;
+        }
+    }
+    Ok(())
+}
+
+/// Read the project ids listed in a `--project-order` file, skipping blank lines and comments.
+fn read_project_order(path: &Path, workspace: &Workspace) -> Result<Vec<String>> {
+    let content = fs_err::read_to_string(path)?;
+    let mut order = Vec::new();
+    for line in content.lines() {
+        let id = line.trim();
+        if id.is_empty() || id.starts_with('#') {
+            continue;
+        }
+        let dir = workspace.root().join(id);
+        if !dir.join(CONFIG_FILE).is_file() && !dir.join(ALT_CONFIG_FILE).is_file() {
+            anyhow::bail!(
+                "Project `{}` listed in `{}` does not exist",
+                id.cyan(),
+                path.user_display().cyan()
+            );
+        }
+        order.push(id.trim_end_matches('/').to_string());
+    }
+    Ok(order)
 }
 
 // `pre-commit` sets these environment variables for other git hooks.
-fn set_env_vars(from_ref: Option<&String>, to_ref: Option<&String>, args: &RunExtraArgs) {
+pub(super) fn set_env_vars(
+    from_ref: Option<&String>,
+    to_ref: Option<&String>,
+    args: &RunExtraArgs,
+) {
     unsafe {
         std::env::set_var("PRE_COMMIT", "1");
 
@@ -221,6 +653,36 @@ fn set_env_vars(from_ref: Option<&String>, to_ref: Option<&String>, args: &RunEx
     }
 }
 
+/// Remove the installed environments of the hooks, so they are installed again.
+///
+/// With hook ids, only the environments of the matching hooks are removed.
+/// An environment shared with other hooks is removed for them too.
+fn remove_hook_envs(hooks: &[Arc<Hook>], hook_ids: &[String], store: &Store) -> Result<()> {
+    let hooks: Vec<_> = hooks
+        .iter()
+        .filter(|hook| {
+            hook_ids.is_empty()
+                || hook_ids
+                    .iter()
+                    .any(|id| *id == hook.id || *id == hook.full_id())
+        })
+        .collect();
+
+    for info in store.installed_hooks() {
+        if hooks.iter().any(|hook| info.matches(hook)) {
+            debug!("Removing hook environment `{}`", info.env_path.display());
+            fs_err::remove_dir_all(&info.env_path).with_context(|| {
+                format!(
+                    "Failed to remove hook environment `{}`",
+                    info.env_path.display()
+                )
+            })?;
+        }
+    }
+
+    Ok(())
+}
+
 pub async fn install_hooks(
     hooks: Vec<Arc<Hook>>,
     store: &Store,
@@ -286,7 +748,16 @@ pub async fn install_hooks(
                         .language
                         .install(hook.clone(), store, reporter)
                         .await
-                        .context(format!("Failed to install hook `{hook}`"))?;
+                        .with_context(|| {
+                            if hook.language_request.is_any() {
+                                format!("Failed to install hook `{hook}`")
+                            } else {
+                                format!(
+                                    "Failed to install hook `{hook}` with `language_version: {}`",
+                                    hook.language_version
+                                )
+                            }
+                        })?;
 
                     installed_hook
                         .mark_as_installed(store)
@@ -374,9 +845,40 @@ fn partition_hooks(hooks: &[Arc<Hook>]) -> Vec<Vec<Arc<Hook>>> {
     groups
 }
 
+/// Receives the progress of a run, so that all output formats report the same events.
+trait RunReporter {
+    /// Called before running the hooks of a project.
+    fn on_project_start(&mut self, project: &Project) -> Result<()>;
+
+    /// Called right before a hook is run, not called for skipped hooks.
+    fn on_hook_start(&mut self, hook: &Hook) -> Result<()>;
+
+    /// Called when batches of the running hook are scheduled or complete.
+    fn on_batch_progress(&mut self, _hook: &Hook, _progress: BatchProgress) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called once a hook has completed or was skipped.
+    fn on_hook_complete(&mut self, hook: &Hook, result: &HookResult) -> Result<()>;
+
+    /// Called after all hooks have run.
+    fn finish(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Reports hook results in the human-readable `pre-commit` format.
 struct StatusPrinter {
     printer: Printer,
     columns: usize,
+    verbose: bool,
+    /// Whether to print a header before the hooks of each project.
+    project_headers: bool,
+    first_project: bool,
+    /// The results so far, summarized at the end of runs of more than one project.
+    tally: Option<ResultTally>,
+    /// The progress shown in the status line of the running hook, if any.
+    progress: Option<BatchProgress>,
 }
 
 impl StatusPrinter {
@@ -386,10 +888,25 @@ impl StatusPrinter {
     const DRY_RUN: &'static str = "Dry Run";
     const NO_FILES: &'static str = "(no files to check)";
     const UNIMPLEMENTED: &'static str = "(unimplemented yet)";
+    const EXCLUDED: &'static str = "(excluded)";
+    const CACHED: &'static str = "(cached)";
 
-    fn for_hooks(hooks: &[InstalledHook], printer: Printer) -> Self {
+    fn for_hooks(hooks: &[InstalledHook], verbose: bool, printer: Printer) -> Self {
         let columns = Self::calculate_columns(hooks);
-        Self { printer, columns }
+        let projects = hooks
+            .iter()
+            .map(|hook| hook.project().idx())
+            .collect::<FxHashSet<_>>();
+        let project_headers = projects.len() > 1 || !hooks[0].project().is_root();
+        Self {
+            printer,
+            columns,
+            verbose,
+            project_headers,
+            first_project: true,
+            tally: (projects.len() > 1).then(ResultTally::default),
+            progress: None,
+        }
     }
 
     fn calculate_columns(hooks: &[InstalledHook]) -> usize {
@@ -429,6 +946,18 @@ impl StatusPrinter {
         )
     }
 
+    fn write_cached(&self, hook_name: &str) -> Result<(), std::fmt::Error> {
+        let dots =
+            self.columns - hook_name.width_cjk() - Self::PASSED.len() - Self::CACHED.len() - 1;
+        writeln!(
+            self.printer.stdout(),
+            "{hook_name}{}{}{}",
+            ".".repeat(dots),
+            Self::CACHED,
+            Self::PASSED.on_green()
+        )
+    }
+
     fn write_dry_run(&self) -> Result<(), std::fmt::Error> {
         writeln!(self.printer.stdout(), "{}", Self::DRY_RUN.on_yellow())
     }
@@ -446,24 +975,628 @@ impl StatusPrinter {
     }
 }
 
+impl RunReporter for StatusPrinter {
+    fn on_project_start(&mut self, project: &Project) -> Result<()> {
+        if self.project_headers {
+            writeln!(
+                self.stdout(),
+                "{}{}:",
+                if self.first_project { "" } else { "\n" },
+                format!("Running hooks for `{}`", project.to_string().cyan()).bold()
+            )?;
+            self.first_project = false;
+        }
+        Ok(())
+    }
+
+    fn on_hook_start(&mut self, hook: &Hook) -> Result<()> {
+        // Interactive hooks write to the terminal directly, so print the status line once they are done.
+        if !hook.interactive {
+            self.write_running(&hook.name)?;
+            std::io::stdout().flush()?;
+        }
+        Ok(())
+    }
+
+    fn on_batch_progress(&mut self, hook: &Hook, progress: BatchProgress) -> Result<()> {
+        // The status line can only be redrawn on a terminal.
+        if self.printer != Printer::Default || !std::io::stdout().is_terminal() {
+            return Ok(());
+        }
+        // A single batch has nothing to show beyond the running hook itself.
+        if hook.interactive || progress.total() < 2 {
+            return Ok(());
+        }
+        let text = format!("({progress})");
+        let Some(dots) = self
+            .columns
+            .checked_sub(hook.name.width_cjk() + text.len() + Self::PASSED.len() + 1)
+        else {
+            return Ok(());
+        };
+        write!(
+            self.stdout(),
+            "\r{}{}{}",
+            hook.name,
+            ".".repeat(dots),
+            text.dimmed()
+        )?;
+        std::io::stdout().flush()?;
+        self.progress = Some(progress);
+        Ok(())
+    }
+
+    fn on_hook_complete(&mut self, hook: &Hook, result: &HookResult) -> Result<()> {
+        if let Some(tally) = &mut self.tally {
+            tally.add(hook, result.status);
+        }
+        // The status line has the same width with or without the progress, so it is fully overwritten.
+        if self.progress.take().is_some() {
+            write!(self.stdout(), "\r")?;
+            self.write_running(&hook.name)?;
+        }
+        if hook.interactive && result.skip_reason.is_none() {
+            self.write_running(&hook.name)?;
+        }
+        match (result.status, result.skip_reason) {
+            (HookStatus::Skipped, Some(reason)) => {
+                let (reason, style) = match reason {
+                    SkipReason::Selector => ("", Style::new().black().on_yellow()),
+                    SkipReason::Excluded => (Self::EXCLUDED, Style::new().black().on_yellow()),
+                    SkipReason::NoFiles => (Self::NO_FILES, Style::new().black().on_cyan()),
+                    SkipReason::Unimplemented => {
+                        (Self::UNIMPLEMENTED, Style::new().black().on_yellow())
+                    }
+                };
+                self.write_skipped(&hook.name, reason, style)?;
+                return Ok(());
+            }
+            (HookStatus::Passed, _) if result.cached => {
+                self.write_cached(&hook.name)?;
+                return Ok(());
+            }
+            (HookStatus::Skipped, None) => self.write_dry_run()?,
+            (HookStatus::Passed, _) => self.write_passed()?,
+            (HookStatus::Failed, _) => self.write_failed()?,
+        }
+
+        // The plan of a dry run is always shown.
+        let verbose = self.verbose || hook.verbose;
+        let dry_run = result.status == HookStatus::Skipped;
+        if !verbose && !dry_run && result.status != HookStatus::Failed {
+            return Ok(());
+        }
+
+        writeln!(
+            self.stdout(),
+            "{}",
+            format!("- hook id: {}", hook.id).dimmed()
+        )?;
+        // Tell apart the instances of a hook used more than once.
+        if !hook.alias.is_empty() && hook.alias != hook.id {
+            writeln!(
+                self.stdout(),
+                "{}",
+                format!("- hook alias: {}", hook.alias).dimmed()
+            )?;
+        }
+        if verbose {
+            writeln!(
+                self.stdout(),
+                "{}",
+                format!("- duration: {:.2?}s", result.duration.as_secs_f64()).dimmed()
+            )?;
+        }
+        if let Some(code) = result.exit_code
+            && code != 0
+        {
+            writeln!(self.stdout(), "{}", format!("- exit code: {code}").dimmed())?;
+        }
+        if let Some(timeout) = result.timed_out {
+            writeln!(
+                self.stdout(),
+                "{}",
+                format!("- timed out after {timeout:?}").dimmed()
+            )?;
+        }
+        if result.files_modified {
+            writeln!(
+                self.stdout(),
+                "{}",
+                "- files were modified by this hook".dimmed()
+            )?;
+        }
+
+        // The output goes to the log file instead, if the hook has one.
+        let stdout = result.stdout.trim_ascii();
+        if !stdout.is_empty() && hook.log_file.is_none() {
+            writeln!(self.stdout(), "{}", textwrap::indent(stdout, "  ").dimmed())?;
+        }
+
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        let Some(tally) = &self.tally else {
+            return Ok(());
+        };
+        let count = |n: usize, label: &str, style: Style| {
+            let text = format!("{n} {label}");
+            if n == 0 {
+                text
+            } else {
+                text.style(style).to_string()
+            }
+        };
+        writeln!(
+            self.stdout(),
+            "\n{} {}, {}, {} across {}",
+            "Summary:".bold(),
+            count(tally.counts.passed, "passed", Style::new().green()),
+            count(tally.counts.failed, "failed", Style::new().red()),
+            count(tally.counts.skipped, "skipped", Style::new().yellow()),
+            match tally.projects.len() {
+                1 => "1 project".to_string(),
+                n => format!("{n} projects"),
+            }
+        )?;
+        Ok(())
+    }
+}
+
+/// Reports nothing, used for the extra runs of `--hook-repeat`.
+struct SilentReporter;
+
+impl RunReporter for SilentReporter {
+    fn on_project_start(&mut self, _project: &Project) -> Result<()> {
+        Ok(())
+    }
+
+    fn on_hook_start(&mut self, _hook: &Hook) -> Result<()> {
+        Ok(())
+    }
+
+    fn on_hook_complete(&mut self, _hook: &Hook, _result: &HookResult) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Reports each hook result as a JSON object on its own line, as soon as the hook completes.
+struct JsonLinesReporter {
+    printer: Printer,
+    tally: ResultTally,
+    /// The files no hook was run on, set by `--report-unmatched-files`.
+    unmatched_files: Option<BTreeMap<String, Vec<PathBuf>>>,
+}
+
+/// Counts hook results and the projects they belong to, for the summary at the end of a run.
+#[derive(Debug, Default)]
+struct ResultTally {
+    counts: HookCounts,
+    projects: FxHashSet<usize>,
+}
+
+impl ResultTally {
+    fn add(&mut self, hook: &Hook, status: HookStatus) {
+        self.counts.add(status);
+        self.projects.insert(hook.project().idx());
+    }
+}
+
+/// The last line of `--output-format json`.
+#[derive(Debug, serde::Serialize)]
+struct JsonSummary {
+    summary: JsonSummaryCounts,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct JsonSummaryCounts {
+    #[serde(flatten)]
+    counts: HookCounts,
+    /// The number of projects with at least one hook result.
+    projects: usize,
+    /// The files no hook was run on grouped by extension, with `--report-unmatched-files`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    unmatched_files: Option<BTreeMap<String, Vec<PathBuf>>>,
+}
+
+impl RunReporter for JsonLinesReporter {
+    fn on_project_start(&mut self, _project: &Project) -> Result<()> {
+        Ok(())
+    }
+
+    fn on_hook_start(&mut self, _hook: &Hook) -> Result<()> {
+        Ok(())
+    }
+
+    fn on_hook_complete(&mut self, hook: &Hook, result: &HookResult) -> Result<()> {
+        self.tally.add(hook, result.status);
+        let line = serde_json::to_string(result)?;
+        writeln!(self.printer.stdout(), "{line}")?;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        let summary = JsonSummary {
+            summary: JsonSummaryCounts {
+                counts: self.tally.counts,
+                projects: self.tally.projects.len(),
+                unmatched_files: self.unmatched_files.take(),
+            },
+        };
+        let line = serde_json::to_string(&summary)?;
+        writeln!(self.printer.stdout(), "{line}")?;
+        Ok(())
+    }
+}
+
+/// Counts hook results, and prints them as a single JSON object once all hooks have run.
+struct SummaryReporter {
+    printer: Printer,
+    start: std::time::Instant,
+    summary: RunSummary,
+}
+
+/// The summary printed by `--summary-json-only`.
+///
+/// Bump the version when changing the meaning of existing fields.
+#[derive(Debug, Default, serde::Serialize)]
+struct RunSummary {
+    version: u32,
+    #[serde(flatten)]
+    counts: HookCounts,
+    #[serde(rename = "duration_ms", serialize_with = "serialize_millis")]
+    duration: Duration,
+    projects: BTreeMap<String, HookCounts>,
+    /// The files no hook was run on grouped by extension, with `--report-unmatched-files`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    unmatched_files: Option<BTreeMap<String, Vec<PathBuf>>>,
+}
+
+#[derive(Debug, Default, Clone, Copy, serde::Serialize)]
+struct HookCounts {
+    total: usize,
+    passed: usize,
+    failed: usize,
+    skipped: usize,
+}
+
+impl HookCounts {
+    fn add(&mut self, status: HookStatus) {
+        self.total += 1;
+        match status {
+            HookStatus::Passed => self.passed += 1,
+            HookStatus::Failed => self.failed += 1,
+            HookStatus::Skipped => self.skipped += 1,
+        }
+    }
+}
+
+impl SummaryReporter {
+    const VERSION: u32 = 1;
+
+    fn new(printer: Printer) -> Self {
+        Self {
+            printer,
+            start: std::time::Instant::now(),
+            summary: RunSummary {
+                version: Self::VERSION,
+                ..RunSummary::default()
+            },
+        }
+    }
+}
+
+impl RunReporter for SummaryReporter {
+    fn on_project_start(&mut self, project: &Project) -> Result<()> {
+        self.summary
+            .projects
+            .entry(project.to_string())
+            .or_default();
+        Ok(())
+    }
+
+    fn on_hook_start(&mut self, _hook: &Hook) -> Result<()> {
+        Ok(())
+    }
+
+    fn on_hook_complete(&mut self, hook: &Hook, result: &HookResult) -> Result<()> {
+        self.summary.counts.add(result.status);
+        self.summary
+            .projects
+            .entry(hook.project().to_string())
+            .or_default()
+            .add(result.status);
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.summary.duration = self.start.elapsed();
+        let summary = serde_json::to_string(&self.summary)?;
+        writeln!(self.printer.stdout(), "{summary}")?;
+        Ok(())
+    }
+}
+
+/// Forwards hook results to another reporter, and appends the output of each hook to a log file.
+struct LogFileReporter {
+    inner: Box<dyn RunReporter>,
+    /// Hooks may color their output, which is stripped from the log.
+    file: StripStream<std::fs::File>,
+}
+
+impl LogFileReporter {
+    fn new(inner: Box<dyn RunReporter>, path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            fs_err::create_dir_all(parent)?;
+        }
+        let file = fs_err::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+        Ok(Self {
+            inner,
+            file: StripStream::new(file.into_file()),
+        })
+    }
+}
+
+impl RunReporter for LogFileReporter {
+    fn on_project_start(&mut self, project: &Project) -> Result<()> {
+        self.inner.on_project_start(project)
+    }
+
+    fn on_hook_start(&mut self, hook: &Hook) -> Result<()> {
+        self.inner.on_hook_start(hook)
+    }
+
+    fn on_batch_progress(&mut self, hook: &Hook, progress: BatchProgress) -> Result<()> {
+        self.inner.on_batch_progress(hook, progress)
+    }
+
+    fn on_hook_complete(&mut self, hook: &Hook, result: &HookResult) -> Result<()> {
+        // Hooks that did not run have no output to log.
+        if result.status != HookStatus::Skipped {
+            let exit_code = result
+                .exit_code
+                .map_or_else(|| "none".to_string(), |code| code.to_string());
+            writeln!(
+                self.file,
+                "==> {}:{hook} ({}, exit code {exit_code}, {}ms) <==",
+                hook.project(),
+                result.status.as_str(),
+                result.duration.as_millis()
+            )?;
+            self.file.write_all(result.stdout.as_bytes())?;
+            if !result.stdout.is_empty() && !result.stdout.ends_with('\n') {
+                writeln!(self.file)?;
+            }
+        }
+        self.inner.on_hook_complete(hook, result)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.inner.finish()
+    }
+}
+
+/// Reports hook results like [`StatusPrinter`], and writes them to a JUnit XML file once all hooks have run.
+struct JunitReporter {
+    status_printer: StatusPrinter,
+    path: PathBuf,
+    /// The test cases of each project, in the order the projects were run.
+    suites: Vec<(String, Vec<JunitTestCase>)>,
+}
+
+struct JunitTestCase {
+    hook_id: String,
+    duration: Duration,
+    status: HookStatus,
+    skip_reason: Option<SkipReason>,
+    timed_out: Option<Duration>,
+    stdout: String,
+}
+
+impl JunitReporter {
+    fn new(status_printer: StatusPrinter, path: &Path) -> Self {
+        Self {
+            status_printer,
+            path: path.to_path_buf(),
+            suites: Vec::new(),
+        }
+    }
+
+    fn to_xml(&self) -> String {
+        let count = |cases: &[JunitTestCase], status: HookStatus| {
+            cases.iter().filter(|case| case.status == status).count()
+        };
+        let time = |cases: &[JunitTestCase]| {
+            cases
+                .iter()
+                .map(|case| case.duration.as_secs_f64())
+                .sum::<f64>()
+        };
+        let total = |f: &dyn Fn(&[JunitTestCase]) -> usize| {
+            self.suites.iter().map(|(_, cases)| f(cases)).sum::<usize>()
+        };
+
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        let _ = writeln!(
+            xml,
+            r#"<testsuites name="prek" tests="{}" failures="{}" skipped="{}" time="{:.3}">"#,
+            total(&|cases| cases.len()),
+            total(&|cases| count(cases, HookStatus::Failed)),
+            total(&|cases| count(cases, HookStatus::Skipped)),
+            self.suites
+                .iter()
+                .map(|(_, cases)| time(cases))
+                .sum::<f64>(),
+        );
+        for (project, cases) in &self.suites {
+            let project = xml_escape(project);
+            let _ = writeln!(
+                xml,
+                r#"  <testsuite name="{project}" tests="{}" failures="{}" skipped="{}" time="{:.3}">"#,
+                cases.len(),
+                count(cases, HookStatus::Failed),
+                count(cases, HookStatus::Skipped),
+                time(cases),
+            );
+            for case in cases {
+                let _ = write!(
+                    xml,
+                    r#"    <testcase name="{}" classname="{project}" time="{:.3}""#,
+                    xml_escape(&case.hook_id),
+                    case.duration.as_secs_f64(),
+                );
+                match case.status {
+                    HookStatus::Passed => xml.push_str("/>\n"),
+                    HookStatus::Failed => {
+                        let message = match case.timed_out {
+                            Some(timeout) => format!("timed out after {timeout:?}"),
+                            None => "hook failed".to_string(),
+                        };
+                        let _ = write!(
+                            xml,
+                            ">\n      <failure message=\"{message}\">{}</failure>\n    </testcase>\n",
+                            xml_escape(case.stdout.trim_ascii())
+                        );
+                    }
+                    HookStatus::Skipped => {
+                        let message = match case.skip_reason {
+                            Some(SkipReason::Selector) => "skipped",
+                            Some(SkipReason::Excluded) => "excluded",
+                            Some(SkipReason::NoFiles) => "no files to check",
+                            Some(SkipReason::Unimplemented) => "unimplemented yet",
+                            None => "dry run",
+                        };
+                        let _ = write!(
+                            xml,
+                            ">\n      <skipped message=\"{message}\"/>\n    </testcase>\n"
+                        );
+                    }
+                }
+            }
+            xml.push_str("  </testsuite>\n");
+        }
+        xml.push_str("</testsuites>\n");
+        xml
+    }
+}
+
+impl RunReporter for JunitReporter {
+    fn on_project_start(&mut self, project: &Project) -> Result<()> {
+        self.suites.push((project.to_string(), Vec::new()));
+        self.status_printer.on_project_start(project)
+    }
+
+    fn on_hook_start(&mut self, hook: &Hook) -> Result<()> {
+        self.status_printer.on_hook_start(hook)
+    }
+
+    fn on_batch_progress(&mut self, hook: &Hook, progress: BatchProgress) -> Result<()> {
+        self.status_printer.on_batch_progress(hook, progress)
+    }
+
+    fn on_hook_complete(&mut self, hook: &Hook, result: &HookResult) -> Result<()> {
+        if let Some((_, cases)) = self.suites.last_mut() {
+            cases.push(JunitTestCase {
+                hook_id: hook.label().to_string(),
+                duration: result.duration,
+                status: result.status,
+                skip_reason: result.skip_reason,
+                timed_out: result.timed_out,
+                stdout: anstream::adapter::strip_str(&result.stdout).to_string(),
+            });
+        }
+        self.status_printer.on_hook_complete(hook, result)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs_err::create_dir_all(parent)?;
+        }
+        fs_err::write(&self.path, self.to_xml())?;
+        Ok(())
+    }
+}
+
+/// Escape text for XML, dropping the control characters XML does not allow, like ANSI colors.
+fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\t' | '\n' | '\r' => escaped.push(c),
+            c if c.is_control() => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
 /// Run all hooks.
 #[allow(clippy::fn_params_excessive_bools)]
 async fn run_hooks(
     workspace: &Workspace,
     hooks: &[InstalledHook],
+    skipped_hooks: &FxHashMap<(usize, usize), SkipReason>,
     filenames: Vec<PathBuf>,
+    hook_stage: Stage,
     store: &Store,
     show_diff_on_failure: bool,
     dry_run: bool,
+    no_cache: bool,
+    hook_repeat: u32,
+    include_nested_project_files: bool,
+    max_total_duration: Option<Duration>,
+    per_project_status: Option<&Path>,
+    project_order: &[String],
+    output_dir: Option<&Path>,
+    output_format: RunOutputFormat,
+    output: Option<&Path>,
+    log_file: Option<&Path>,
+    unmatched_files: Option<&BTreeMap<String, Vec<PathBuf>>>,
     verbose: bool,
     printer: Printer,
 ) -> Result<ExitStatus> {
     debug_assert!(!hooks.is_empty(), "No hooks to run");
 
-    let printer = StatusPrinter::for_hooks(hooks, printer);
+    let mut reporter: Box<dyn RunReporter> = match output_format {
+        RunOutputFormat::Text => Box::new(StatusPrinter::for_hooks(hooks, verbose, printer)),
+        RunOutputFormat::Json => Box::new(JsonLinesReporter {
+            printer,
+            tally: ResultTally::default(),
+            unmatched_files: unmatched_files.cloned(),
+        }),
+        RunOutputFormat::SummaryJson => {
+            let mut reporter = SummaryReporter::new(printer);
+            reporter.summary.unmatched_files = unmatched_files.cloned();
+            Box::new(reporter)
+        }
+        RunOutputFormat::Junit => Box::new(JunitReporter::new(
+            StatusPrinter::for_hooks(hooks, verbose, printer),
+            output.expect("`--output` is required with `--output-format junit`"),
+        )),
+    };
+    if let Some(path) = log_file {
+        reporter = Box::new(LogFileReporter::new(reporter, path)?);
+    }
 
+    let cache = (!no_cache && !dry_run && hook_repeat == 1).then(|| ResultCache::new(store));
     let mut success = true;
 
+    // Projects without any hooks to run are reported as empty.
+    let mut project_status: BTreeMap<String, ProjectStatus> = workspace
+        .projects()
+        .iter()
+        .map(|project| (project.to_string(), ProjectStatus::Empty))
+        .collect();
+
     // Group hooks by project to run them in order of their depth in the workspace.
     #[allow(clippy::mutable_key_type)]
     let mut project_to_hooks: FxHashMap<&Project, Vec<&InstalledHook>> = FxHashMap::default();
@@ -474,64 +1607,217 @@ async fn run_hooks(
             .push(hook);
     }
 
-    // Sort projects by their depth in the workspace.
+    // Sort projects by `--project-order` first, then by their depth in the workspace.
     let mut project_to_hooks: Vec<_> = project_to_hooks.into_iter().collect();
-    project_to_hooks.sort_by_key(|(_, hooks)| hooks[0].project().idx());
+    project_to_hooks.sort_by_key(|(project, hooks)| {
+        let position = project_order
+            .iter()
+            .position(|id| *id == project.to_string())
+            .unwrap_or(usize::MAX);
+        (position, hooks[0].project().idx())
+    });
 
-    let projects_len = project_to_hooks.len();
-    let mut first = true;
+    for (project, _) in &project_to_hooks {
+        project_status.insert(project.to_string(), ProjectStatus::Skipped);
+    }
 
-    // Hooks might modify the files, so they must be run sequentially.
-    'outer: for (_, mut hooks) in project_to_hooks {
+    // The `fail_fast` of the workspace root config is the default for all projects.
+    let default_fail_fast = workspace
+        .projects()
+        .iter()
+        .find(|project| project.is_root())
+        .and_then(|project| project.config().fail_fast);
+
+    for (_, hooks) in &mut project_to_hooks {
         hooks.sort_by_key(|h| h.idx);
+    }
+    let planned_hooks: Vec<&InstalledHook> = project_to_hooks
+        .iter()
+        .flat_map(|(_, hooks)| hooks.iter().copied())
+        .collect();
+
+    // Snapshot the working tree, to only show the changes made by hooks on failure.
+    // Without any commit there is nothing to snapshot, and the index is compared instead.
+    let diff_base = if show_diff_on_failure && !output_format.is_json() {
+        git::stash_create(workspace.root()).await.ok().flatten()
+    } else {
+        None
+    };
 
+    let deadline = max_total_duration.map(|duration| tokio::time::Instant::now() + duration);
+    // The hooks that were run or skipped, to report the others if the run is out of time.
+    let mut reported_hooks = FxHashSet::default();
+    let mut out_of_time = false;
+
+    // Hooks might modify the files, so they must be run sequentially.
+    'outer: for (_, hooks) in project_to_hooks {
         let project = hooks[0].project();
-        if projects_len > 1 || !project.is_root() {
-            writeln!(
-                printer.stdout(),
-                "{}{}:",
-                if first { "" } else { "\n" },
-                format!("Running hooks for `{}`", project.to_string().cyan()).bold()
-            )?;
-            first = false;
-        }
+        let mut project_success = true;
+        reporter.on_project_start(project)?;
         let mut diff = git::get_diff(project.path()).await?;
 
-        let fail_fast = project.config().fail_fast.unwrap_or(false);
+        let fail_fast = project
+            .config()
+            .fail_fast
+            .or(default_fail_fast)
+            .unwrap_or(false);
 
-        let filter = FileFilter::for_project(filenames.iter(), project);
+        let nested_projects = if include_nested_project_files {
+            vec![]
+        } else {
+            workspace.nested_projects(project)
+        };
+        let filter = FileFilter::for_project(filenames.iter(), project, &nested_projects);
         trace!("Files for `{project}` after filtered: {}", filter.len());
 
         for hook in hooks {
-            let (hook_success, new_diff) =
-                run_hook(hook, &filter, store, diff, verbose, dry_run, &printer).await?;
+            if deadline.is_some_and(|deadline| tokio::time::Instant::now() >= deadline) {
+                out_of_time = true;
+                break 'outer;
+            }
+            reported_hooks.insert((project.idx(), hook.idx));
+
+            if let Some(reason) = skipped_hooks.get(&(project.idx(), hook.idx)) {
+                let result = HookResult::skipped(hook, *reason);
+                reporter.on_hook_complete(hook, &result)?;
+                continue;
+            }
+
+            let start = std::time::Instant::now();
+            let run = async {
+                if hook_repeat > 1 && !dry_run {
+                    run_hook_repeated(
+                        hook,
+                        &filter,
+                        hook_stage,
+                        store,
+                        diff,
+                        verbose,
+                        hook_repeat,
+                        &mut *reporter,
+                    )
+                    .await
+                } else {
+                    run_hook(
+                        hook,
+                        &filter,
+                        hook_stage,
+                        store,
+                        cache.as_ref(),
+                        diff,
+                        verbose,
+                        dry_run,
+                        &mut *reporter,
+                    )
+                    .await
+                }
+            };
+            let outcome = match deadline {
+                Some(deadline) => tokio::time::timeout_at(deadline, run).await.ok(),
+                None => Some(run.await),
+            };
+            // Dropping the run terminates the hook processes.
+            let Some(outcome) = outcome else {
+                let result = HookResult {
+                    duration: start.elapsed(),
+                    stdout: "The hook was terminated, the run exceeded `--max-total-duration`."
+                        .to_string(),
+                    ..HookResult::new(hook, HookStatus::Failed)
+                };
+                reporter.on_hook_complete(hook, &result)?;
+                success = false;
+                project_status.insert(project.to_string(), ProjectStatus::Failed);
+                out_of_time = true;
+                break 'outer;
+            };
+            let (result, new_diff) = outcome?;
+            reporter.on_hook_complete(hook, &result)?;
+            let hook_success = result.status != HookStatus::Failed;
+
+            if let Some(output_dir) = output_dir
+                && !dry_run
+            {
+                collect_artifacts(hook, output_dir)
+                    .with_context(|| format!("Failed to collect artifacts of hook `{hook}`"))?;
+            }
 
             success &= hook_success;
+            project_success &= hook_success;
+            project_status.insert(project.to_string(), ProjectStatus::from(project_success));
             diff = new_diff;
-            if !success && (fail_fast || hook.fail_fast) {
+            // A failing hook with `fail_fast` stops the whole run,
+            // while the project's `fail_fast` only stops the rest of the project.
+            if !success && hook.fail_fast {
                 break 'outer;
             }
+            if !project_success && fail_fast {
+                break;
+            }
         }
     }
 
-    if !success && show_diff_on_failure {
-        writeln!(printer.stdout(), "All changes made by hooks:")?;
+    reporter.finish()?;
+
+    if out_of_time {
+        let unrun_hooks: Vec<_> = planned_hooks
+            .iter()
+            .filter(|hook| !reported_hooks.contains(&(hook.project().idx(), hook.idx)))
+            .collect();
+        writeln!(
+            printer.stderr(),
+            "{}: The run exceeded `{}` of {:?}, {} did not run{}",
+            "error".red().bold(),
+            "--max-total-duration".cyan(),
+            max_total_duration.unwrap_or_default(),
+            match unrun_hooks.len() {
+                1 => "1 hook".to_string(),
+                n => format!("{n} hooks"),
+            },
+            if unrun_hooks.is_empty() { "" } else { ":" },
+        )?;
+        for hook in unrun_hooks {
+            if hook.project().is_root() {
+                writeln!(printer.stderr(), "  - {hook}")?;
+            } else {
+                writeln!(printer.stderr(), "  - {}:{hook}", hook.project())?;
+            }
+        }
+    }
+
+    if let Some(path) = per_project_status {
+        let content = serde_json::to_string_pretty(&project_status)?;
+        fs_err::tokio::write(path, content).await?;
+    }
+
+    if !success && show_diff_on_failure && !output_format.is_json() {
         let color = if *USE_COLOR {
             "--color=always"
         } else {
             "--color=never"
         };
-        git::git_cmd("git diff")?
-            .arg("--no-pager")
+        let mut cmd = git::git_cmd("git diff")?;
+        cmd.arg("--no-pager")
             .arg("diff")
             .arg("--no-ext-diff")
-            .arg(color)
+            .arg(color);
+        if let Some(diff_base) = &diff_base {
+            cmd.arg(diff_base);
+        }
+        let output = cmd
             .arg("--")
             .arg(workspace.root())
             .check(true)
-            .spawn()?
-            .wait()
+            .output()
             .await?;
+        // Only the workspace root is diffed, so changes are not repeated for each project.
+        if !output.stdout.is_empty() {
+            writeln!(printer.stdout(), "All changes made by hooks:")?;
+            write!(
+                printer.stdout(),
+                "{}",
+                String::from_utf8_lossy(&output.stdout)
+            )?;
+        }
     }
 
     if success {
@@ -541,6 +1827,220 @@ async fn run_hooks(
     }
 }
 
+#[derive(Debug, Clone, Copy, serde::Serialize)]
+#[serde(rename_all = "kebab-case")]
+enum ProjectStatus {
+    /// All hooks of the project passed.
+    Passed,
+    /// At least one hook of the project failed.
+    Failed,
+    /// The project has hooks, but they were not run because of `fail_fast`.
+    Skipped,
+    /// The project has no hooks to run.
+    Empty,
+}
+
+impl From<bool> for ProjectStatus {
+    fn from(success: bool) -> Self {
+        if success { Self::Passed } else { Self::Failed }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "kebab-case")]
+enum HookStatus {
+    Passed,
+    Failed,
+    /// The hook had no files to check, is not implemented yet, or was not run because of `--dry-run`.
+    Skipped,
+}
+
+impl HookStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            HookStatus::Passed => "passed",
+            HookStatus::Failed => "failed",
+            HookStatus::Skipped => "skipped",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum SkipReason {
+    /// The hook was skipped by id with `--skip` or `SKIP`.
+    Selector,
+    /// The hook was excluded with `--exclude-hook`.
+    Excluded,
+    NoFiles,
+    Unimplemented,
+}
+
+/// The result of a hook run, reported with `--output-format json`.
+#[derive(Debug, serde::Serialize)]
+struct HookResult {
+    project: String,
+    hook_id: String,
+    /// The alias of the hook, to tell apart hooks with the same id.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    alias: Option<String>,
+    name: String,
+    status: HookStatus,
+    #[serde(skip)]
+    skip_reason: Option<SkipReason>,
+    #[serde(rename = "duration_ms", serialize_with = "serialize_millis")]
+    duration: Duration,
+    /// The files the hook was run on.
+    files: Vec<String>,
+    /// The exit code of the hook, `None` if the hook was not run or timed out.
+    exit_code: Option<i32>,
+    /// The timeout the hook was killed after, if it ran too long.
+    #[serde(
+        rename = "timed_out_after_ms",
+        serialize_with = "serialize_optional_millis"
+    )]
+    timed_out: Option<Duration>,
+    files_modified: bool,
+    /// Whether the hook was not run because it already passed on the same files.
+    cached: bool,
+    /// The output of the hook, with stderr merged into stdout like pre-commit.
+    stdout: String,
+}
+
+impl HookResult {
+    fn new(hook: &Hook, status: HookStatus) -> Self {
+        Self {
+            project: hook
+                .project()
+                .relative_path()
+                .to_string_lossy()
+                .into_owned(),
+            hook_id: hook.id.clone(),
+            alias: (!hook.alias.is_empty() && hook.alias != hook.id).then(|| hook.alias.clone()),
+            name: hook.name.clone(),
+            status,
+            skip_reason: None,
+            duration: Duration::ZERO,
+            files: Vec::new(),
+            exit_code: None,
+            timed_out: None,
+            files_modified: false,
+            cached: false,
+            stdout: String::new(),
+        }
+    }
+
+    fn skipped(hook: &Hook, reason: SkipReason) -> Self {
+        Self {
+            skip_reason: Some(reason),
+            ..Self::new(hook, HookStatus::Skipped)
+        }
+    }
+}
+
+fn serialize_millis<S: serde::Serializer>(
+    duration: &Duration,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u64(u64::try_from(duration.as_millis()).unwrap_or(u64::MAX))
+}
+
+#[allow(clippy::ref_option)]
+fn serialize_optional_millis<S: serde::Serializer>(
+    duration: &Option<Duration>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match duration {
+        Some(duration) => serialize_millis(duration, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Copy the files matching the hook's `output_artifacts` glob into
+/// `<output_dir>/<project>/<hook-id>/`, keeping their path relative to the project.
+fn collect_artifacts(hook: &Hook, output_dir: &Path) -> Result<()> {
+    let Some(artifacts) = hook.output_artifacts.as_ref() else {
+        return Ok(());
+    };
+
+    let project_dir = hook.project().path();
+    let target = output_dir
+        .join(hook.project().relative_path())
+        .join(&hook.id);
+
+    let skip_dir = output_dir.to_path_buf();
+    let walker = WalkBuilder::new(project_dir)
+        .standard_filters(false)
+        .filter_entry(move |entry| {
+            entry.file_name() != ".git" && !entry.path().starts_with(&skip_dir)
+        })
+        .build();
+
+    for entry in walker {
+        let entry = entry?;
+        if !entry.file_type().is_some_and(|t| t.is_file()) {
+            continue;
+        }
+        let path = entry.path();
+        if !artifacts.matched(path, false).is_whitelist() {
+            continue;
+        }
+
+        let relative = path.strip_prefix(project_dir)?;
+        let dest = target.join(relative);
+        trace!(
+            "Collecting artifact `{}` to `{}`",
+            path.display(),
+            dest.display()
+        );
+        if let Some(parent) = dest.parent() {
+            fs_err::create_dir_all(parent)?;
+        }
+        fs_err::copy(path, &dest)?;
+    }
+
+    Ok(())
+}
+
+/// Forward the progress of the batches of `hook` to the reporter while it runs.
+async fn with_batch_progress<T>(
+    hook: &Hook,
+    run: impl Future<Output = Result<T>>,
+    reporter: &mut dyn RunReporter,
+) -> Result<T> {
+    let mut progress = crate::run::subscribe_batch_progress();
+    progress.mark_unchanged();
+    tokio::pin!(run);
+    loop {
+        tokio::select! {
+            output = &mut run => return output,
+            Ok(()) = progress.changed() => {
+                let progress = *progress.borrow_and_update();
+                reporter.on_batch_progress(hook, progress)?;
+            }
+        }
+    }
+}
+
+/// Explain how to run a hook that is not run in the current stage.
+fn stage_hint(hook: &Hook) -> String {
+    let Stages::Some(stages) = &hook.stages else {
+        unreachable!("hooks of all stages run in every stage");
+    };
+    let mut stages = stages.iter().copied().collect::<Vec<_>>();
+    stages.sort_by_key(|stage| (*stage != Stage::Manual, stage.to_string()));
+    format!(
+        "Hook `{}` only runs in {} {}, use `{}` to run it",
+        hook.id.cyan(),
+        if stages.len() == 1 { "stage" } else { "stages" },
+        stages
+            .iter()
+            .map(|stage| format!("`{}`", stage.cyan()))
+            .collect::<Vec<_>>()
+            .join(", "),
+        format!("--hook-stage {}", stages[0]).cyan()
+    )
+}
+
 /// Shuffle the files so that they more evenly fill out the xargs
 /// partitions, but do it deterministically in case a hook cares about ordering.
 fn shuffle<T>(filenames: &mut [T]) {
@@ -552,12 +2052,14 @@ fn shuffle<T>(filenames: &mut [T]) {
 async fn run_hook(
     hook: &InstalledHook,
     filter: &FileFilter<'_>,
+    hook_stage: Stage,
     store: &Store,
+    cache: Option<&ResultCache>,
     diff: Vec<u8>,
     verbose: bool,
     dry_run: bool,
-    printer: &StatusPrinter,
-) -> Result<(bool, Vec<u8>)> {
+    reporter: &mut dyn RunReporter,
+) -> Result<(HookResult, Vec<u8>)> {
     let mut filenames = filter.for_hook(hook);
     trace!(
         "Files for `{}` after filtered: {}",
@@ -565,26 +2067,38 @@ async fn run_hook(
         filenames.len()
     );
 
-    if filenames.is_empty() && !hook.always_run {
-        printer.write_skipped(
-            &hook.name,
-            StatusPrinter::NO_FILES,
-            Style::new().black().on_cyan(),
-        )?;
-        return Ok((true, diff));
+    // Stages like `post-commit` have no files, their hooks run without any.
+    let runs_without_files = !hook_stage.operate_on_files()
+        && matches!(&hook.stages, Stages::Some(stages) if stages.contains(&hook_stage));
+    if filenames.is_empty() && !hook.always_run && !runs_without_files {
+        return Ok((HookResult::skipped(hook, SkipReason::NoFiles), diff));
     }
 
     if !Language::supported(hook.language) {
-        printer.write_skipped(
-            &hook.name,
-            StatusPrinter::UNIMPLEMENTED,
-            Style::new().black().on_yellow(),
-        )?;
-        return Ok((true, diff));
+        return Ok((HookResult::skipped(hook, SkipReason::Unimplemented), diff));
     }
 
-    printer.write_running(&hook.name)?;
-    std::io::stdout().flush()?;
+    // A hook that already passed on the same files doesn't need to run again,
+    // unless its output is wanted.
+    let cache_entry = cache.and_then(|cache| cache.entry(hook, &filenames));
+    if let Some(entry) = &cache_entry
+        && !verbose
+        && !hook.verbose
+        && entry.is_fresh()
+    {
+        trace!("Using cached result of hook `{hook}`");
+        let result = HookResult {
+            cached: true,
+            files: filenames
+                .iter()
+                .map(|filename| filename.to_string_lossy().into_owned())
+                .collect(),
+            ..HookResult::new(hook, HookStatus::Passed)
+        };
+        return Ok((result, diff));
+    }
+
+    reporter.on_hook_start(hook)?;
 
     let start = std::time::Instant::now();
 
@@ -595,25 +2109,49 @@ async fn run_hook(
         vec![]
     };
 
+    let mut timed_out = None;
     let (status, output) = if dry_run {
+        // The files are only listed in verbose mode, the count is always shown.
+        let verbose = verbose || hook.verbose;
         let mut output = Vec::new();
-        if !filenames.is_empty() {
+        if filenames.is_empty() {
+            writeln!(output, "`{hook}` would be run without files")?;
+        } else {
             writeln!(
                 output,
-                "`{}` would be run on {} files:",
+                "`{}` would be run on {} files{}",
                 hook,
-                filenames.len()
+                filenames.len(),
+                if verbose { ":" } else { "" }
             )?;
         }
-        for filename in &filenames {
-            writeln!(output, "- {}", filename.to_string_lossy())?;
+        if verbose {
+            for filename in &filenames {
+                writeln!(output, "- {}", filename.to_string_lossy())?;
+            }
         }
         (0, output)
     } else {
-        hook.language
-            .run(hook, &filenames, store)
-            .await
-            .context(format!("Failed to run hook `{hook}`"))?
+        take_hook_output();
+        let run = with_batch_progress(hook, hook.language.run(hook, &filenames, store), reporter);
+        // Dropping the run on timeout terminates the hook processes,
+        // the output they wrote until then is kept.
+        let output = match hook_timeout(hook) {
+            Some(timeout) => tokio::time::timeout(timeout, run)
+                .await
+                .unwrap_or_else(|_| {
+                    timed_out = Some(timeout);
+                    let mut output = take_hook_output();
+                    if !output.is_empty() && !output.ends_with(b"\n") {
+                        output.push(b'\n');
+                    }
+                    writeln!(output, "timed out after {timeout:?}")?;
+                    Ok((1, output))
+                }),
+            None => run.await,
+        };
+        take_hook_output();
+        output.context(format!("Failed to run hook `{hook}`"))?
     };
 
     let duration = start.elapsed();
@@ -621,62 +2159,143 @@ async fn run_hook(
     let new_diff = git::get_diff(hook.work_dir()).await?;
     let file_modified = diff != new_diff;
     let success = status == 0 && !file_modified;
-    if dry_run {
-        printer.write_dry_run()?;
+
+    // To be consistent with pre-commit, merge stderr into stdout.
+    let stdout = output.trim_ascii();
+    if let Some(file) = hook.log_file.as_deref()
+        && !stdout.is_empty()
+        && (verbose || hook.verbose || !success)
+    {
+        let mut file = fs_err::tokio::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(file)
+            .await?;
+        // Colors are meant for the terminal, not for the log.
+        file.write_all(&anstream::adapter::strip_bytes(stdout).into_vec())
+            .await?;
+        file.sync_all().await?;
+    }
+
+    if success && let Some(entry) = cache_entry {
+        if let Err(err) = entry.save() {
+            debug!("Failed to save the result of hook `{hook}` to cache: {err}");
+        }
+    }
+
+    let status_kind = if dry_run {
+        HookStatus::Skipped
     } else if success {
-        printer.write_passed()?;
+        HookStatus::Passed
     } else {
-        printer.write_failed()?;
+        HookStatus::Failed
+    };
+    let result = HookResult {
+        duration,
+        files: filenames
+            .iter()
+            .map(|filename| filename.to_string_lossy().into_owned())
+            .collect(),
+        exit_code: (!dry_run && timed_out.is_none()).then_some(status),
+        timed_out,
+        files_modified: file_modified,
+        stdout: String::from_utf8_lossy(&output).into_owned(),
+        ..HookResult::new(hook, status_kind)
+    };
+
+    Ok((result, new_diff))
+}
+
+/// Run a hook `repeat` times on the same files, and fail if the outcome or the output
+/// differs from the first run.
+async fn run_hook_repeated(
+    hook: &InstalledHook,
+    filter: &FileFilter<'_>,
+    hook_stage: Stage,
+    store: &Store,
+    diff: Vec<u8>,
+    verbose: bool,
+    repeat: u32,
+    reporter: &mut dyn RunReporter,
+) -> Result<(HookResult, Vec<u8>)> {
+    // Keep the original content of the files, to restore them before each run.
+    let originals = filter
+        .for_hook(hook)
+        .into_iter()
+        .map(|filename| {
+            let path = hook.work_dir().join(filename);
+            let content = fs_err::read(&path)?;
+            Ok((path, content))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let (mut result, mut new_diff) = run_hook(
+        hook,
+        filter,
+        hook_stage,
+        store,
+        None,
+        diff.clone(),
+        verbose,
+        false,
+        reporter,
+    )
+    .await?;
+    if result.skip_reason.is_some() {
+        return Ok((result, new_diff));
     }
 
-    if verbose || hook.verbose || !success {
-        writeln!(
-            printer.stdout(),
-            "{}",
-            format!("- hook id: {}", hook.id).dimmed()
-        )?;
-        if verbose || hook.verbose {
-            writeln!(
-                printer.stdout(),
-                "{}",
-                format!("- duration: {:.2?}s", duration.as_secs_f64()).dimmed()
-            )?;
-        }
-        if status != 0 {
-            writeln!(
-                printer.stdout(),
-                "{}",
-                format!("- exit code: {status}").dimmed()
-            )?;
+    let normalize = |output: &str| {
+        output
+            .lines()
+            .map(str::trim_end)
+            .collect::<Vec<_>>()
+            .join("\n")
+            .trim()
+            .to_string()
+    };
+    let first_output = normalize(&result.stdout);
+
+    let mut discrepancies = Vec::new();
+    for run in 2..=repeat {
+        for (path, content) in &originals {
+            fs_err::tokio::write(path, content).await?;
         }
-        if file_modified {
-            writeln!(
-                printer.stdout(),
-                "{}",
-                "- files were modified by this hook".dimmed()
-            )?;
+        let (other, other_diff) = run_hook(
+            hook,
+            filter,
+            hook_stage,
+            store,
+            None,
+            diff.clone(),
+            verbose,
+            false,
+            &mut SilentReporter,
+        )
+        .await?;
+        new_diff = other_diff;
+
+        if other.status != result.status {
+            discrepancies.push(format!(
+                "run {run} {}, but run 1 {}",
+                other.status.as_str(),
+                result.status.as_str()
+            ));
+        } else if normalize(&other.stdout) != first_output {
+            discrepancies.push(format!("run {run} printed a different output than run 1"));
         }
+    }
 
-        // To be consistent with pre-commit, merge stderr into stdout.
-        let stdout = output.trim_ascii();
-        if !stdout.is_empty() {
-            if let Some(file) = hook.log_file.as_deref() {
-                let mut file = fs_err::tokio::OpenOptions::new()
-                    .create(true)
-                    .append(true)
-                    .open(file)
-                    .await?;
-                file.write_all(stdout).await?;
-                file.sync_all().await?;
-            } else {
-                writeln!(
-                    printer.stdout(),
-                    "{}",
-                    textwrap::indent(&String::from_utf8_lossy(stdout), "  ").dimmed()
-                )?;
-            }
+    if !discrepancies.is_empty() {
+        result.status = HookStatus::Failed;
+        writeln!(
+            result.stdout,
+            "\nResults of the hook differ between {repeat} runs:"
+        )?;
+        for discrepancy in discrepancies {
+            writeln!(result.stdout, "- {discrepancy}")?;
         }
     }
 
-    Ok((success, new_diff))
+    Ok((result, new_diff))
 }
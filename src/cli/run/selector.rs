@@ -223,6 +223,38 @@ impl Selectors {
         included
     }
 
+    /// Check if a hook is excluded by a skip selector naming the hook itself.
+    ///
+    /// Such hooks are still reported as `Skipped` in the run output, like `SKIP` in `pre-commit`,
+    /// while hooks excluded by a project skip are hidden entirely.
+    pub(crate) fn skips_hook_id(&self, hook: &Hook) -> bool {
+        let skipped = self.skips.iter().any(|skip| {
+            matches!(
+                skip.expr,
+                SelectorExpr::HookId(_) | SelectorExpr::ProjectHook { .. }
+            ) && skip.matches_hook(hook)
+        });
+        if !skipped {
+            return false;
+        }
+
+        self.includes.is_empty()
+            || self
+                .includes
+                .iter()
+                .any(|include| include.matches_hook(hook))
+    }
+
+    /// Whether the hook is selected by its id, rather than only as part of a project.
+    pub(crate) fn selects_hook_id(&self, hook: &Hook) -> bool {
+        self.includes.iter().any(|include| {
+            matches!(
+                include.expr,
+                SelectorExpr::HookId(_) | SelectorExpr::ProjectHook { .. }
+            ) && include.matches_hook(hook)
+        })
+    }
+
     pub(crate) fn matches_path(&self, path: &Path) -> bool {
         let mut usage = self.usage.lock().unwrap();
 
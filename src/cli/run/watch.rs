@@ -0,0 +1,343 @@
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use anyhow::Result;
+use ignore::WalkBuilder;
+use owo_colors::OwoColorize;
+use rustc_hash::FxHashMap;
+
+use crate::cli::ExitStatus;
+use crate::cli::run::RunOptions;
+use crate::fs::{CWD, Simplified};
+use crate::printer::Printer;
+use crate::workspace::Workspace;
+
+/// Source of file changes for `--watch`.
+pub(crate) trait Watcher {
+    /// Wait for the next batch of changed files, `None` if there will be no more changes.
+    async fn changes(&mut self) -> Result<Option<Vec<PathBuf>>>;
+
+    /// Forget the changes made so far, like the ones made by the hooks themselves.
+    fn reset(&mut self);
+}
+
+/// Watches a directory by polling the modification time of its files.
+///
+/// Files ignored by git are not watched.
+pub(crate) struct PollingWatcher {
+    root: PathBuf,
+    interval: Duration,
+    /// How long the files must stay unchanged before the changes are reported.
+    debounce: Duration,
+    files: FxHashMap<PathBuf, (SystemTime, u64)>,
+}
+
+impl PollingWatcher {
+    pub(crate) fn new(root: PathBuf, interval: Duration, debounce: Duration) -> Self {
+        let files = scan(&root);
+        Self {
+            root,
+            interval,
+            debounce,
+            files,
+        }
+    }
+}
+
+impl Watcher for PollingWatcher {
+    async fn changes(&mut self) -> Result<Option<Vec<PathBuf>>> {
+        loop {
+            tokio::time::sleep(self.interval).await;
+            let mut current = scan(&self.root);
+            if current == self.files {
+                continue;
+            }
+
+            // Wait until the files settle, so a burst of changes triggers a single run.
+            loop {
+                tokio::time::sleep(self.debounce).await;
+                let next = scan(&self.root);
+                if next == current {
+                    break;
+                }
+                current = next;
+            }
+
+            let mut changed: Vec<_> = current
+                .iter()
+                .filter(|(path, stat)| self.files.get(*path) != Some(stat))
+                .map(|(path, _)| path.clone())
+                .collect();
+            self.files = current;
+
+            // Removed files have nothing to check.
+            if changed.is_empty() {
+                continue;
+            }
+            changed.sort();
+            return Ok(Some(changed));
+        }
+    }
+
+    fn reset(&mut self) {
+        self.files = scan(&self.root);
+    }
+}
+
+/// The modification time and size of the files under `root` that are not ignored by git.
+fn scan(root: &Path) -> FxHashMap<PathBuf, (SystemTime, u64)> {
+    let mut builder = WalkBuilder::new(root);
+    WalkBuilder::hidden(&mut builder, false);
+    builder
+        .filter_entry(|entry| entry.file_name() != ".git")
+        .build()
+        .flatten()
+        .filter(|entry| {
+            entry
+                .file_type()
+                .is_some_and(|file_type| file_type.is_file())
+        })
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            let modified = metadata.modified().ok()?;
+            Some((entry.into_path(), (modified, metadata.len())))
+        })
+        .collect()
+}
+
+/// The projects owning the given files: the deepest project containing each file.
+fn owning_projects(projects: &[PathBuf], files: &[PathBuf]) -> Vec<PathBuf> {
+    let mut owners: Vec<PathBuf> = files
+        .iter()
+        .filter_map(|file| {
+            projects
+                .iter()
+                .filter(|project| file.starts_with(project))
+                .max_by_key(|project| project.components().count())
+                .cloned()
+        })
+        .collect();
+    owners.sort();
+    owners.dedup();
+    owners
+}
+
+/// Re-run hooks on each batch of changed files, scoped to the projects owning them.
+async fn watch_loop(
+    watcher: &mut impl Watcher,
+    projects: &[PathBuf],
+    mut status: ExitStatus,
+    mut rerun: impl AsyncFnMut(Vec<PathBuf>, Vec<PathBuf>) -> Result<ExitStatus>,
+) -> Result<ExitStatus> {
+    while let Some(changes) = watcher.changes().await? {
+        let owners = owning_projects(projects, &changes);
+        if owners.is_empty() {
+            continue;
+        }
+        status = rerun(changes, owners).await?;
+        watcher.reset();
+    }
+    Ok(status)
+}
+
+/// Run hooks, then watch the workspace and run them again on the changed files, until interrupted.
+pub(crate) async fn watch(
+    git_root: &Path,
+    options: RunOptions,
+    printer: Printer,
+) -> Result<ExitStatus> {
+    let workspace_root = Workspace::find_root(git_root, options.config.as_deref(), &CWD)?;
+    let workspace = Workspace::discover(
+        git_root,
+        workspace_root.clone(),
+        options.config.clone(),
+        None,
+        options.refresh,
+    )?;
+    let projects: Vec<_> = workspace
+        .projects()
+        .iter()
+        .map(|project| project.path().to_path_buf())
+        .collect();
+
+    let run_once = async |includes: Vec<String>,
+                          all_files: bool,
+                          files: Vec<String>,
+                          directories: Vec<String>,
+                          initial: bool| {
+        super::run(
+            git_root,
+            &CWD,
+            RunOptions {
+                includes,
+                all_files,
+                files,
+                directories,
+                from_ref: options.from_ref.clone().filter(|_| initial),
+                to_ref: options.to_ref.clone().filter(|_| initial),
+                last_commit: options.last_commit && initial,
+                refresh_configs: options.refresh_configs && initial,
+                refresh_hooks: options.refresh_hooks.clone().filter(|_| initial),
+                refresh: options.refresh && initial,
+                ..options.clone()
+            },
+            printer,
+        )
+        .await
+    };
+
+    let status = run_once(
+        options.includes.clone(),
+        options.all_files,
+        options.files.clone(),
+        options.directories.clone(),
+        true,
+    )
+    .await?;
+
+    let mut watcher = PollingWatcher::new(
+        workspace_root,
+        Duration::from_millis(500),
+        Duration::from_millis(200),
+    );
+    writeln!(
+        printer.stderr(),
+        "{}",
+        "Watching for changes, press Ctrl-C to stop".dimmed()
+    )?;
+
+    watch_loop(
+        &mut watcher,
+        &projects,
+        status,
+        async |changes: Vec<PathBuf>, owners: Vec<PathBuf>| {
+            writeln!(
+                printer.stderr(),
+                "\n{}",
+                format!(
+                    "{} changed, running hooks again",
+                    match changes.as_slice() {
+                        [file] => format!("`{}`", file.user_display()),
+                        files => format!("{} files", files.len()),
+                    }
+                )
+                .dimmed()
+            )?;
+
+            // Hooks selected explicitly are kept, otherwise only the owning projects are run.
+            let includes = if options.includes.is_empty() {
+                owners
+                    .iter()
+                    .map(|project| format!("{}/", project.display()))
+                    .collect()
+            } else {
+                options.includes.clone()
+            };
+            let files = changes
+                .iter()
+                .map(|file| file.to_string_lossy().into_owned())
+                .collect();
+            let status = run_once(includes, false, files, vec![], false).await?;
+
+            writeln!(
+                printer.stderr(),
+                "{}",
+                "Watching for changes, press Ctrl-C to stop".dimmed()
+            )?;
+            Ok(status)
+        },
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use super::*;
+
+    /// A watcher that reports the given batches of changes, then stops.
+    struct ScriptedWatcher {
+        batches: VecDeque<Vec<PathBuf>>,
+        resets: usize,
+    }
+
+    impl Watcher for ScriptedWatcher {
+        async fn changes(&mut self) -> Result<Option<Vec<PathBuf>>> {
+            Ok(self.batches.pop_front())
+        }
+
+        fn reset(&mut self) {
+            self.resets += 1;
+        }
+    }
+
+    #[tokio::test]
+    async fn rerun_scoped_to_owning_projects() -> Result<()> {
+        let projects = [
+            PathBuf::from("/repo"),
+            PathBuf::from("/repo/app"),
+            PathBuf::from("/repo/lib"),
+        ];
+        let mut watcher = ScriptedWatcher {
+            batches: VecDeque::from([
+                vec![PathBuf::from("/repo/app/main.py")],
+                vec![
+                    PathBuf::from("/repo/README.md"),
+                    PathBuf::from("/repo/lib/src/lib.rs"),
+                    PathBuf::from("/repo/lib/Cargo.toml"),
+                ],
+            ]),
+            resets: 0,
+        };
+
+        let mut runs = Vec::new();
+        let status = watch_loop(
+            &mut watcher,
+            &projects,
+            ExitStatus::Success,
+            async |changes, owners| {
+                runs.push((changes.len(), owners));
+                Ok(ExitStatus::Failure)
+            },
+        )
+        .await?;
+
+        assert!(matches!(status, ExitStatus::Failure));
+        assert_eq!(watcher.resets, 2);
+        assert_eq!(
+            runs,
+            [
+                (1, vec![PathBuf::from("/repo/app")]),
+                (3, vec![PathBuf::from("/repo"), PathBuf::from("/repo/lib")]),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn polling_watcher_reports_changed_files() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let root = dir.path().to_path_buf();
+        fs_err::write(root.join("unchanged.txt"), "unchanged")?;
+        fs_err::write(root.join("changed.txt"), "before")?;
+
+        let mut watcher = PollingWatcher::new(
+            root.clone(),
+            Duration::from_millis(10),
+            Duration::from_millis(10),
+        );
+        fs_err::write(root.join("changed.txt"), "after, with a different size")?;
+        fs_err::write(root.join("new.txt"), "new")?;
+
+        let changes = watcher.changes().await?;
+        assert_eq!(
+            changes,
+            Some(vec![root.join("changed.txt"), root.join("new.txt")])
+        );
+
+        Ok(())
+    }
+}
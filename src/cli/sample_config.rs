@@ -19,14 +19,33 @@ repos:
       - id: end-of-file-fixer
       - id: check-yaml
       - id: check-added-large-files
+  - repo: local
+    hooks:
+      - id: forbid-merge-leftovers
+        name: forbid merge leftovers
+        entry: Remove `.orig` and `.rej` files left over from merges and patches
+        language: fail
+        files: '\\.(orig|rej)$'
+      - id: no-nocommit
+        name: no NOCOMMIT markers
+        entry: NOCOMMIT
+        language: pygrep
+        types: [text]
 ";
 
 #[allow(clippy::print_stdout)]
-pub(crate) fn sample_config(file: Option<PathBuf>, printer: Printer) -> Result<ExitStatus> {
+pub(crate) fn sample_config(
+    file: Option<PathBuf>,
+    force: bool,
+    printer: Printer,
+) -> Result<ExitStatus> {
     if let Some(file) = file {
         fs_err::create_dir_all(file.parent().unwrap_or(Path::new(".")))?;
-        if file.exists() {
-            anyhow::bail!("File `{}` already exists", file.simplified_display().cyan());
+        if file.exists() && !force {
+            anyhow::bail!(
+                "File `{}` already exists, use `--force` to overwrite it",
+                file.simplified_display().cyan()
+            );
         }
         fs_err::write(&file, SAMPLE_CONFIG)?;
 
@@ -42,3 +61,25 @@ pub(crate) fn sample_config(file: Option<PathBuf>, printer: Printer) -> Result<E
     print!("{SAMPLE_CONFIG}");
     Ok(ExitStatus::Success)
 }
+
+#[cfg(test)]
+mod tests {
+    use constants::CONFIG_FILE;
+
+    use super::SAMPLE_CONFIG;
+    use crate::config::{Repo, read_config};
+
+    #[test]
+    fn sample_config_is_valid() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join(CONFIG_FILE);
+        fs_err::write(&path, SAMPLE_CONFIG)?;
+
+        let config = read_config(&path)?;
+        assert_eq!(config.repos.len(), 2);
+        assert!(matches!(config.repos[0], Repo::Remote(_)));
+        assert!(matches!(config.repos[1], Repo::Local(_)));
+
+        Ok(())
+    }
+}
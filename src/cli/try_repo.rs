@@ -0,0 +1,94 @@
+use std::fmt::Write;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use owo_colors::OwoColorize;
+
+use constants::MANIFEST_FILE;
+
+use crate::cli::reporter::HookInitReporter;
+use crate::config::{self, RemoteRepo, STDIN_CONFIG};
+use crate::fs::CWD;
+use crate::git;
+use crate::printer::Printer;
+use crate::store::STORE;
+use crate::workspace::HookInitReporter as _;
+
+/// Clone `repo` at `rev` and use a configuration that runs all of its hooks.
+///
+/// The configuration only exists in memory, it is served as the configuration read from
+/// stdin, so the returned path can be passed to `prek run` as `--config`.
+pub(crate) async fn try_repo(
+    repo: String,
+    rev: Option<String>,
+    printer: Printer,
+) -> Result<PathBuf> {
+    // Local repositories are cloned from their absolute path.
+    let path = CWD.join(&repo);
+    let local = path.is_dir();
+    let repo = if local {
+        path.to_string_lossy().to_string()
+    } else {
+        repo
+    };
+
+    let rev = match rev {
+        Some(rev) => rev,
+        // Try the uncommitted changes of a local repository as well.
+        None if local => match git::stash_create(&path).await? {
+            Some(commit) => commit,
+            None => git::get_head_commit(&path).await?,
+        },
+        None => git::get_remote_head(&repo)
+            .await
+            .with_context(|| format!("Failed to resolve `HEAD` of `{}`", repo.cyan()))?,
+    };
+
+    let remote = RemoteRepo {
+        repo,
+        rev,
+        hooks: vec![],
+    };
+
+    let store = STORE.as_ref()?;
+    let reporter = HookInitReporter::from(printer);
+    let path = {
+        let _lock = store.lock_async().await?;
+        store.clone_repo(&remote, Some(&reporter)).await?
+    };
+    reporter.on_complete();
+
+    let manifest = config::read_manifest(&path.join(MANIFEST_FILE))?;
+
+    // A mapping keeps the order of its keys, so the configuration reads like a handwritten one.
+    let mut repo = serde_yaml::Mapping::new();
+    repo.insert("repo".into(), remote.repo.into());
+    repo.insert("rev".into(), remote.rev.into());
+    repo.insert(
+        "hooks".into(),
+        manifest
+            .hooks
+            .into_iter()
+            .map(|hook| {
+                let mut mapping = serde_yaml::Mapping::new();
+                mapping.insert("id".into(), hook.id.into());
+                serde_yaml::Value::Mapping(mapping)
+            })
+            .collect(),
+    );
+    let mut config = serde_yaml::Mapping::new();
+    config.insert(
+        "repos".into(),
+        serde_yaml::Value::Sequence(vec![repo.into()]),
+    );
+    let content = serde_yaml::to_string(&config)?;
+
+    writeln!(printer.stderr(), "{}", "Using config:".bold())?;
+    for line in content.lines() {
+        writeln!(printer.stderr(), "  {}", line.dimmed())?;
+    }
+
+    config::set_stdin_config(content);
+
+    Ok(PathBuf::from(STDIN_CONFIG))
+}
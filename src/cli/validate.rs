@@ -1,29 +1,172 @@
 use std::error::Error;
 use std::iter;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use anstream::eprintln;
+use itertools::Itertools;
 use owo_colors::OwoColorize;
+use rustc_hash::FxHashMap;
+
+use constants::{ALT_CONFIG_FILE, CONFIG_FILE, MANIFEST_FILE};
 
 use crate::cli::ExitStatus;
-use crate::config::{read_config, read_manifest};
+use crate::config::{
+    self, Config, PathRepo, RemoteRepo, Repo, lint_config, read_config, read_manifest,
+};
+use crate::fs::{CWD, Simplified};
+use crate::git::GIT_ROOT;
+use crate::store::STORE;
+use crate::workspace::Workspace;
 
-pub(crate) fn validate_configs(configs: Vec<PathBuf>) -> ExitStatus {
-    let mut status = ExitStatus::Success;
+/// Validate the given config files, or the config files of all the projects in the workspace.
+///
+/// All the problems of all the files are reported. Nothing is cloned, so this works offline:
+/// the hooks of remote repos are only checked against their manifest if the repo was cloned before.
+pub(crate) fn validate_configs(configs: Vec<PathBuf>, config: Option<PathBuf>) -> ExitStatus {
+    let configs = if !configs.is_empty() {
+        configs
+    } else if config.is_some() {
+        config.into_iter().collect()
+    } else if let Some(root) = GIT_ROOT
+        .as_ref()
+        .ok()
+        .and_then(|git_root| Workspace::find_root(git_root, None, &CWD).ok())
+    {
+        Workspace::find_configs(&root)
+    } else {
+        [CONFIG_FILE, ALT_CONFIG_FILE]
+            .into_iter()
+            .map(PathBuf::from)
+            .find(|path| path.is_file())
+            .into_iter()
+            .collect()
+    };
 
+    let mut status = ExitStatus::Success;
     for config in configs {
-        if let Err(err) = read_config(&config) {
-            eprintln!("{}: {}", "error".red().bold(), err);
-            for source in iter::successors(err.source(), |&err| err.source()) {
-                eprintln!("  {}: {}", "caused by".red().bold(), source);
+        match read_config(&config) {
+            Ok(parsed) => {
+                for problem in check_config(&config, &parsed) {
+                    eprintln!("{}: {}", "error".red().bold(), problem);
+                    status = ExitStatus::Failure;
+                }
+            }
+            Err(err) => {
+                eprintln!("{}: {}", "error".red().bold(), err);
+                for source in iter::successors(err.source(), |&err| err.source()) {
+                    eprintln!("  {}: {}", "caused by".red().bold(), source);
+                }
+
+                // Parsing stops at the first problem, list all of them when there are more.
+                if matches!(err, config::Error::Yaml(..)) {
+                    let content = fs_err::read_to_string(&config).unwrap_or_default();
+                    let problems = lint_config(&content);
+                    if problems.len() > 1 {
+                        eprintln!("  {}:", "all problems".red().bold());
+                        for problem in problems {
+                            eprintln!("    - {problem}");
+                        }
+                    }
+                }
+                status = ExitStatus::Failure;
             }
-            status = ExitStatus::Failure;
         }
     }
 
     status
 }
 
+/// Find the problems of a parsed config that would only show up when running its hooks.
+fn check_config(config_path: &Path, config: &Config) -> Vec<String> {
+    let path = config_path.user_display();
+    if config.repos.is_empty() {
+        return vec![format!("No repos in `{path}`, no hooks would run")];
+    }
+
+    let mut problems = Vec::new();
+
+    // Hooks with the same id can only be told apart by their alias.
+    let mut unaliased = Vec::new();
+    for repo in &config.repos {
+        let hooks: Vec<_> = match repo {
+            Repo::Remote(RemoteRepo { hooks, .. }) | Repo::Path(PathRepo { hooks, .. }) => hooks
+                .iter()
+                .map(|hook| (&hook.id, &hook.options.alias))
+                .collect(),
+            Repo::Local(repo) => repo
+                .hooks
+                .iter()
+                .map(|hook| (&hook.id, &hook.options.alias))
+                .collect(),
+            Repo::Meta(repo) => repo
+                .hooks
+                .iter()
+                .map(|hook| (&hook.0.id, &hook.0.options.alias))
+                .collect(),
+        };
+        unaliased.extend(
+            hooks
+                .into_iter()
+                .filter(|(_, alias)| alias.as_ref().is_none_or(String::is_empty))
+                .map(|(id, _)| id.as_str()),
+        );
+    }
+    let mut counts = FxHashMap::<&str, usize>::default();
+    for id in &unaliased {
+        *counts.entry(id).or_default() += 1;
+    }
+    for id in unaliased.into_iter().unique() {
+        let count = counts[id];
+        if count > 1 {
+            problems.push(format!(
+                "Hook id `{id}` is used by {count} hooks without an `alias` in `{path}`, give each of them an `alias`"
+            ));
+        }
+    }
+
+    // Check the hooks exist, like when they are initialized before running.
+    for repo in &config.repos {
+        let (name, dir, hooks) = match repo {
+            Repo::Remote(repo) => {
+                let Some(dir) = STORE
+                    .as_ref()
+                    .ok()
+                    .and_then(|store| store.cloned_repo(repo))
+                else {
+                    continue;
+                };
+                (repo.repo.clone(), dir, &repo.hooks)
+            }
+            Repo::Path(repo) => {
+                let base = config_path.parent().unwrap_or(Path::new(""));
+                (
+                    repo.path.display().to_string(),
+                    base.join(&repo.path),
+                    &repo.hooks,
+                )
+            }
+            Repo::Local(_) | Repo::Meta(_) => continue,
+        };
+        match read_manifest(&dir.join(MANIFEST_FILE)) {
+            Ok(manifest) => {
+                for hook in hooks {
+                    if !manifest.hooks.iter().any(|manifest| manifest.id == hook.id) {
+                        problems.push(format!(
+                            "Hook `{}` not present in repo `{name}`, used in `{path}`",
+                            hook.id
+                        ));
+                    }
+                }
+            }
+            Err(err) => problems.push(format!(
+                "Failed to read the manifest of repo `{name}`, used in `{path}`: {err}"
+            )),
+        }
+    }
+
+    problems
+}
+
 pub(crate) fn validate_manifest(configs: Vec<PathBuf>) -> ExitStatus {
     let mut status = ExitStatus::Success;
 
@@ -1,19 +1,23 @@
 use std::fmt::Display;
 use std::ops::{Deref, RangeInclusive};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
-use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, LazyLock, Mutex, OnceLock};
+use std::time::SystemTime;
 
 use anyhow::Result;
+use clap::ValueEnum;
+use constants::env_vars::EnvVars;
 use constants::{ALT_CONFIG_FILE, CONFIG_FILE};
 use fancy_regex::{self as regex, Regex};
-use rustc_hash::FxHashMap;
+use rustc_hash::{FxHashMap, FxHashSet};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use crate::fs::Simplified;
 use crate::identify;
 use crate::version;
-use crate::warn_user;
+use crate::{warn_user, warn_user_once};
 
 #[derive(Clone)]
 pub struct SerdeRegex(Regex);
@@ -39,7 +43,7 @@ impl<'de> Deserialize<'de> for SerdeRegex {
         let s = String::deserialize(deserializer)?;
         Regex::new(&s)
             .map(SerdeRegex)
-            .map_err(serde::de::Error::custom)
+            .map_err(|err| serde::de::Error::custom(format!("invalid regex `{s}`: {err}")))
     }
 }
 
@@ -165,9 +169,7 @@ impl Display for HookType {
     }
 }
 
-#[derive(
-    Debug, Clone, Copy, PartialEq, Eq, Default, Hash, Deserialize, Serialize, clap::ValueEnum,
-)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Hash, Serialize, clap::ValueEnum)]
 #[serde(rename_all = "kebab-case")]
 pub enum Stage {
     Manual,
@@ -177,16 +179,68 @@ pub enum Stage {
     PostMerge,
     PostRewrite,
     #[default]
-    #[serde(alias = "commit")]
     PreCommit,
-    #[serde(alias = "merge-commit")]
     PreMergeCommit,
-    #[serde(alias = "push")]
     PrePush,
     PreRebase,
     PrepareCommitMsg,
 }
 
+impl FromStr for Stage {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Legacy stage names from pre-commit.
+        let alias = match s {
+            "commit" => Some(Self::PreCommit),
+            "merge-commit" => Some(Self::PreMergeCommit),
+            "push" => Some(Self::PrePush),
+            _ => None,
+        };
+        if let Some(stage) = alias {
+            return Ok(stage);
+        }
+
+        if let Some(stage) = Self::value_variants()
+            .iter()
+            .find(|stage| stage.as_str() == s)
+        {
+            return Ok(*stage);
+        }
+
+        let valid = Self::value_variants()
+            .iter()
+            .map(|stage| format!("`{stage}`"))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        // Suggest the closest stage name for typos.
+        let suggestion = Self::value_variants()
+            .iter()
+            .map(|stage| (stage, levenshtein::levenshtein(s, stage.as_str())))
+            .filter(|(_, distance)| *distance <= 3)
+            .min_by_key(|(_, distance)| *distance);
+
+        if let Some((stage, _)) = suggestion {
+            Err(format!(
+                "unknown stage `{s}`, did you mean `{stage}`? Valid stages are: {valid}"
+            ))
+        } else {
+            Err(format!("unknown stage `{s}`, valid stages are: {valid}"))
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Stage {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
 impl From<HookType> for Stage {
     fn from(value: HookType) -> Self {
         match value {
@@ -267,6 +321,51 @@ where
     Ok(Some(s))
 }
 
+/// Deserializes `minimum_pre_commit_version`, accepting short versions like `3.2` as pre-commit does.
+fn deserialize_minimum_pre_commit_version<'de, D>(
+    deserializer: D,
+) -> Result<Option<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = match serde_yaml::Value::deserialize(deserializer)? {
+        serde_yaml::Value::String(s) => s,
+        // Unquoted versions like `3.2` are parsed as numbers, which loses trailing zeros.
+        serde_yaml::Value::Number(n) => {
+            if n.is_f64() {
+                warn_user_once!(
+                    "`minimum_pre_commit_version: {n}` is not quoted and is read as a number, which drops trailing zeros (`2.10` becomes `2.1`). Quote the version to keep it as written."
+                );
+            }
+            n.to_string()
+        }
+        serde_yaml::Value::Null => return Ok(None),
+        _ => return Err(serde::de::Error::custom("expected a version string")),
+    };
+    if s.is_empty() {
+        return Ok(None);
+    }
+
+    let padded = match s.matches('.').count() {
+        0 => format!("{s}.0.0"),
+        1 => format!("{s}.0"),
+        _ => s.clone(),
+    };
+    let version = padded
+        .parse::<semver::Version>()
+        .map_err(serde::de::Error::custom)?;
+    let compatible = version::PRE_COMMIT_VERSION
+        .parse::<semver::Version>()
+        .expect("Invalid pre-commit version");
+    if version > compatible {
+        return Err(serde::de::Error::custom(format!(
+            "Required minimum pre-commit version `{s}` is greater than pre-commit `{compatible}` that prek is compatible with. Please consider updating prek.",
+        )));
+    }
+
+    Ok(Some(s))
+}
+
 // TODO: warn deprecated stage
 // TODO: warn sensible regex
 #[derive(Debug, Clone, Deserialize)]
@@ -288,13 +387,61 @@ pub struct Config {
     /// Set to true to have prek stop running hooks after the first failure.
     /// Default is false.
     pub fail_fast: Option<bool>,
+    /// A default for the `timeout` of hooks, in seconds.
+    /// Default is no timeout.
+    pub timeout: Option<u64>,
+    /// Set to false to treat configs in subdirectories as part of this project,
+    /// instead of as nested projects in workspace mode.
+    /// Default is true.
+    pub nested: Option<bool>,
+    /// Set to true to inherit the defaults of the parent project in workspace mode.
+    /// Default is false.
+    pub inherit: Option<bool>,
     /// The minimum version of prek required to run this configuration.
     #[serde(deserialize_with = "deserialize_minimum_version", default)]
     pub minimum_prek_version: Option<String>,
+    /// The minimum version of pre-commit required by this configuration,
+    /// checked against the pre-commit version prek is compatible with.
+    #[serde(deserialize_with = "deserialize_minimum_pre_commit_version", default)]
+    pub minimum_pre_commit_version: Option<String>,
     /// Configuration for pre-commit.ci service.
     pub ci: Option<FxHashMap<String, serde_yaml::Value>>,
 }
 
+impl Config {
+    /// Iterate over the remote repos in the configuration.
+    pub fn remote_repos(&self) -> impl Iterator<Item = &RemoteRepo> {
+        self.repos.iter().filter_map(|repo| match repo {
+            Repo::Remote(repo) => Some(repo),
+            _ => None,
+        })
+    }
+
+    /// Fill in the defaults that are not set in this configuration from the configuration
+    /// of the parent project: `default_language_version`, `default_stages`, `files`,
+    /// `exclude` and `timeout`. Values set in this configuration always win.
+    pub(crate) fn inherit_from(&mut self, parent: &Config) {
+        if let Some(parent_versions) = &parent.default_language_version {
+            let versions = self.default_language_version.get_or_insert_default();
+            for (language, version) in parent_versions {
+                versions.entry(*language).or_insert_with(|| version.clone());
+            }
+        }
+        if self.default_stages.is_none() {
+            self.default_stages.clone_from(&parent.default_stages);
+        }
+        if self.files.is_none() {
+            self.files.clone_from(&parent.files);
+        }
+        if self.exclude.is_none() {
+            self.exclude.clone_from(&parent.exclude);
+        }
+        if self.timeout.is_none() {
+            self.timeout = parent.timeout;
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub enum RepoLocation {
@@ -397,6 +544,18 @@ pub struct HookOptions {
     /// The minimum version of prek required to run this hook.
     #[serde(deserialize_with = "deserialize_minimum_version", default)]
     pub minimum_prek_version: Option<String>,
+    /// A glob of files produced by the hook, relative to the project directory.
+    /// Matching files are collected into `--output-dir` after the hook runs.
+    pub output_artifacts: Option<String>,
+    /// Connect the hook directly to the terminal, so it can prompt the user.
+    /// The output of the hook is not captured.
+    /// Default is false.
+    pub interactive: Option<bool>,
+    /// Kill the hook and mark it as failed if it runs longer than this many seconds.
+    /// Default is no timeout.
+    pub timeout: Option<u64>,
+    /// Environment variables to set for the hook, on top of the inherited environment.
+    pub env: Option<FxHashMap<String, String>>,
 }
 
 impl HookOptions {
@@ -430,6 +589,10 @@ impl HookOptions {
             stages,
             verbose,
             minimum_prek_version,
+            output_artifacts,
+            interactive,
+            timeout,
+            env,
         );
     }
 }
@@ -605,8 +768,26 @@ impl Display for RemoteRepo {
     }
 }
 
+/// A repo on the local filesystem, used directly without cloning.
+#[derive(Debug, Clone)]
+pub struct PathRepo {
+    /// The path to the repo, relative to the project root or absolute.
+    pub path: PathBuf,
+    pub hooks: Vec<RemoteHook>,
+}
+
+impl Display for PathRepo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.path.display())
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct LocalRepo {
+    /// Dependencies shared by all hooks of the repo, unless a hook sets its own.
+    pub additional_dependencies: Option<Vec<String>>,
+    /// The `language_version` of all hooks of the repo, unless a hook sets its own.
+    pub language_version: Option<String>,
     pub hooks: Vec<LocalHook>,
 }
 
@@ -630,6 +811,7 @@ impl Display for MetaRepo {
 #[derive(Debug, Clone)]
 pub enum Repo {
     Remote(RemoteRepo),
+    Path(PathRepo),
     Local(LocalRepo),
     Meta(MetaRepo),
 }
@@ -649,6 +831,21 @@ impl<'de> Deserialize<'de> for Repo {
         let RepoWire { repo, rest } = RepoWire::deserialize(deserializer)?;
 
         match repo {
+            // A filesystem path without `rev` is used in place, without cloning.
+            RepoLocation::Remote(path) if is_path_like(&path) && rest.get("rev").is_none() => {
+                #[derive(Deserialize)]
+                #[serde(deny_unknown_fields)]
+                struct _PathRepo {
+                    hooks: Vec<RemoteHook>,
+                }
+                let _PathRepo { hooks } = _PathRepo::deserialize(rest)
+                    .map_err(|e| serde::de::Error::custom(format!("Invalid path repo: {e}")))?;
+
+                Ok(Repo::Path(PathRepo {
+                    path: PathBuf::from(path),
+                    hooks,
+                }))
+            }
             RepoLocation::Remote(url) => {
                 #[derive(Deserialize)]
                 #[serde(deny_unknown_fields)]
@@ -669,11 +866,21 @@ impl<'de> Deserialize<'de> for Repo {
                 #[derive(Deserialize)]
                 #[serde(deny_unknown_fields)]
                 struct _LocalRepo {
+                    additional_dependencies: Option<Vec<String>>,
+                    language_version: Option<String>,
                     hooks: Vec<LocalHook>,
                 }
-                let _LocalRepo { hooks } = _LocalRepo::deserialize(rest)
+                let _LocalRepo {
+                    additional_dependencies,
+                    language_version,
+                    hooks,
+                } = _LocalRepo::deserialize(rest)
                     .map_err(|e| serde::de::Error::custom(format!("Invalid local repo: {e}")))?;
-                Ok(Repo::Local(LocalRepo { hooks }))
+                Ok(Repo::Local(LocalRepo {
+                    additional_dependencies,
+                    language_version,
+                    hooks,
+                }))
             }
             RepoLocation::Meta => {
                 #[derive(Deserialize)]
@@ -689,6 +896,15 @@ impl<'de> Deserialize<'de> for Repo {
     }
 }
 
+/// Whether the `repo` value refers to a path on the local filesystem rather than a URL.
+fn is_path_like(repo: &str) -> bool {
+    repo == "."
+        || repo == ".."
+        || repo.starts_with("./")
+        || repo.starts_with("../")
+        || Path::new(repo).is_absolute()
+}
+
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub struct ManifestHook {
@@ -721,31 +937,93 @@ pub enum Error {
 
     #[error("Failed to parse `{0}`")]
     Yaml(String, #[source] serde_yaml::Error),
+
+    #[error("Unexpected keys in `{0}`: {1}")]
+    UnexpectedKeys(String, String),
+
+    #[error("Duplicate hook alias `{1}` in `{0}`, each alias can only be used once")]
+    DuplicateAlias(String, String),
+}
+
+/// The `--config` path that reads the configuration from stdin.
+pub(crate) const STDIN_CONFIG: &str = "-";
+
+/// Whether the configuration at `path` is read from stdin.
+pub(crate) fn is_stdin_config(path: &Path) -> bool {
+    path == Path::new(STDIN_CONFIG)
+}
+
+/// The content of the configuration read from stdin.
+/// Whether unknown keys in config files are rejected, set by `--strict`.
+static STRICT: AtomicBool = AtomicBool::new(false);
+
+/// Reject unknown keys in config files instead of ignoring them.
+pub(crate) fn set_strict() {
+    STRICT.store(true, Ordering::Relaxed);
 }
 
-/// Read the configuration file from the given path.
+static STDIN_CONTENT: OnceLock<Result<String, String>> = OnceLock::new();
+
+/// Use `content` as the configuration read from stdin, instead of reading stdin.
+///
+/// This allows running hooks from a configuration that only exists in memory.
+pub(crate) fn set_stdin_config(content: String) {
+    STDIN_CONTENT
+        .set(Ok(content))
+        .expect("stdin config should only be set once");
+}
+
+/// Read the configuration from stdin.
+///
+/// Stdin can only be consumed once, so its content is kept for later reads.
+fn read_stdin_config() -> Result<String, Error> {
+    STDIN_CONTENT
+        .get_or_init(|| std::io::read_to_string(std::io::stdin()).map_err(|e| e.to_string()))
+        .clone()
+        .map_err(|e| Error::Io(std::io::Error::other(e)))
+}
+
+/// Read the configuration file from the given path, or from stdin if the path is `-`.
 pub fn read_config(path: &Path) -> Result<Config, Error> {
-    let content = match fs_err::read_to_string(path) {
-        Ok(content) => content,
-        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
-            return Err(Error::NotFound(path.user_display().to_string()));
+    let content = if is_stdin_config(path) {
+        read_stdin_config()?
+    } else {
+        match fs_err::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(Error::NotFound(path.user_display().to_string()));
+            }
+            Err(e) => return Err(e.into()),
         }
-        Err(e) => return Err(e.into()),
     };
 
-    let expected_unused = ["minimum_pre_commit_version"];
+    // Hooks copied from a manifest may keep its hook-level `minimum_pre_commit_version`.
+    let expected_hook_keys = ["minimum_pre_commit_version"];
 
     let deserializer = serde_yaml::Deserializer::from_str(&content);
     let mut unused = Vec::new();
     let config: Config = serde_ignored::deserialize(deserializer, |path| {
-        let key = path.to_string();
-        if !expected_unused.contains(&key.as_str()) {
-            unused.push(key);
-        }
+        unused.push(path.to_string());
     })
     .map_err(|e| Error::Yaml(path.user_display().to_string(), e))?;
+    // Hooks are deserialized through `flatten`, which hides their unknown keys from `serde_ignored`.
+    unused.extend(unexpected_hook_keys(&content).into_iter().filter(|key| {
+        !expected_hook_keys
+            .iter()
+            .any(|k| key.ends_with(&format!(".{k}")))
+    }));
 
     if !unused.is_empty() {
+        if STRICT.load(Ordering::Relaxed) || EnvVars::is_set(EnvVars::PREK_STRICT) {
+            return Err(Error::UnexpectedKeys(
+                path.user_display().to_string(),
+                unused
+                    .into_iter()
+                    .map(|key| format!("`{key}`"))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            ));
+        }
         warn_user!(
             "Ignored unexpected keys in `{}`: {}",
             path.display().cyan(),
@@ -757,6 +1035,30 @@ pub fn read_config(path: &Path) -> Result<Config, Error> {
         );
     }
 
+    // An alias selects a single hook, so it must be unique within the config.
+    let mut aliases = FxHashSet::default();
+    for repo in &config.repos {
+        let hook_aliases: Vec<_> = match repo {
+            Repo::Remote(RemoteRepo { hooks, .. }) | Repo::Path(PathRepo { hooks, .. }) => {
+                hooks.iter().map(|hook| &hook.options.alias).collect()
+            }
+            Repo::Local(repo) => repo.hooks.iter().map(|hook| &hook.options.alias).collect(),
+            Repo::Meta(repo) => repo
+                .hooks
+                .iter()
+                .map(|hook| &hook.0.options.alias)
+                .collect(),
+        };
+        for alias in hook_aliases.into_iter().flatten() {
+            if !alias.is_empty() && !aliases.insert(alias.as_str()) {
+                return Err(Error::DuplicateAlias(
+                    path.user_display().to_string(),
+                    alias.clone(),
+                ));
+            }
+        }
+    }
+
     // Check for mutable revs and warn the user.
     let repos_has_mutable_rev = config
         .repos
@@ -797,6 +1099,162 @@ pub fn read_config(path: &Path) -> Result<Config, Error> {
     Ok(config)
 }
 
+/// Read the configuration file from the given path, parsing it at most once per process
+/// unless its modification time changed.
+pub(crate) fn read_config_cached(path: &Path) -> Result<Arc<Config>, Error> {
+    /// Parsed configs with the modification time of their file when they were read.
+    type ConfigCache = FxHashMap<PathBuf, (SystemTime, Arc<Config>)>;
+    static CACHE: LazyLock<Mutex<ConfigCache>> = LazyLock::new(Mutex::default);
+
+    // Stdin has no modification time, and its content is kept by `read_config`.
+    if is_stdin_config(path) {
+        return read_config(path).map(Arc::new);
+    }
+
+    let Ok(modified) = fs_err::metadata(path).and_then(|metadata| metadata.modified()) else {
+        // Let `read_config` report the error.
+        return read_config(path).map(Arc::new);
+    };
+    if let Some((cached_modified, config)) = CACHE.lock().unwrap().get(path)
+        && *cached_modified == modified
+    {
+        return Ok(Arc::clone(config));
+    }
+
+    let config = Arc::new(read_config(path)?);
+    CACHE
+        .lock()
+        .unwrap()
+        .insert(path.to_path_buf(), (modified, Arc::clone(&config)));
+    Ok(config)
+}
+
+/// Find the keys of hook entries that are not known hook options, as `repos.<i>.hooks.<j>.<key>`.
+fn unexpected_hook_keys(content: &str) -> Vec<String> {
+    static HOOK_KEYS: OnceLock<Vec<&'static str>> = OnceLock::new();
+    let hook_keys = HOOK_KEYS.get_or_init(|| {
+        ["id", "name", "entry", "language"]
+            .into_iter()
+            .chain(struct_fields::<HookOptions>().iter().copied())
+            .collect()
+    });
+
+    let mut unexpected = Vec::new();
+    let Ok(value) = serde_yaml::from_str::<serde_yaml::Value>(content) else {
+        return unexpected;
+    };
+    let Some(repos) = value.get("repos").and_then(serde_yaml::Value::as_sequence) else {
+        return unexpected;
+    };
+    for (i, repo) in repos.iter().enumerate() {
+        let Some(hooks) = repo.get("hooks").and_then(serde_yaml::Value::as_sequence) else {
+            continue;
+        };
+        for (j, hook) in hooks.iter().enumerate() {
+            let Some(hook) = hook.as_mapping() else {
+                continue;
+            };
+            for key in hook.keys().filter_map(serde_yaml::Value::as_str) {
+                if !hook_keys.contains(&key) {
+                    unexpected.push(format!("repos.{i}.hooks.{j}.{key}"));
+                }
+            }
+        }
+    }
+
+    unexpected
+}
+
+/// Find the problems in a config file that can be checked without parsing it fully, as
+/// `<key>: <problem>`.
+///
+/// Parsing stops at the first problem, this reports all the invalid regexes, the missing
+/// fields of local hooks and the missing `rev` of remote repos at once.
+pub fn lint_config(content: &str) -> Vec<String> {
+    fn check_regexes(value: &serde_yaml::Value, prefix: &str, problems: &mut Vec<String>) {
+        for key in ["files", "exclude"] {
+            let Some(pattern) = value.get(key).and_then(serde_yaml::Value::as_str) else {
+                continue;
+            };
+            if let Err(err) = Regex::new(pattern) {
+                problems.push(format!("`{prefix}{key}`: invalid regex: {err}"));
+            }
+        }
+    }
+
+    let mut problems = Vec::new();
+    let Ok(value) = serde_yaml::from_str::<serde_yaml::Value>(content) else {
+        return problems;
+    };
+    check_regexes(&value, "", &mut problems);
+
+    let Some(repos) = value.get("repos").and_then(serde_yaml::Value::as_sequence) else {
+        return problems;
+    };
+    for (i, repo) in repos.iter().enumerate() {
+        let location = repo.get("repo").and_then(serde_yaml::Value::as_str);
+        let is_local = location == Some("local");
+        if location.is_some_and(|location| {
+            !matches!(location, "local" | "meta") && !is_path_like(location)
+        }) && repo.get("rev").is_none()
+        {
+            problems.push(format!("`repos.{i}`: missing field `rev`"));
+        }
+        let Some(hooks) = repo.get("hooks").and_then(serde_yaml::Value::as_sequence) else {
+            continue;
+        };
+        for (j, hook) in hooks.iter().enumerate() {
+            let prefix = format!("repos.{i}.hooks.{j}.");
+            if is_local {
+                for field in ["id", "name", "entry", "language"] {
+                    if hook.get(field).is_none() {
+                        problems.push(format!("`repos.{i}.hooks.{j}`: missing field `{field}`"));
+                    }
+                }
+            }
+            check_regexes(hook, &prefix, &mut problems);
+        }
+    }
+
+    problems
+}
+
+/// Get the field names of a struct from its derived `Deserialize` implementation.
+fn struct_fields<'de, T: Deserialize<'de>>() -> &'static [&'static str] {
+    struct FieldsDeserializer<'a>(&'a mut &'static [&'static str]);
+
+    impl<'de> Deserializer<'de> for FieldsDeserializer<'_> {
+        type Error = serde::de::value::Error;
+
+        fn deserialize_any<V: serde::de::Visitor<'de>>(
+            self,
+            _visitor: V,
+        ) -> Result<V::Value, Self::Error> {
+            Err(serde::de::Error::custom("expected a struct"))
+        }
+
+        fn deserialize_struct<V: serde::de::Visitor<'de>>(
+            self,
+            _name: &'static str,
+            fields: &'static [&'static str],
+            _visitor: V,
+        ) -> Result<V::Value, Self::Error> {
+            *self.0 = fields;
+            Err(serde::de::Error::custom("fields collected"))
+        }
+
+        serde::forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+            bytes byte_buf option unit unit_struct newtype_struct seq tuple
+            tuple_struct map enum identifier ignored_any
+        }
+    }
+
+    let mut fields: &'static [&'static str] = &[];
+    let _ = T::deserialize(FieldsDeserializer(&mut fields));
+    fields
+}
+
 /// Read the manifest file from the given path.
 pub fn read_manifest(path: &Path) -> Result<Manifest, Error> {
     let content = fs_err::read_to_string(path)?;
@@ -853,6 +1311,8 @@ mod tests {
                 repos: [
                     Local(
                         LocalRepo {
+                            additional_dependencies: None,
+                            language_version: None,
                             hooks: [
                                 ManifestHook {
                                     id: "cargo-fmt",
@@ -878,6 +1338,10 @@ mod tests {
                                         stages: None,
                                         verbose: None,
                                         minimum_prek_version: None,
+                                        output_artifacts: None,
+                                        interactive: None,
+                                        timeout: None,
+                                        env: None,
                                     },
                                 },
                             ],
@@ -890,7 +1354,11 @@ mod tests {
                 files: None,
                 exclude: None,
                 fail_fast: None,
+                timeout: None,
+                nested: None,
+                inherit: None,
                 minimum_prek_version: None,
+                minimum_pre_commit_version: None,
                 ci: None,
             },
         )
@@ -909,7 +1377,7 @@ mod tests {
         let result = serde_yaml::from_str::<Config>(yaml);
         insta::assert_debug_snapshot!(result, @r###"
         Err(
-            Error("repos: Invalid local repo: unknown field `rev`, expected `hooks`", line: 2, column: 3),
+            Error("repos: Invalid local repo: unknown field `rev`, expected one of `additional_dependencies`, `language_version`, `hooks`", line: 2, column: 3),
         )
         "###);
 
@@ -955,6 +1423,10 @@ mod tests {
                                         stages: None,
                                         verbose: None,
                                         minimum_prek_version: None,
+                                        output_artifacts: None,
+                                        interactive: None,
+                                        timeout: None,
+                                        env: None,
                                     },
                                 },
                             ],
@@ -967,7 +1439,11 @@ mod tests {
                 files: None,
                 exclude: None,
                 fail_fast: None,
+                timeout: None,
+                nested: None,
+                inherit: None,
                 minimum_prek_version: None,
+                minimum_pre_commit_version: None,
                 ci: None,
             },
         )
@@ -1039,6 +1515,8 @@ mod tests {
                 repos: [
                     Local(
                         LocalRepo {
+                            additional_dependencies: None,
+                            language_version: None,
                             hooks: [
                                 ManifestHook {
                                     id: "cargo-fmt",
@@ -1064,6 +1542,10 @@ mod tests {
                                         stages: None,
                                         verbose: None,
                                         minimum_prek_version: None,
+                                        output_artifacts: None,
+                                        interactive: None,
+                                        timeout: None,
+                                        env: None,
                                     },
                                 },
                             ],
@@ -1076,7 +1558,11 @@ mod tests {
                 files: None,
                 exclude: None,
                 fail_fast: None,
+                timeout: None,
+                nested: None,
+                inherit: None,
                 minimum_prek_version: None,
+                minimum_pre_commit_version: None,
                 ci: None,
             },
         )
@@ -1191,6 +1677,10 @@ mod tests {
                                             stages: None,
                                             verbose: None,
                                             minimum_prek_version: None,
+                                            output_artifacts: None,
+                                            interactive: None,
+                                            timeout: None,
+                                            env: None,
                                         },
                                     },
                                 ),
@@ -1223,6 +1713,10 @@ mod tests {
                                             stages: None,
                                             verbose: None,
                                             minimum_prek_version: None,
+                                            output_artifacts: None,
+                                            interactive: None,
+                                            timeout: None,
+                                            env: None,
                                         },
                                     },
                                 ),
@@ -1253,6 +1747,10 @@ mod tests {
                                                 true,
                                             ),
                                             minimum_prek_version: None,
+                                            output_artifacts: None,
+                                            interactive: None,
+                                            timeout: None,
+                                            env: None,
                                         },
                                     },
                                 ),
@@ -1266,7 +1764,11 @@ mod tests {
                 files: None,
                 exclude: None,
                 fail_fast: None,
+                timeout: None,
+                nested: None,
+                inherit: None,
                 minimum_prek_version: None,
+                minimum_pre_commit_version: None,
                 ci: None,
             },
         )
@@ -1302,6 +1804,8 @@ mod tests {
                 repos: [
                     Local(
                         LocalRepo {
+                            additional_dependencies: None,
+                            language_version: None,
                             hooks: [
                                 ManifestHook {
                                     id: "hook-1",
@@ -1329,6 +1833,10 @@ mod tests {
                                         stages: None,
                                         verbose: None,
                                         minimum_prek_version: None,
+                                        output_artifacts: None,
+                                        interactive: None,
+                                        timeout: None,
+                                        env: None,
                                     },
                                 },
                                 ManifestHook {
@@ -1357,6 +1865,10 @@ mod tests {
                                         stages: None,
                                         verbose: None,
                                         minimum_prek_version: None,
+                                        output_artifacts: None,
+                                        interactive: None,
+                                        timeout: None,
+                                        env: None,
                                     },
                                 },
                                 ManifestHook {
@@ -1385,6 +1897,10 @@ mod tests {
                                         stages: None,
                                         verbose: None,
                                         minimum_prek_version: None,
+                                        output_artifacts: None,
+                                        interactive: None,
+                                        timeout: None,
+                                        env: None,
                                     },
                                 },
                             ],
@@ -1397,7 +1913,11 @@ mod tests {
                 files: None,
                 exclude: None,
                 fail_fast: None,
+                timeout: None,
+                nested: None,
+                inherit: None,
                 minimum_prek_version: None,
+                minimum_pre_commit_version: None,
                 ci: None,
             },
         )
@@ -1579,4 +2099,55 @@ mod tests {
                 .contains("Type tag \"not-a-real-tag\" is not recognized")
         );
     }
+
+    #[test]
+    fn test_legacy_stages() {
+        let yaml = indoc::indoc! {r"
+            repos:
+              - repo: local
+                hooks:
+                  - id: test-hook
+                    name: Test Hook
+                    entry: echo test
+                    language: system
+                    stages: [commit, push, manual]
+            default_stages: [merge-commit, pre-commit]
+        "};
+        let config = serde_yaml::from_str::<Config>(yaml).unwrap();
+        assert_eq!(
+            config.default_stages,
+            Some(vec![Stage::PreMergeCommit, Stage::PreCommit])
+        );
+        let Repo::Local(repo) = &config.repos[0] else {
+            panic!("expected a local repo");
+        };
+        assert_eq!(
+            repo.hooks[0].options.stages,
+            Some(vec![Stage::PreCommit, Stage::PrePush, Stage::Manual])
+        );
+    }
+
+    #[test]
+    fn read_config_cached_reparses_modified() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join(CONFIG_FILE);
+        fs_err::write(&path, "repos: []\n")?;
+
+        let first = read_config_cached(&path)?;
+        let second = read_config_cached(&path)?;
+        assert!(Arc::ptr_eq(&first, &second));
+
+        fs_err::write(&path, "repos: []\nfail_fast: true\n")?;
+        // Make sure the modification time changes, even on coarse-grained file systems.
+        std::fs::File::options()
+            .write(true)
+            .open(&path)?
+            .set_modified(SystemTime::now() + std::time::Duration::from_secs(10))?;
+
+        let third = read_config_cached(&path)?;
+        assert!(!Arc::ptr_eq(&second, &third));
+        assert_eq!(third.fail_fast, Some(true));
+
+        Ok(())
+    }
 }
@@ -1,7 +1,6 @@
 use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 use std::process::Stdio;
-use std::str::Utf8Error;
 use std::sync::LazyLock;
 
 use anyhow::Result;
@@ -22,9 +21,52 @@ pub(crate) enum Error {
 
     #[error(transparent)]
     Io(#[from] std::io::Error),
+}
 
-    #[error(transparent)]
-    UTF8(#[from] Utf8Error),
+impl Error {
+    /// Whether the error is likely caused by the network, so running the command again may work.
+    ///
+    /// Authoritative errors from the server, like a missing repository or rev, are not transient.
+    pub(crate) fn is_transient(&self) -> bool {
+        // Messages of `git` and `curl` for failures at the transport level.
+        const TRANSIENT: &[&str] = &[
+            "could not resolve host",
+            "connection timed out",
+            "operation timed out",
+            "connection reset",
+            "connection refused",
+            "empty reply from server",
+            "failed to connect",
+            "the remote end hung up unexpectedly",
+            "early eof",
+            "rpc failed",
+            "the requested url returned error: 5",
+            "gnutls_handshake",
+            "ssl_error_syscall",
+        ];
+
+        match self {
+            Error::Io(err) => matches!(
+                err.kind(),
+                std::io::ErrorKind::TimedOut
+                    | std::io::ErrorKind::ConnectionReset
+                    | std::io::ErrorKind::ConnectionAborted
+                    | std::io::ErrorKind::Interrupted
+            ),
+            Error::Command(process::Error::Status {
+                error:
+                    StatusError {
+                        output: Some(output),
+                        ..
+                    },
+                ..
+            }) => {
+                let stderr = String::from_utf8_lossy(&output.stderr).to_lowercase();
+                TRANSIENT.iter().any(|pattern| stderr.contains(pattern))
+            }
+            _ => false,
+        }
+    }
 }
 
 pub(crate) static GIT: LazyLock<Result<PathBuf, which::Error>> =
@@ -72,13 +114,27 @@ pub(crate) fn git_cmd(summary: &str) -> Result<Cmd, Error> {
     Ok(cmd)
 }
 
-fn zsplit(s: &[u8]) -> Result<Vec<PathBuf>, Utf8Error> {
+/// Split NUL separated paths, keeping the raw bytes of paths that are not valid UTF-8.
+fn zsplit(s: &[u8]) -> Vec<PathBuf> {
     s.split(|&b| b == b'\0')
         .filter(|slice| !slice.is_empty())
-        .map(|slice| str::from_utf8(slice).map(PathBuf::from))
+        .map(path_from_bytes)
         .collect()
 }
 
+#[cfg(unix)]
+fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+    use std::os::unix::ffi::OsStrExt;
+
+    PathBuf::from(std::ffi::OsStr::from_bytes(bytes))
+}
+
+#[cfg(not(unix))]
+fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+    // Git writes paths as UTF-8 on Windows.
+    PathBuf::from(String::from_utf8_lossy(bytes).into_owned())
+}
+
 pub(crate) async fn intent_to_add_files(root: &Path) -> Result<Vec<PathBuf>, Error> {
     let output = git_cmd("get intent to add files")?
         .arg("diff")
@@ -92,7 +148,7 @@ pub(crate) async fn intent_to_add_files(root: &Path) -> Result<Vec<PathBuf>, Err
         .check(true)
         .output()
         .await?;
-    Ok(zsplit(&output.stdout)?)
+    Ok(zsplit(&output.stdout))
 }
 
 pub(crate) async fn get_changed_files(
@@ -112,7 +168,7 @@ pub(crate) async fn get_changed_files(
         .check(true)
         .output()
         .await?;
-    Ok(zsplit(&output.stdout)?)
+    Ok(zsplit(&output.stdout))
 }
 
 pub(crate) async fn ls_files(cwd: &Path, path: &Path) -> Result<Vec<PathBuf>, Error> {
@@ -126,7 +182,7 @@ pub(crate) async fn ls_files(cwd: &Path, path: &Path) -> Result<Vec<PathBuf>, Er
         .output()
         .await?;
 
-    Ok(zsplit(&output.stdout)?)
+    Ok(zsplit(&output.stdout))
 }
 
 pub(crate) async fn get_git_dir() -> Result<PathBuf, Error> {
@@ -170,7 +226,7 @@ pub(crate) async fn get_staged_files(root: &Path) -> Result<Vec<PathBuf>, Error>
         .check(true)
         .output()
         .await?;
-    Ok(zsplit(&output.stdout)?)
+    Ok(zsplit(&output.stdout))
 }
 
 pub(crate) async fn files_not_staged(files: &[&Path]) -> Result<Vec<PathBuf>> {
@@ -186,7 +242,7 @@ pub(crate) async fn files_not_staged(files: &[&Path]) -> Result<Vec<PathBuf>> {
         .await?;
 
     if output.status.code().is_some_and(|code| code == 1) {
-        return Ok(zsplit(&output.stdout)?);
+        return Ok(zsplit(&output.stdout));
     }
 
     Ok(vec![])
@@ -229,7 +285,7 @@ pub(crate) async fn get_conflicted_files(root: &Path) -> Result<Vec<PathBuf>, Er
         .output()
         .await?;
 
-    Ok(zsplit(&output.stdout)?
+    Ok(zsplit(&output.stdout)
         .into_iter()
         .chain(parse_merge_msg_for_conflicts().await?)
         .collect::<HashSet<PathBuf>>()
@@ -266,6 +322,22 @@ pub(crate) async fn get_diff(path: &Path) -> Result<Vec<u8>, Error> {
     Ok(output.stdout)
 }
 
+/// Record the tracked files of the working tree and the index of the repository at `path`
+/// in a commit, without changing them or any ref, like `git stash create`.
+///
+/// Returns `None` if there are no local changes.
+pub(crate) async fn stash_create(path: &Path) -> Result<Option<String>, Error> {
+    let output = git_cmd("git stash create")?
+        .current_dir(path)
+        .arg("stash")
+        .arg("create")
+        .check(true)
+        .output()
+        .await?;
+    let commit = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Ok((!commit.is_empty()).then_some(commit))
+}
+
 /// Create a tree object from the current index.
 ///
 /// The name of the new tree object is printed to standard output.
@@ -325,19 +397,116 @@ pub(crate) async fn init_repo(url: &str, path: &Path) -> Result<(), Error> {
     Ok(())
 }
 
-async fn shallow_clone(rev: &str, path: &Path) -> Result<(), Error> {
-    git_cmd("git shallow clone")?
-        .current_dir(path)
-        .arg("-c")
-        .arg("protocol.version=2")
-        .arg("fetch")
-        .arg("origin")
-        .arg(rev)
-        .arg("--depth=1")
-        .remove_git_env()
-        .check(true)
-        .output()
-        .await?;
+/// Called with the bytes received so far and the estimated total, if known.
+pub(crate) type FetchProgress<'a> = &'a dyn Fn(u64, Option<u64>);
+
+/// Parse a `Receiving objects` progress line of `git fetch --progress`, like
+/// `Receiving objects:  45% (450/1000), 1.20 MiB | 2.00 MiB/s`.
+///
+/// Git only reports the number of objects to receive, the total bytes are estimated
+/// from the bytes received for the objects so far.
+fn parse_fetch_progress(line: &str) -> Option<(u64, Option<u64>)> {
+    let line = line.trim().strip_prefix("Receiving objects:")?;
+    let (_, rest) = line.split_once('(')?;
+    let (objects, rest) = rest.split_once(')')?;
+    let (received_objects, total_objects) = objects.split_once('/')?;
+    let received_objects: u64 = received_objects.parse().ok()?;
+    let total_objects: u64 = total_objects.parse().ok()?;
+
+    // The size is only shown once enough data has been received.
+    let Some(size) = rest.strip_prefix(", ") else {
+        return Some((0, None));
+    };
+    let size = size.split(['|', ',']).next()?.trim();
+    let (value, unit) = size.split_once(' ')?;
+    let value: f64 = value.parse().ok()?;
+    let multiplier = match unit {
+        "bytes" => 1.0,
+        "KiB" => 1024.0,
+        "MiB" => 1024.0 * 1024.0,
+        "GiB" => 1024.0 * 1024.0 * 1024.0,
+        _ => return None,
+    };
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let received = (value * multiplier) as u64;
+    let total = (received_objects > 0).then(|| received * total_objects / received_objects);
+
+    Some((received, total))
+}
+
+/// Run a `git fetch`, reporting its progress as it goes.
+async fn fetch(cmd: &mut Cmd, progress: Option<FetchProgress<'_>>) -> Result<(), Error> {
+    let Some(progress) = progress else {
+        cmd.check(true).output().await?;
+        return Ok(());
+    };
+
+    cmd.arg("--progress")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    let mut child = cmd.spawn()?;
+    let mut stdout = child.stdout.take().expect("failed to open stdout");
+    let mut stderr = child.stderr.take().expect("failed to open stderr");
+
+    let read_stdout = async {
+        let mut out = Vec::new();
+        stdout.read_to_end(&mut out).await?;
+        Ok::<_, std::io::Error>(out)
+    };
+    // Git rewrites the progress line in place with `\r`.
+    let read_stderr = async {
+        let mut out = Vec::new();
+        let mut line_start = 0;
+        let mut buf = [0u8; 4096];
+        loop {
+            let n = stderr.read(&mut buf).await?;
+            if n == 0 {
+                break;
+            }
+            out.extend_from_slice(&buf[..n]);
+            while let Some(end) = out[line_start..]
+                .iter()
+                .position(|&b| b == b'\r' || b == b'\n')
+            {
+                let line = String::from_utf8_lossy(&out[line_start..line_start + end]);
+                if let Some((received, total)) = parse_fetch_progress(&line) {
+                    progress(received, total);
+                }
+                line_start += end + 1;
+            }
+        }
+        Ok::<_, std::io::Error>(out)
+    };
+
+    let (stdout, stderr) = tokio::try_join!(read_stdout, read_stderr)?;
+    let status = child.wait().await?;
+    cmd.check_output(&std::process::Output {
+        status,
+        stdout,
+        stderr,
+    })?;
+
+    Ok(())
+}
+
+async fn shallow_clone(
+    rev: &str,
+    path: &Path,
+    progress: Option<FetchProgress<'_>>,
+) -> Result<(), Error> {
+    fetch(
+        git_cmd("git shallow clone")?
+            .current_dir(path)
+            .arg("-c")
+            .arg("protocol.version=2")
+            .arg("fetch")
+            .arg("origin")
+            .arg(rev)
+            .arg("--depth=1")
+            .remove_git_env(),
+        progress,
+    )
+    .await?;
 
     git_cmd("git checkout")?
         .current_dir(path)
@@ -365,16 +534,21 @@ async fn shallow_clone(rev: &str, path: &Path) -> Result<(), Error> {
     Ok(())
 }
 
-async fn full_clone(rev: &str, path: &Path) -> Result<(), Error> {
-    git_cmd("git full clone")?
-        .current_dir(path)
-        .arg("fetch")
-        .arg("origin")
-        .arg("--tags")
-        .remove_git_env()
-        .check(true)
-        .output()
-        .await?;
+async fn full_clone(
+    rev: &str,
+    path: &Path,
+    progress: Option<FetchProgress<'_>>,
+) -> Result<(), Error> {
+    fetch(
+        git_cmd("git full clone")?
+            .current_dir(path)
+            .arg("fetch")
+            .arg("origin")
+            .arg("--tags")
+            .remove_git_env(),
+        progress,
+    )
+    .await?;
 
     git_cmd("git checkout")?
         .current_dir(path)
@@ -399,14 +573,23 @@ async fn full_clone(rev: &str, path: &Path) -> Result<(), Error> {
     Ok(())
 }
 
-pub(crate) async fn clone_repo(url: &str, rev: &str, path: &Path) -> Result<(), Error> {
+pub(crate) async fn clone_repo(
+    url: &str,
+    rev: &str,
+    path: &Path,
+    progress: Option<FetchProgress<'_>>,
+) -> Result<(), Error> {
     init_repo(url, path).await?;
 
-    if let Err(err) = shallow_clone(rev, path).await {
-        warn!(?err, "Failed to shallow clone, falling back to full clone");
-        full_clone(rev, path).await
-    } else {
-        Ok(())
+    // Fetch only the pinned rev. Some servers refuse to fetch a commit by its SHA, then the
+    // whole history is fetched instead. Network errors would fail the full clone as well,
+    // they are left to the caller to retry.
+    match shallow_clone(rev, path, progress).await {
+        Err(err) if !err.is_transient() => {
+            warn!(?err, "Failed to shallow clone, falling back to full clone");
+            full_clone(rev, path, progress).await
+        }
+        result => result,
     }
 }
 
@@ -446,7 +629,7 @@ pub(crate) async fn get_lfs_files(paths: &[&Path]) -> Result<FxHashSet<PathBuf>,
 
     let writer = async move {
         for path in paths {
-            stdin.write_all(path.to_string_lossy().as_bytes()).await?;
+            stdin.write_all(path.as_os_str().as_encoded_bytes()).await?;
             stdin.write_all(b"\0").await?;
         }
         stdin.shutdown().await?;
@@ -472,14 +655,13 @@ pub(crate) async fn get_lfs_files(paths: &[&Path]) -> Result<FxHashSet<PathBuf>,
     }
 
     let mut lfs_files = FxHashSet::default();
-    let read_result = String::from_utf8_lossy(&read_result);
-    let mut it = read_result.split_terminator('\0');
+    let mut it = read_result.split(|&b| b == b'\0');
     loop {
         let (Some(file), Some(_attr), Some(value)) = (it.next(), it.next(), it.next()) else {
             break;
         };
-        if value == "lfs" {
-            lfs_files.insert(PathBuf::from(file));
+        if value == b"lfs" {
+            lfs_files.insert(path_from_bytes(file));
         }
     }
 
@@ -552,3 +734,59 @@ pub(crate) async fn get_parent_commit(commit: &str) -> Result<Option<String>, Er
         Ok(None)
     }
 }
+
+/// Get the commit that `HEAD` points to in the repository at `url`.
+pub(crate) async fn get_remote_head(url: &str) -> Result<String, Error> {
+    let output = git_cmd("git ls-remote")?
+        .arg("ls-remote")
+        .arg("--exit-code")
+        .arg(url)
+        .arg("HEAD")
+        .check(true)
+        .output()
+        .await?;
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_string())
+}
+
+/// Get the commit checked out in the repository at `path`.
+pub(crate) async fn get_head_commit(path: &Path) -> Result<String, Error> {
+    let output = git_cmd("get head commit")?
+        .current_dir(path)
+        .arg("rev-parse")
+        .arg("HEAD")
+        .check(true)
+        .output()
+        .await?;
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_fetch_progress;
+
+    #[test]
+    fn fetch_progress() {
+        assert_eq!(
+            parse_fetch_progress("Receiving objects:   0% (1/1000)"),
+            Some((0, None))
+        );
+        assert_eq!(
+            parse_fetch_progress("Receiving objects:  25% (250/1000), 1.00 MiB | 2.00 MiB/s"),
+            Some((1024 * 1024, Some(4 * 1024 * 1024)))
+        );
+        assert_eq!(
+            parse_fetch_progress(
+                "Receiving objects: 100% (1000/1000), 512.00 KiB | 1.00 MiB/s, done."
+            ),
+            Some((512 * 1024, Some(512 * 1024)))
+        );
+        assert_eq!(
+            parse_fetch_progress("Resolving deltas: 100% (10/10), done."),
+            None
+        );
+    }
+}
@@ -3,11 +3,15 @@ use std::fmt::{Display, Formatter};
 use std::hash::{Hash, Hasher};
 use std::ops::Deref;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, OnceLock};
+use std::time::Duration;
 
 use anyhow::{Context, Result};
 use clap::ValueEnum;
 use constants::MANIFEST_FILE;
+use constants::env_vars::EnvVars;
+use ignore::overrides::{Override, OverrideBuilder};
 use rand::Rng;
 use rustc_hash::{FxBuildHasher, FxHashMap, FxHashSet};
 use serde::{Deserialize, Serialize};
@@ -41,6 +45,28 @@ pub(crate) enum Error {
         #[source]
         error: config::Error,
     },
+
+    #[error(
+        "repo `{repo}` at rev `{rev}` has no valid .pre-commit-hooks.yaml (at {})",
+        path.display()
+    )]
+    RemoteManifest {
+        repo: String,
+        rev: String,
+        /// Path to the cloned repo, so users can inspect it.
+        path: PathBuf,
+        #[source]
+        error: config::Error,
+    },
+}
+
+/// Whether hooks with an unsupported language are an error, set by
+/// `--treat-unknown-language-as-error`.
+static UNKNOWN_LANGUAGE_AS_ERROR: AtomicBool = AtomicBool::new(false);
+
+/// Fail on hooks with an unsupported language, instead of skipping them.
+pub(crate) fn set_treat_unknown_language_as_error() {
+    UNKNOWN_LANGUAGE_AS_ERROR.store(true, Ordering::Relaxed);
 }
 
 #[derive(Debug, Clone)]
@@ -52,6 +78,11 @@ pub(crate) enum Repo {
         rev: String,
         hooks: Vec<ManifestHook>,
     },
+    Path {
+        /// Absolute path to the repo on the local filesystem.
+        path: PathBuf,
+        hooks: Vec<ManifestHook>,
+    },
     Local {
         hooks: Vec<ManifestHook>,
     },
@@ -63,10 +94,13 @@ pub(crate) enum Repo {
 impl Repo {
     /// Load the remote repo manifest from the path.
     pub(crate) fn remote(url: String, rev: String, path: PathBuf) -> Result<Self, Error> {
-        let manifest = read_manifest(&path.join(MANIFEST_FILE)).map_err(|e| Error::Manifest {
-            repo: url.to_string(),
-            error: e,
-        })?;
+        let manifest =
+            read_manifest(&path.join(MANIFEST_FILE)).map_err(|e| Error::RemoteManifest {
+                repo: url.clone(),
+                rev: rev.clone(),
+                path: path.clone(),
+                error: e,
+            })?;
         let hooks = manifest.hooks;
 
         Ok(Self::Remote {
@@ -77,6 +111,17 @@ impl Repo {
         })
     }
 
+    /// Load the manifest of a repo on the local filesystem, without cloning it.
+    pub(crate) fn from_path(path: PathBuf) -> Result<Self, Error> {
+        let manifest = read_manifest(&path.join(MANIFEST_FILE)).map_err(|e| Error::Manifest {
+            repo: path.display().to_string(),
+            error: e,
+        })?;
+        let hooks = manifest.hooks;
+
+        Ok(Self::Path { path, hooks })
+    }
+
     /// Construct a local repo from a list of hooks.
     pub(crate) fn local(hooks: Vec<LocalHook>) -> Self {
         Self::Local { hooks }
@@ -89,10 +134,10 @@ impl Repo {
         }
     }
 
-    /// Get the path to the cloned repo if it is a remote repo.
+    /// Get the path to the cloned repo if it is a remote repo, or the repo itself if it is a path repo.
     pub(crate) fn path(&self) -> Option<&Path> {
         match self {
-            Repo::Remote { path, .. } => Some(path),
+            Repo::Remote { path, .. } | Repo::Path { path, .. } => Some(path),
             _ => None,
         }
     }
@@ -101,6 +146,7 @@ impl Repo {
     pub(crate) fn get_hook(&self, id: &str) -> Option<&ManifestHook> {
         let hooks = match self {
             Repo::Remote { hooks, .. } => hooks,
+            Repo::Path { hooks, .. } => hooks,
             Repo::Local { hooks } => hooks,
             Repo::Meta { hooks } => hooks,
         };
@@ -112,6 +158,7 @@ impl Display for Repo {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Repo::Remote { url, rev, .. } => write!(f, "{url}@{rev}"),
+            Repo::Path { path, .. } => write!(f, "{}", path.display()),
             Repo::Local { .. } => write!(f, "local"),
             Repo::Meta { .. } => write!(f, "meta"),
         }
@@ -124,6 +171,10 @@ pub(crate) struct HookBuilder {
     config: ManifestHook,
     // The index of the hook in the project configuration.
     idx: usize,
+    // Whether `entry` and `args` come from the user's config, environment variables are only
+    // expanded there, never in the manifest of a hook repository.
+    expand_entry: bool,
+    expand_args: bool,
 }
 
 impl HookBuilder {
@@ -133,11 +184,14 @@ impl HookBuilder {
         config: ManifestHook,
         idx: usize,
     ) -> Self {
+        let user_defined = matches!(*repo, Repo::Local { .. } | Repo::Meta { .. });
         Self {
             project,
             repo,
             config,
             idx,
+            expand_entry: user_defined,
+            expand_args: user_defined,
         }
     }
 
@@ -148,16 +202,34 @@ impl HookBuilder {
         }
         if let Some(entry) = &config.entry {
             self.config.entry.clone_from(entry);
+            self.expand_entry = true;
         }
         if let Some(language) = &config.language {
             self.config.language.clone_from(language);
         }
+        if config.options.args.is_some() {
+            self.expand_args = true;
+        }
 
         self.config.options.update(&config.options);
 
         self
     }
 
+    /// Fill in the options shared by all hooks of a local repo.
+    pub(crate) fn inherit(&mut self, repo: &config::LocalRepo) -> &mut Self {
+        let options = &mut self.config.options;
+        if options.additional_dependencies.is_none() {
+            options
+                .additional_dependencies
+                .clone_from(&repo.additional_dependencies);
+        }
+        if options.language_version.is_none() {
+            options.language_version.clone_from(&repo.language_version);
+        }
+        self
+    }
+
     /// Combine the hook configuration with the project level configuration.
     pub(crate) fn combine(&mut self, config: &Config) {
         let options = &mut self.config.options;
@@ -172,6 +244,10 @@ impl HookBuilder {
         if options.stages.is_none() {
             options.stages.clone_from(&config.default_stages);
         }
+
+        if options.timeout.is_none() {
+            options.timeout = config.timeout;
+        }
     }
 
     /// Fill in the default values for the hook configuration.
@@ -188,7 +264,9 @@ impl HookBuilder {
         options.pass_filenames.get_or_insert(true);
         options.require_serial.get_or_insert(false);
         options.verbose.get_or_insert(false);
+        options.interactive.get_or_insert(false);
         options.additional_dependencies.get_or_insert_default();
+        options.env.get_or_insert_default();
     }
 
     /// Check the hook configuration.
@@ -200,6 +278,27 @@ impl HookBuilder {
             ..
         } = &self.config.options;
 
+        if !Language::supported(language)
+            && (UNKNOWN_LANGUAGE_AS_ERROR.load(Ordering::Relaxed)
+                || EnvVars::is_set(EnvVars::PREK_TREAT_UNKNOWN_LANGUAGE_AS_ERROR))
+        {
+            let supported = Language::value_variants()
+                .iter()
+                .filter(|lang| Language::supported(**lang))
+                .map(|lang| format!("`{}`", lang.as_str()))
+                .collect::<Vec<_>>();
+            return Err(Error::Hook {
+                hook: self.config.id.clone(),
+                error: anyhow::anyhow!(
+                    "Language `{}` of hook `{}` from repo `{}` is not supported yet. Supported languages are: {}",
+                    language,
+                    self.config.id,
+                    self.repo,
+                    supported.join(", "),
+                ),
+            });
+        }
+
         let additional_dependencies = additional_dependencies
             .as_ref()
             .map_or(&[][..], |deps| deps.as_slice());
@@ -257,7 +356,35 @@ impl HookBuilder {
                 error: anyhow::anyhow!(e),
             })?;
 
-        let entry = Entry::new(self.config.id.clone(), self.config.entry);
+        let output_artifacts = options
+            .output_artifacts
+            .as_deref()
+            .map(|glob| {
+                let mut builder = OverrideBuilder::new(self.project.path());
+                builder.add(glob)?;
+                builder.build()
+            })
+            .transpose()
+            .map_err(|e| Error::Hook {
+                hook: self.config.id.clone(),
+                error: anyhow::anyhow!("Invalid `output_artifacts` glob: {e}"),
+            })?;
+
+        let mut entry = Entry::new(self.config.id.clone(), self.config.entry);
+        if self.expand_entry {
+            entry = entry.expand_env_vars()?;
+        }
+        let mut args = options.args.expect("args not set");
+        if self.expand_args {
+            args = args
+                .iter()
+                .map(|arg| expand_env_vars(arg))
+                .collect::<Result<Vec<_>>>()
+                .map_err(|e| Error::Hook {
+                    hook: self.config.id.clone(),
+                    error: e,
+                })?;
+        }
 
         let additional_dependencies = options
             .additional_dependencies
@@ -280,6 +407,7 @@ impl HookBuilder {
         let mut hook = Hook {
             entry,
             stages,
+            language_version,
             language_request,
             additional_dependencies,
             dependencies: OnceLock::new(),
@@ -295,7 +423,7 @@ impl HookBuilder {
             types: options.types.expect("types not set"),
             types_or: options.types_or.expect("types_or not set"),
             exclude_types: options.exclude_types.expect("exclude_types not set"),
-            args: options.args.expect("args not set"),
+            args,
             always_run: options.always_run.expect("always_run not set"),
             fail_fast: options.fail_fast.expect("fail_fast not set"),
             pass_filenames: options.pass_filenames.expect("pass_filenames not set"),
@@ -304,6 +432,10 @@ impl HookBuilder {
             require_serial: options.require_serial.expect("require_serial not set"),
             verbose: options.verbose.expect("verbose not set"),
             minimum_prek_version: options.minimum_prek_version,
+            output_artifacts,
+            interactive: options.interactive.expect("interactive not set"),
+            timeout: options.timeout.map(Duration::from_secs),
+            env: options.env.expect("env not set"),
         };
 
         if let Err(err) = extract_metadata_from_entry(&mut hook).await {
@@ -380,6 +512,65 @@ impl Entry {
     pub(crate) fn raw(&self) -> &str {
         &self.entry
     }
+
+    /// Expand the environment variables of the entry in place, see [`expand_env_vars`].
+    ///
+    /// Values are inserted as is before the entry is split, quote the variable to keep
+    /// a value with spaces as a single word.
+    fn expand_env_vars(self) -> Result<Self, Error> {
+        let entry = expand_env_vars(&self.entry).map_err(|e| Error::Hook {
+            hook: self.hook.clone(),
+            error: e,
+        })?;
+
+        Ok(Self {
+            hook: self.hook,
+            entry,
+        })
+    }
+}
+
+/// Expand `${VAR}` and `${VAR:-default}` from the environment, `$$` is a literal `$`.
+///
+/// The default is used when the variable is unset or empty, like in shells.
+/// Other uses of `$` are kept as is.
+fn expand_env_vars(value: &str) -> Result<String> {
+    let mut expanded = String::with_capacity(value.len());
+    let mut rest = value;
+
+    while let Some(pos) = rest.find('$') {
+        expanded.push_str(&rest[..pos]);
+        rest = &rest[pos..];
+
+        if let Some(after) = rest.strip_prefix("$$") {
+            expanded.push('$');
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix("${") {
+            let end = after
+                .find('}')
+                .with_context(|| format!("Unclosed `${{` in `{value}`"))?;
+            let (name, default) = match after[..end].split_once(":-") {
+                Some((name, default)) => (name, Some(default)),
+                None => (&after[..end], None),
+            };
+
+            match (EnvVars::var(name).ok(), default) {
+                (Some(var), Some(default)) if var.is_empty() => expanded.push_str(default),
+                (Some(var), _) => expanded.push_str(&var),
+                (None, Some(default)) => expanded.push_str(default),
+                (None, None) => anyhow::bail!(
+                    "Environment variable `{name}` is not set, use `${{{name}:-<default>}}` to provide a default"
+                ),
+            }
+            rest = &after[end + 1..];
+        } else {
+            expanded.push('$');
+            rest = &rest[1..];
+        }
+    }
+    expanded.push_str(rest);
+
+    Ok(expanded)
 }
 
 #[allow(clippy::struct_excessive_bools)]
@@ -408,25 +599,43 @@ pub(crate) struct Hook {
     pub fail_fast: bool,
     pub pass_filenames: bool,
     pub description: Option<String>,
+    /// The `language_version` as written in the config, before parsing.
+    pub language_version: String,
     pub language_request: LanguageRequest,
     pub log_file: Option<String>,
     pub require_serial: bool,
     pub stages: Stages,
     pub verbose: bool,
     pub minimum_prek_version: Option<String>,
+    pub output_artifacts: Option<Override>,
+    pub interactive: bool,
+    pub timeout: Option<Duration>,
+    /// Environment variables set for the hook, on top of the inherited environment.
+    pub env: FxHashMap<String, String>,
 }
 
 impl Display for Hook {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         if f.alternate() {
-            write!(f, "{}:{}", self.repo, self.id)
+            write!(f, "{}:{}", self.repo, self.label())
         } else {
-            write!(f, "{}", self.id)
+            write!(f, "{}", self.label())
         }
     }
 }
 
 impl Hook {
+    /// The alias of the hook if it has one, otherwise its id.
+    ///
+    /// Used to tell apart hooks sharing the same id in messages and reports.
+    pub(crate) fn label(&self) -> &str {
+        if self.alias.is_empty() {
+            &self.id
+        } else {
+            &self.alias
+        }
+    }
+
     pub(crate) fn project(&self) -> &Project {
         &self.project
     }
@@ -467,11 +676,11 @@ impl Hook {
     }
 
     pub(crate) fn dependencies(&self) -> &FxHashSet<String> {
-        if !self.is_remote() {
+        if self.repo_path().is_none() {
             return &self.additional_dependencies;
         }
         self.dependencies.get_or_init(|| {
-            // For remote hooks, itself is an implicit dependency of the hook.
+            // For remote and path hooks, the repo itself is an implicit dependency of the hook.
             let mut deps = FxHashSet::with_capacity_and_hasher(
                 self.additional_dependencies.len() + 1,
                 FxBuildHasher,
@@ -613,3 +822,155 @@ impl InstallInfo {
             && hook.language_request.satisfied_by(self)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use constants::CONFIG_FILE;
+
+    use super::*;
+
+    async fn build_local_hooks(config: &str) -> Result<Vec<Hook>> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join(CONFIG_FILE);
+        fs_err::write(&path, config)?;
+
+        let project = Arc::new(Project::from_config_file(
+            Cow::Borrowed(&path),
+            None,
+            dir.path(),
+        )?);
+        let config::Repo::Local(repo) = &project.config().repos[0] else {
+            anyhow::bail!("expected a local repo");
+        };
+        let local = Arc::new(Repo::local(repo.hooks.clone()));
+
+        let mut hooks = Vec::new();
+        for (idx, hook) in repo.hooks.iter().enumerate() {
+            let mut builder = HookBuilder::new(project.clone(), local.clone(), hook.clone(), idx);
+            builder.inherit(repo);
+            builder.combine(project.config());
+            hooks.push(builder.build().await?);
+        }
+        Ok(hooks)
+    }
+
+    #[tokio::test]
+    async fn default_language_version() -> Result<()> {
+        let hooks = build_local_hooks(indoc::indoc! {r"
+            default_language_version:
+              python: python3.11
+            repos:
+              - repo: local
+                hooks:
+                  - id: inherited
+                    name: inherited
+                    entry: python -c 'print()'
+                    language: python
+                  - id: explicit
+                    name: explicit
+                    entry: python -c 'print()'
+                    language: python
+                    language_version: python3.12
+                  - id: no-default
+                    name: no-default
+                    entry: node -e ''
+                    language: node
+        "})
+        .await?;
+
+        let versions: Vec<_> = hooks
+            .iter()
+            .map(|hook| (hook.id.as_str(), hook.language_version.as_str()))
+            .collect();
+        assert_eq!(
+            versions,
+            [
+                ("inherited", "python3.11"),
+                ("explicit", "python3.12"),
+                // Languages without a default keep the unset version.
+                ("no-default", ""),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn local_repo_defaults() -> Result<()> {
+        let hooks = build_local_hooks(indoc::indoc! {r"
+            repos:
+              - repo: local
+                additional_dependencies: [ruff, black]
+                language_version: python3.12
+                hooks:
+                  - id: inherited
+                    name: inherited
+                    entry: ruff
+                    language: python
+                  - id: overridden
+                    name: overridden
+                    entry: mypy
+                    language: python
+                    additional_dependencies: [mypy]
+                    language_version: python3.11
+        "})
+        .await?;
+
+        let mut dependencies: Vec<_> = hooks[0].dependencies().iter().collect();
+        dependencies.sort();
+        assert_eq!(dependencies, ["black", "ruff"]);
+        assert_eq!(hooks[0].language_version, "python3.12");
+
+        let dependencies: Vec<_> = hooks[1].dependencies().iter().collect();
+        assert_eq!(dependencies, ["mypy"]);
+        assert_eq!(hooks[1].language_version, "python3.11");
+
+        Ok(())
+    }
+
+    /// Environments are matched on the language, version and dependencies of a hook, so
+    /// identical hooks of different projects share an environment.
+    #[tokio::test]
+    async fn environment_shared_across_projects() -> Result<()> {
+        let first = build_local_hooks(indoc::indoc! {r"
+            repos:
+              - repo: local
+                hooks:
+                  - id: ruff
+                    name: ruff
+                    entry: ruff check
+                    language: python
+                    additional_dependencies: [ruff]
+        "})
+        .await?;
+        let second = build_local_hooks(indoc::indoc! {r"
+            repos:
+              - repo: local
+                hooks:
+                  - id: ruff
+                    name: ruff
+                    entry: ruff check
+                    language: python
+                    additional_dependencies: [ruff]
+                    args: [--fix]
+                  - id: ruff-pinned
+                    name: ruff
+                    entry: ruff check
+                    language: python
+                    additional_dependencies: [ruff==0.12.0]
+        "})
+        .await?;
+
+        let info = InstallInfo::new(
+            first[0].language,
+            first[0].dependencies().clone(),
+            Path::new("hooks"),
+        );
+        assert!(info.matches(&second[0]));
+        assert!(!info.matches(&second[1]));
+
+        Ok(())
+    }
+}
@@ -778,10 +778,12 @@ pub fn tags_from_path(path: &Path) -> Result<Vec<&str>> {
 
 fn tags_from_filename(filename: &Path) -> Vec<&str> {
     let ext = filename.extension().and_then(|ext| ext.to_str());
+    // Non-UTF-8 names can still match by their extension.
     let filename = filename
         .file_name()
-        .and_then(|name| name.to_str())
-        .expect("Invalid filename");
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+    let filename = filename.as_ref();
 
     let mut result = FxHashSet::default();
 
@@ -809,10 +811,8 @@ fn tags_from_filename(filename: &Path) -> Vec<&str> {
 }
 
 fn tags_from_interpreter(interpreter: &str) -> Vec<&'static str> {
-    let Some(pos) = interpreter.rfind('/') else {
-        return Vec::new();
-    };
-    let mut name = &interpreter[pos + 1..];
+    // `/usr/bin/env python3` is parsed as `python3`, without a directory.
+    let mut name = interpreter.rsplit('/').next().unwrap_or(interpreter);
     // python3.12.3 should match python3.12.3, python3.12, python3, python
     loop {
         if let Some(tags) = by_interpreter().get(name) {
@@ -990,4 +990,17 @@ mod tests {
         let tags = super::tags_from_filename(Path::new("Pipfile.lock"));
         assert_eq!(tags, vec!["json", "text"]);
     }
+
+    #[test]
+    fn tags_from_interpreter() {
+        let tags = super::tags_from_interpreter("/usr/bin/python3.12");
+        assert_eq!(tags, vec!["python", "python3"]);
+
+        // Interpreters found through `/usr/bin/env` have no directory.
+        let tags = super::tags_from_interpreter("python3");
+        assert_eq!(tags, vec!["python", "python3"]);
+
+        let tags = super::tags_from_interpreter("unknown");
+        assert!(tags.is_empty());
+    }
 }
@@ -2,18 +2,20 @@ use std::borrow::Cow;
 use std::collections::hash_map::DefaultHasher;
 use std::fs;
 use std::hash::{Hash, Hasher};
-use std::path::Path;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, LazyLock};
 
 use anyhow::{Context, Result};
 use fancy_regex::Regex;
+use rand::Rng;
 use tracing::trace;
 
 use crate::cli::reporter::HookInstallReporter;
 use crate::hook::{Hook, InstallInfo, InstalledHook};
 use crate::languages::LanguageImpl;
 use crate::process::Cmd;
-use crate::run::{USE_COLOR, run_by_batch};
+use crate::run::{USE_COLOR, hook_env, hook_env_passthrough, run_by_batch};
 use crate::store::Store;
 
 #[derive(Debug, Copy, Clone)]
@@ -64,6 +66,13 @@ static CONTAINER_MOUNTS: LazyLock<Result<Vec<Mount>, Error>> = LazyLock::new(||
 });
 
 impl Docker {
+    /// Find the `docker` executable, with an actionable error if it is not installed.
+    fn docker() -> Result<PathBuf> {
+        which::which("docker").context(
+            "The `docker` executable was not found in PATH, please install Docker (https://docs.docker.com/get-docker/) to run `docker` and `docker_image` hooks",
+        )
+    }
+
     fn docker_tag(hook: &InstalledHook) -> String {
         let InstalledHook::Installed { info, .. } = hook else {
             panic!("Docker tag can only be generated for installed hooks");
@@ -80,7 +89,7 @@ impl Docker {
         };
 
         let tag = Self::docker_tag(hook);
-        let mut cmd = Cmd::new("docker", "build docker image");
+        let mut cmd = Cmd::new(Self::docker()?, "build docker image");
         let cmd = cmd
             .arg("build")
             .arg("--tag")
@@ -151,14 +160,39 @@ impl Docker {
         Ok(path.into())
     }
 
-    pub(crate) fn docker_run_cmd(work_dir: &Path) -> Result<Cmd> {
-        let mut command = Cmd::new("docker", "run container");
-        command.arg("run").arg("--rm");
+    pub(crate) fn docker_run_cmd(hook: &Hook, container: &Container) -> Result<Cmd> {
+        let mut command = Cmd::new(Self::docker()?, "run container");
+        command
+            .arg("run")
+            .arg("--rm")
+            // Forward signals to the entrypoint and reap its children.
+            .arg("--init")
+            .arg("--name")
+            .arg(&container.name)
+            .arg("--label")
+            .arg(format!("prek.hook.id={}", hook.id));
+        // The hook is terminated when its run is cancelled, like on timeout.
+        command.interactive(hook.interactive).kill_on_drop(true);
 
-        if *USE_COLOR {
+        if hook.interactive {
+            command.arg("--interactive");
+        }
+        // `--interactive` with `--tty` requires the input to be a terminal.
+        if *USE_COLOR && (!hook.interactive || std::io::stdin().is_terminal()) {
             command.arg("--tty");
         }
 
+        // Containers don't inherit the environment, the variables are set explicitly.
+        // Without a value, `--env` takes the value of the variable in the environment of prek.
+        if let Some(passthrough) = hook_env_passthrough() {
+            for var in passthrough {
+                command.arg("--env").arg(var);
+            }
+        }
+        for (key, value) in hook_env(hook) {
+            command.arg("--env").arg(format!("{key}={value}"));
+        }
+
         // Run as a non-root user
         #[cfg(unix)]
         {
@@ -168,7 +202,7 @@ impl Docker {
             }));
         }
 
-        let work_dir = Self::get_docker_path(work_dir)?;
+        let work_dir = Self::get_docker_path(hook.work_dir())?;
         command
             .arg("-v")
             // https://docs.docker.com/engine/reference/commandline/run/#mount-volumes-from-container-volumes-from
@@ -182,6 +216,54 @@ impl Docker {
     }
 }
 
+/// A container run by a hook, killed when dropped before it exits.
+///
+/// Killing `docker run` leaves the container running in the background, so it must be
+/// killed too when the hook is cancelled, like on timeout or on `--max-total-duration`.
+pub(crate) struct Container {
+    name: String,
+    running: bool,
+}
+
+impl Container {
+    pub(crate) fn new() -> Self {
+        let suffix: String = rand::rng()
+            .sample_iter(&rand::distr::Alphanumeric)
+            .take(16)
+            .map(char::from)
+            .collect();
+        Self {
+            name: format!("prek-{suffix}"),
+            running: true,
+        }
+    }
+
+    /// Mark the container as exited, it was already removed by `--rm`.
+    pub(crate) fn exited(mut self) {
+        self.running = false;
+    }
+}
+
+impl Drop for Container {
+    fn drop(&mut self) {
+        if !self.running {
+            return;
+        }
+        let Ok(docker) = Docker::docker() else {
+            return;
+        };
+        trace!("Killing container {}", self.name);
+        // Don't wait for it, the hook was already cancelled.
+        let _ = std::process::Command::new(docker)
+            .arg("kill")
+            .arg(&self.name)
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .spawn();
+    }
+}
+
 impl LanguageImpl for Docker {
     async fn install(
         &self,
@@ -218,7 +300,8 @@ impl LanguageImpl for Docker {
     }
 
     async fn check_health(&self) -> Result<()> {
-        todo!()
+        Docker::docker()?;
+        Ok(())
     }
 
     async fn run(
@@ -234,7 +317,8 @@ impl LanguageImpl for Docker {
 
         let run = async move |batch: &[&Path]| {
             // docker run [OPTIONS] IMAGE [COMMAND] [ARG...]
-            let mut cmd = Docker::docker_run_cmd(hook.work_dir())?;
+            let container = Container::new();
+            let mut cmd = Docker::docker_run_cmd(hook, &container)?;
             let mut output = cmd
                 .current_dir(hook.work_dir())
                 .arg("--entrypoint")
@@ -244,8 +328,9 @@ impl LanguageImpl for Docker {
                 .args(&hook.args)
                 .args(batch)
                 .check(false)
-                .output()
+                .pty_output()
                 .await?;
+            container.exited();
 
             output.stdout.extend(output.stderr);
             let code = output.status.code().unwrap_or(1);
@@ -6,7 +6,7 @@ use anyhow::Result;
 use crate::cli::reporter::HookInstallReporter;
 use crate::hook::{Hook, InstalledHook};
 use crate::languages::LanguageImpl;
-use crate::languages::docker::Docker;
+use crate::languages::docker::{Container, Docker};
 use crate::run::run_by_batch;
 use crate::store::Store;
 
@@ -24,7 +24,7 @@ impl LanguageImpl for DockerImage {
     }
 
     async fn check_health(&self) -> Result<()> {
-        todo!()
+        Docker.check_health().await
     }
 
     async fn run(
@@ -35,15 +35,17 @@ impl LanguageImpl for DockerImage {
     ) -> Result<(i32, Vec<u8>)> {
         let entry = hook.entry.resolve(None)?;
         let run = async move |batch: &[&Path]| {
-            let mut cmd = Docker::docker_run_cmd(hook.work_dir())?;
+            let container = Container::new();
+            let mut cmd = Docker::docker_run_cmd(hook, &container)?;
             let mut output = cmd
                 .current_dir(hook.work_dir())
                 .args(&entry[..])
                 .args(&hook.args)
                 .args(batch)
                 .check(false)
-                .output()
+                .pty_output()
                 .await?;
+            container.exited();
 
             output.stdout.extend(output.stderr);
             let code = output.status.code().unwrap_or(1);
@@ -0,0 +1,297 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use constants::env_vars::EnvVars;
+use tracing::{debug, warn};
+
+use crate::cli::reporter::HookInstallReporter;
+use crate::hook::{Hook, InstallInfo, InstalledHook};
+use crate::languages::LanguageImpl;
+use crate::languages::version::LanguageRequest;
+use crate::process::Cmd;
+use crate::run::{hook_cmd, prepend_paths, run_by_batch};
+use crate::store::Store;
+
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Dotnet;
+
+/// An installed .NET SDK.
+struct DotnetSdk {
+    dotnet: PathBuf,
+    version: semver::Version,
+}
+
+impl Dotnet {
+    /// Find the `dotnet` executable, with an actionable error if the .NET SDK is not installed.
+    fn dotnet() -> Result<PathBuf> {
+        which::which("dotnet").context(
+            "The `dotnet` executable was not found in PATH, please install the .NET SDK to run `dotnet` hooks",
+        )
+    }
+
+    /// Find an installed .NET SDK that satisfies the hook's `language_version`.
+    ///
+    /// `prek` does not download the .NET SDK, so `dotnet` must be available in `PATH`.
+    async fn find_sdk(request: &LanguageRequest) -> Result<DotnetSdk> {
+        let dotnet = Self::dotnet()?;
+
+        let output = Cmd::new(&dotnet, "list dotnet sdks")
+            .arg("--list-sdks")
+            .check(true)
+            .output()
+            .await?;
+
+        // Each line looks like `8.0.100 [/usr/share/dotnet/sdk]`.
+        let mut versions = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| line.split_whitespace().next())
+            .filter_map(|version| semver::Version::parse(version).ok())
+            .collect::<Vec<_>>();
+        versions.sort_unstable();
+        debug!(?versions, "Found installed .NET SDKs");
+
+        let version = match request {
+            LanguageRequest::Any => versions.pop(),
+            LanguageRequest::Semver(req) => versions.into_iter().rev().find(|v| req.matches(v)),
+            _ => unreachable!(),
+        };
+        let Some(version) = version else {
+            if request.is_any() {
+                anyhow::bail!(
+                    "No .NET SDK is installed, please install the .NET SDK to run `dotnet` hooks"
+                );
+            }
+            anyhow::bail!("No installed .NET SDK matches the requested `language_version`");
+        };
+
+        Ok(DotnetSdk { dotnet, version })
+    }
+
+    fn dotnet_cmd(sdk: &Path, summary: &str, env_path: &Path) -> Cmd {
+        let mut cmd = Cmd::new(sdk, summary);
+        // Run from the environment directory, so the `global.json` pinning the SDK is picked up.
+        cmd.current_dir(env_path)
+            .env(EnvVars::DOTNET_CLI_TELEMETRY_OPTOUT, "1")
+            .env(EnvVars::DOTNET_NOLOGO, "1")
+            .remove_git_env()
+            .check(true);
+        cmd
+    }
+
+    /// Create the environment at `env_path`, with the tools of the hook repository and the
+    /// additional dependencies.
+    async fn create_env(hook: &Hook, sdk: &DotnetSdk, env_path: &Path) -> Result<()> {
+        let tool_path = bin_dir(env_path);
+        fs_err::tokio::create_dir_all(&tool_path).await?;
+
+        if !hook.language_request.is_any() {
+            let global_json = serde_json::json!({
+                "sdk": {
+                    "version": sdk.version.to_string(),
+                    "rollForward": "disable",
+                }
+            });
+            fs_err::tokio::write(
+                env_path.join("global.json"),
+                serde_json::to_string_pretty(&global_json)?,
+            )
+            .await?;
+        }
+
+        // Pack the hook repository and install the tools it provides
+        if let Some(repo) = hook.repo_path() {
+            let build_dir = env_path.join("build");
+            Self::dotnet_cmd(&sdk.dotnet, "dotnet pack", env_path)
+                .arg("pack")
+                .arg(repo)
+                .arg("--configuration")
+                .arg("Release")
+                .arg(format!(
+                    "--property:PackageOutputPath={}",
+                    build_dir.display()
+                ))
+                .output()
+                .await?;
+
+            let mut packages = Vec::new();
+            for entry in fs_err::read_dir(&build_dir)? {
+                let path = entry?.path();
+                if let Some(package) = path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .and_then(package_id)
+                {
+                    packages.push(package.to_string());
+                }
+            }
+            if packages.is_empty() {
+                anyhow::bail!("`dotnet pack` did not produce any tool package");
+            }
+
+            for package in packages {
+                Self::dotnet_cmd(&sdk.dotnet, "dotnet tool install", env_path)
+                    .arg("tool")
+                    .arg("install")
+                    .arg("--tool-path")
+                    .arg(&tool_path)
+                    .arg("--add-source")
+                    .arg(&build_dir)
+                    .arg(&package)
+                    .output()
+                    .await?;
+            }
+
+            fs_err::tokio::remove_dir_all(&build_dir).await?;
+        }
+
+        // Install additional tools, specified as `<package>` or `<package>:<version>`
+        for dep in &hook.additional_dependencies {
+            let mut cmd = Self::dotnet_cmd(&sdk.dotnet, "dotnet tool install", env_path);
+            cmd.arg("tool")
+                .arg("install")
+                .arg("--tool-path")
+                .arg(&tool_path);
+            if let Some((package, version)) = dep.split_once(':') {
+                cmd.arg(package).arg("--version").arg(version);
+            } else {
+                cmd.arg(dep);
+            }
+            cmd.output().await?;
+        }
+
+        Ok(())
+    }
+}
+
+impl LanguageImpl for Dotnet {
+    async fn install(
+        &self,
+        hook: Arc<Hook>,
+        store: &Store,
+        reporter: &HookInstallReporter,
+    ) -> Result<InstalledHook> {
+        let progress = reporter.on_install_start(&hook);
+
+        // 1. Find the .NET SDK
+        let sdk = Self::find_sdk(&hook.language_request).await?;
+
+        let mut info = InstallInfo::new(
+            hook.language,
+            hook.dependencies().clone(),
+            &store.hooks_dir(),
+        );
+        info.with_toolchain(sdk.dotnet.clone())
+            .with_language_version(sdk.version.clone());
+
+        // 2. Create the environment, removing it if any step fails so no broken one is left
+        if let Err(err) = Self::create_env(&hook, &sdk, &info.env_path).await {
+            if let Err(remove_err) = fs_err::tokio::remove_dir_all(&info.env_path).await {
+                warn!("Failed to remove the environment after a failed install: {remove_err}");
+            }
+            return Err(err);
+        }
+
+        reporter.on_install_complete(progress);
+
+        Ok(InstalledHook::Installed {
+            hook,
+            info: Arc::new(info),
+        })
+    }
+
+    async fn check_health(&self) -> Result<()> {
+        Dotnet::dotnet()?;
+        Ok(())
+    }
+
+    async fn run(
+        &self,
+        hook: &InstalledHook,
+        filenames: &[&Path],
+        _store: &Store,
+    ) -> Result<(i32, Vec<u8>)> {
+        let env_dir = hook.env_path().expect("Dotnet must have env path");
+        let InstalledHook::Installed { hook, info } = hook else {
+            unreachable!()
+        };
+
+        let tool_path = bin_dir(env_dir);
+        // Tools installed with `--tool-path` need `DOTNET_ROOT` to locate the runtime.
+        let dotnet_root = dunce::canonicalize(&info.toolchain)
+            .ok()
+            .and_then(|dotnet| dotnet.parent().map(Path::to_path_buf));
+        let new_path = prepend_paths(&[&tool_path]).context("Failed to join PATH")?;
+
+        let entry = hook.entry.resolve(Some(&new_path))?;
+        let run = async move |batch: &[&Path]| {
+            let mut cmd = hook_cmd(hook, &entry[0], "dotnet hook");
+            cmd.current_dir(hook.work_dir())
+                .args(&entry[1..])
+                .env("PATH", &new_path)
+                .env(EnvVars::DOTNET_CLI_TELEMETRY_OPTOUT, "1")
+                .env(EnvVars::DOTNET_NOLOGO, "1");
+            if let Some(dotnet_root) = &dotnet_root {
+                cmd.env(EnvVars::DOTNET_ROOT, dotnet_root);
+            }
+            let mut output = cmd
+                .args(&hook.args)
+                .args(batch)
+                .check(false)
+                .pty_output()
+                .await?;
+
+            output.stdout.extend(output.stderr);
+            let code = output.status.code().unwrap_or(1);
+            anyhow::Ok((code, output.stdout))
+        };
+
+        let results = run_by_batch(hook, filenames, run).await?;
+
+        let mut combined_status = 0;
+        let mut combined_output = Vec::new();
+
+        for (code, output) in results {
+            combined_status |= code;
+            combined_output.extend(output);
+        }
+
+        Ok((combined_status, combined_output))
+    }
+}
+
+fn bin_dir(env_path: &Path) -> PathBuf {
+    env_path.join("bin")
+}
+
+/// Extract the package id from a `.nupkg` file name, e.g. `MyTool.1.2.0.nupkg` -> `MyTool`.
+fn package_id(file_name: &str) -> Option<&str> {
+    let stem = file_name.strip_suffix(".nupkg")?;
+    let mut end = 0;
+    for segment in stem.split('.') {
+        if segment.parse::<u64>().is_ok() {
+            break;
+        }
+        end += segment.len() + 1;
+    }
+    if end == 0 || end > stem.len() + 1 {
+        return None;
+    }
+    Some(&stem[..end - 1])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::package_id;
+
+    #[test]
+    fn nupkg_package_id() {
+        assert_eq!(package_id("MyTool.1.2.0.nupkg"), Some("MyTool"));
+        assert_eq!(
+            package_id("Company.Format.Tool.0.1.0-beta.nupkg"),
+            Some("Company.Format.Tool")
+        );
+        assert_eq!(package_id("1.0.0.nupkg"), None);
+        assert_eq!(package_id("MyTool.1.2.0.snupkg"), None);
+    }
+}
@@ -31,9 +31,8 @@ impl LanguageImpl for Fail {
         filenames: &[&Path],
         _store: &Store,
     ) -> Result<(i32, Vec<u8>)> {
-        let mut out = shlex::try_join(hook.entry.resolve(None)?.iter().map(std::ops::Deref::deref))
-            .expect("Failed to join `entry` as command")
-            .into_bytes();
+        // The entry is a plain message, not a command line, so print it verbatim.
+        let mut out = hook.entry.raw().as_bytes().to_vec();
         out.extend(b"\n\n");
         for f in filenames {
             out.extend(f.to_string_lossy().as_bytes());
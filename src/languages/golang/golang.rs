@@ -12,8 +12,7 @@ use crate::languages::LanguageImpl;
 use crate::languages::golang::GoRequest;
 use crate::languages::golang::installer::GoInstaller;
 use crate::languages::version::LanguageRequest;
-use crate::process::Cmd;
-use crate::run::{prepend_paths, run_by_batch};
+use crate::run::{hook_cmd, prepend_paths, run_by_batch};
 use crate::store::{CacheBucket, Store, ToolBucket};
 
 #[derive(Debug, Copy, Clone)]
@@ -142,7 +141,7 @@ impl LanguageImpl for Golang {
 
         let entry = hook.entry.resolve(Some(&new_path))?;
         let run = async move |batch: &[&Path]| {
-            let mut output = Cmd::new(&entry[0], "go hook")
+            let mut output = hook_cmd(hook, &entry[0], "go hook")
                 .current_dir(hook.work_dir())
                 .args(&entry[1..])
                 .env("PATH", &new_path)
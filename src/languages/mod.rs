@@ -20,6 +20,7 @@ use crate::{archive, builtin};
 
 mod docker;
 mod docker_image;
+mod dotnet;
 mod fail;
 mod golang;
 mod node;
@@ -36,6 +37,7 @@ static SYSTEM: system::System = system::System;
 static FAIL: fail::Fail = fail::Fail;
 static DOCKER: docker::Docker = docker::Docker;
 static DOCKER_IMAGE: docker_image::DockerImage = docker_image::DockerImage;
+static DOTNET: dotnet::Dotnet = dotnet::Dotnet;
 static SCRIPT: script::Script = script::Script;
 static PYGREP: pygrep::Pygrep = pygrep::Pygrep;
 static UNIMPLEMENTED: Unimplemented = Unimplemented;
@@ -119,6 +121,7 @@ impl Language {
                 | Self::Fail
                 | Self::Docker
                 | Self::DockerImage
+                | Self::Dotnet
                 | Self::Script
                 | Self::Pygrep
         )
@@ -137,7 +140,7 @@ impl Language {
     pub fn supports_language_version(self) -> bool {
         matches!(
             self,
-            Self::Python | Self::Node | Self::Ruby | Self::Rust | Self::Golang
+            Self::Python | Self::Node | Self::Ruby | Self::Rust | Self::Golang | Self::Dotnet
         )
     }
 
@@ -154,7 +157,6 @@ impl Language {
                 | Self::Script
                 | Self::System
                 | Self::Docker
-                | Self::Dotnet
                 | Self::Swift
         )
     }
@@ -173,6 +175,7 @@ impl Language {
             Self::Fail => FAIL.install(hook, store, reporter).await,
             Self::Docker => DOCKER.install(hook, store, reporter).await,
             Self::DockerImage => DOCKER_IMAGE.install(hook, store, reporter).await,
+            Self::Dotnet => DOTNET.install(hook, store, reporter).await,
             Self::Script => SCRIPT.install(hook, store, reporter).await,
             Self::Pygrep => PYGREP.install(hook, store, reporter).await,
             _ => UNIMPLEMENTED.install(hook, store, reporter).await,
@@ -188,6 +191,7 @@ impl Language {
             Self::Fail => FAIL.check_health().await,
             Self::Docker => DOCKER.check_health().await,
             Self::DockerImage => DOCKER_IMAGE.check_health().await,
+            Self::Dotnet => DOTNET.check_health().await,
             Self::Script => SCRIPT.check_health().await,
             Self::Pygrep => PYGREP.check_health().await,
             _ => UNIMPLEMENTED.check_health().await,
@@ -213,6 +217,7 @@ impl Language {
             Self::Fail => FAIL.run(hook, filenames, store).await,
             Self::Docker => DOCKER.run(hook, filenames, store).await,
             Self::DockerImage => DOCKER_IMAGE.run(hook, filenames, store).await,
+            Self::Dotnet => DOTNET.run(hook, filenames, store).await,
             Self::Script => SCRIPT.run(hook, filenames, store).await,
             Self::Pygrep => PYGREP.run(hook, filenames, store).await,
             _ => UNIMPLEMENTED.run(hook, filenames, store).await,
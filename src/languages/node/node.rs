@@ -17,7 +17,7 @@ use crate::languages::node::installer::{NodeInstaller, bin_dir, lib_dir};
 use crate::languages::node::version::EXTRA_KEY_LTS;
 use crate::languages::version::LanguageRequest;
 use crate::process::Cmd;
-use crate::run::{prepend_paths, run_by_batch};
+use crate::run::{hook_cmd, prepend_paths, run_by_batch};
 use crate::store::{Store, ToolBucket};
 
 #[derive(Debug, Copy, Clone)]
@@ -144,7 +144,7 @@ impl LanguageImpl for Node {
 
         let entry = hook.entry.resolve(Some(&new_path))?;
         let run = async move |batch: &[&Path]| {
-            let mut output = Cmd::new(&entry[0], "node hook")
+            let mut output = hook_cmd(hook, &entry[0], "node hook")
                 .current_dir(hook.work_dir())
                 .args(&entry[1..])
                 .env("PATH", &new_path)
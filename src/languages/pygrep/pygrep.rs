@@ -11,8 +11,7 @@ use crate::cli::reporter::HookInstallReporter;
 use crate::hook::{Hook, InstallInfo, InstalledHook};
 use crate::languages::LanguageImpl;
 use crate::languages::python::{Uv, python_exec};
-use crate::process::Cmd;
-use crate::run::CONCURRENCY;
+use crate::run::{hook_cmd, target_concurrency};
 use crate::store::{CacheBucket, Store, ToolBucket};
 
 #[derive(Debug, Default)]
@@ -184,13 +183,13 @@ impl LanguageImpl for Pygrep {
             .context("Failed to write Python script")?;
 
         let args = Args::parse(&hook.args).context("Failed to parse `args`")?;
-        let mut cmd = Cmd::new(&info.toolchain, "python script")
+        let mut cmd = hook_cmd(hook, &info.toolchain, "python script")
             .current_dir(hook.work_dir())
             .arg("-I") // Isolate mode.
             .arg("-B") // Don't write bytecode.
             .arg(py_script.path())
             .args(args.to_args())
-            .arg(CONCURRENCY.to_string())
+            .arg(target_concurrency(hook).to_string())
             .arg(hook.entry.raw())
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
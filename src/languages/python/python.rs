@@ -16,7 +16,7 @@ use crate::languages::python::uv::Uv;
 use crate::languages::version::LanguageRequest;
 use crate::process;
 use crate::process::Cmd;
-use crate::run::{prepend_paths, run_by_batch};
+use crate::run::{hook_cmd, prepend_paths, run_by_batch};
 use crate::store::{Store, ToolBucket};
 
 #[derive(Debug, Copy, Clone)]
@@ -152,7 +152,7 @@ impl LanguageImpl for Python {
         let entry = hook.entry.resolve(Some(&new_path))?;
 
         let run = async move |batch: &[&Path]| {
-            let mut output = Cmd::new(&entry[0], "python hook")
+            let mut output = hook_cmd(hook, &entry[0], "python hook")
                 .current_dir(hook.work_dir())
                 .args(&entry[1..])
                 .env("VIRTUAL_ENV", env_dir)
@@ -7,8 +7,7 @@ use crate::cli::reporter::HookInstallReporter;
 use crate::hook::Hook;
 use crate::hook::InstalledHook;
 use crate::languages::{LanguageImpl, resolve_command};
-use crate::process::Cmd;
-use crate::run::run_by_batch;
+use crate::run::{hook_cmd, run_by_batch};
 use crate::store::Store;
 
 #[derive(Debug, Copy, Clone)]
@@ -42,11 +41,21 @@ impl LanguageImpl for Script {
         let mut split = hook.entry.split()?;
 
         let cmd = repo_path.join(&split[0]);
+        if !cmd.is_file() {
+            anyhow::bail!("The script `{}` does not exist", cmd.display());
+        }
         split[0] = cmd.to_string_lossy().to_string();
         let entry = resolve_command(split, None);
+        // Scripts with a shebang are run by their interpreter, others are run directly.
+        if Path::new(&entry[0]) == cmd && !is_executable(&cmd) {
+            anyhow::bail!(
+                "The script `{}` is not executable, make it executable or add a shebang line",
+                cmd.display()
+            );
+        }
 
         let run = async move |batch: &[&Path]| {
-            let mut output = Cmd::new(&entry[0], "run script command")
+            let mut output = hook_cmd(hook, &entry[0], "run script command")
                 .current_dir(hook.work_dir())
                 .args(&entry[1..])
                 .args(&hook.args)
@@ -73,3 +82,17 @@ impl LanguageImpl for Script {
         Ok((combined_status, combined_output))
     }
 }
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    path.metadata()
+        .is_ok_and(|metadata| metadata.permissions().mode() & 0o111 != 0)
+}
+
+/// Windows has no executable bit, the extension decides how the script is run.
+#[cfg(not(unix))]
+fn is_executable(_path: &Path) -> bool {
+    true
+}
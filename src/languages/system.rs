@@ -1,13 +1,15 @@
+use std::ffi::OsStr;
 use std::path::Path;
 use std::sync::Arc;
 
 use anyhow::Result;
 
+use constants::env_vars::EnvVars;
+
 use crate::cli::reporter::HookInstallReporter;
 use crate::hook::{Hook, InstalledHook};
 use crate::languages::LanguageImpl;
-use crate::process::Cmd;
-use crate::run::run_by_batch;
+use crate::run::{hook_cmd, hook_env, run_by_batch};
 use crate::store::Store;
 
 #[derive(Debug, Copy, Clone)]
@@ -33,10 +35,28 @@ impl LanguageImpl for System {
         filenames: &[&Path],
         _store: &Store,
     ) -> Result<(i32, Vec<u8>)> {
-        let entry = hook.entry.resolve(None)?;
+        // The hook's `env` may override `PATH`, the program is looked up in that one.
+        let env = hook_env(hook);
+        let env_path = env
+            .iter()
+            .find(|(key, _)| {
+                if cfg!(windows) {
+                    key.eq_ignore_ascii_case(EnvVars::PATH)
+                } else {
+                    *key == EnvVars::PATH
+                }
+            })
+            .map(|(_, value)| OsStr::new(*value));
+        let entry = hook.entry.resolve(env_path)?;
+
+        // `resolve` keeps the program as is when it is not found, which would only fail
+        // with an obscure error when spawning it.
+        if which::which_in(&entry[0], env_path, hook.work_dir()).is_err() {
+            anyhow::bail!("The `{}` executable was not found in PATH", entry[0]);
+        }
 
         let run = async move |batch: &[&Path]| {
-            let mut output = Cmd::new(&entry[0], "run system command")
+            let mut output = hook_cmd(hook, &entry[0], "run system command")
                 .current_dir(hook.work_dir())
                 .args(&entry[1..])
                 .args(&hook.args)
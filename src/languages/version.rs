@@ -82,6 +82,10 @@ impl FromStr for SemverRequest {
 }
 
 impl SemverRequest {
+    pub(crate) fn matches(&self, version: &semver::Version) -> bool {
+        self.0.matches(version)
+    }
+
     fn satisfied_by(&self, install_info: &InstallInfo) -> bool {
         self.0.matches(&install_info.language_version)
     }
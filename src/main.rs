@@ -17,11 +17,15 @@ use tracing_subscriber::util::SubscriberInitExt;
 use tracing_subscriber::{EnvFilter, Layer};
 
 use crate::cleanup::cleanup;
-use crate::cli::{Cli, Command, ExitStatus};
+use crate::cli::run::RunOptions;
+use crate::cli::{Cli, Command, ExitStatus, ListOutputFormat, TryRepoArgs};
 #[cfg(feature = "self-update")]
 use crate::cli::{SelfCommand, SelfNamespace, SelfUpdateArgs};
+use crate::fs::CWD;
+use crate::git::GIT_ROOT;
 use crate::printer::Printer;
 use crate::run::USE_COLOR;
+use crate::sandbox::Sandbox;
 use crate::store::STORE;
 
 mod archive;
@@ -39,6 +43,7 @@ mod process;
 #[cfg(all(unix, feature = "profiler"))]
 mod profiler;
 mod run;
+mod sandbox;
 mod store;
 mod version;
 mod warnings;
@@ -108,7 +113,7 @@ fn setup_logging(level: Level) -> Result<()> {
 }
 
 async fn run(mut cli: Cli) -> Result<ExitStatus> {
-    ColorChoice::write_global(cli.globals.color.into());
+    ColorChoice::write_global(cli.globals.color.resolve());
 
     setup_logging(match cli.globals.verbose {
         0 => Level::Default,
@@ -139,9 +144,39 @@ async fn run(mut cli: Cli) -> Result<ExitStatus> {
     }
 
     if let Some(dir) = cli.globals.cd.as_ref() {
+        // Paths given on the command line are relative to where prek was invoked,
+        // only discovery starts from the `--cd` directory.
+        let cwd = std::env::current_dir()?;
+        if let Some(config) = cli.globals.config.as_mut() {
+            if !config::is_stdin_config(config) {
+                *config = cwd.join(&*config);
+            }
+        }
+        match cli.command.as_mut() {
+            Some(Command::Run(args)) => args.resolve_paths(&cwd),
+            Some(Command::TryRepo(args)) => args.run_args.resolve_paths(&cwd),
+            _ => {}
+        }
         std::env::set_current_dir(dir)?;
     }
 
+    if cli.globals.offline {
+        store::set_offline();
+    }
+    if cli.globals.strict {
+        config::set_strict();
+    }
+    if cli.globals.follow_symlinks {
+        workspace::set_follow_symlinks();
+    }
+    if cli.globals.include_ignored {
+        workspace::set_include_ignored();
+    }
+    if cli.globals.skip_invalid_configs {
+        workspace::set_skip_invalid_configs();
+    }
+    workspace::set_workspace_order(cli.globals.workspace_order);
+
     debug!("prek: {}", version::version());
 
     macro_rules! show_settings {
@@ -159,7 +194,21 @@ async fn run(mut cli: Cli) -> Result<ExitStatus> {
     }
     show_settings!(cli.globals, false);
 
-    match cli.command.unwrap() {
+    let mut command = cli.command.unwrap();
+    // `try-repo` is `run` with an in-memory configuration using the hooks of the repository.
+    if let Command::TryRepo(args) = command {
+        show_settings!(args);
+
+        let TryRepoArgs {
+            repository,
+            rev,
+            run_args,
+        } = *args;
+        cli.globals.config = Some(cli::try_repo(repository, rev, printer).await?);
+        command = Command::Run(Box::new(run_args));
+    }
+
+    match command {
         Command::Install(args) => {
             show_settings!(args);
 
@@ -193,28 +242,91 @@ async fn run(mut cli: Cli) -> Result<ExitStatus> {
 
             cli::uninstall(cli.globals.config, args.hook_types, printer).await
         }
-        Command::Run(args) => {
+        Command::Run(mut args) => {
             show_settings!(args);
 
-            cli::run(
+            if args.treat_unknown_language_as_error {
+                hook::set_treat_unknown_language_as_error();
+            }
+
+            if args.clean_env || !args.hook_env_passthrough.is_empty() {
+                run::set_hook_env_passthrough(args.hook_env_passthrough.clone());
+            }
+
+            if !args.env.is_empty() {
+                run::set_hook_env(args.env.clone());
+            }
+
+            if args.sandbox {
+                run::set_hook_sandbox(Sandbox::detect()?);
+            }
+
+            if let Some(jobs) = args.jobs {
+                run::set_jobs(usize::try_from(jobs).unwrap_or(usize::MAX));
+            }
+
+            if !args.hook_concurrency.is_empty() {
+                run::set_hook_concurrency(args.hook_concurrency.iter().cloned().collect());
+            }
+
+            if let Some(timeout) = args.timeout {
+                run::set_default_hook_timeout(timeout);
+            }
+
+            if args.no_parallel_clone {
+                workspace::set_clone_concurrency(1);
+            }
+            if let Some(retries) = args.clone_retries {
+                store::set_clone_retries(retries);
+            }
+
+            if !args.repos.is_empty() {
+                let repos = std::mem::take(&mut args.repos);
+                let options = RunOptions::new(
+                    *args,
+                    cli.globals.config,
+                    cli.globals.refresh,
+                    cli.globals.verbose > 0,
+                );
+                return cli::run::run_repos(repos, options, printer).await;
+            }
+
+            if let (Some(hook), Some(file)) = (args.hook.clone(), args.on_file.clone()) {
+                return cli::run::run_on_file(
+                    cli.globals.config,
+                    hook,
+                    file,
+                    cli.globals.refresh,
+                    printer,
+                )
+                .await;
+            }
+
+            if args.print_resolved_revs {
+                return cli::run::print_resolved_revs(
+                    cli.globals.config,
+                    args.includes,
+                    args.skips,
+                    args.output_format.into(),
+                    cli.globals.refresh,
+                    printer,
+                )
+                .await;
+            }
+
+            let watch = args.watch;
+            let options = RunOptions::new(
+                *args,
                 cli.globals.config,
-                args.includes,
-                args.skips,
-                args.hook_stage,
-                args.from_ref,
-                args.to_ref,
-                args.all_files,
-                args.files,
-                args.directory,
-                args.last_commit,
-                args.show_diff_on_failure,
-                args.dry_run,
                 cli.globals.refresh,
-                args.extra,
                 cli.globals.verbose > 0,
-                printer,
-            )
-            .await
+            );
+            let git_root = GIT_ROOT.as_ref()?;
+            if watch {
+                return cli::run::watch(git_root, options, printer).await;
+            }
+
+            cli::run(git_root, &CWD, options, printer).await
         }
         Command::List(args) => {
             show_settings!(args);
@@ -225,7 +337,12 @@ async fn run(mut cli: Cli) -> Result<ExitStatus> {
                 args.skips,
                 args.hook_stage,
                 args.language,
-                args.output_format,
+                if args.json {
+                    ListOutputFormat::Json
+                } else {
+                    args.output_format
+                },
+                args.projects,
                 cli.globals.refresh,
                 cli.globals.verbose > 0,
                 printer,
@@ -248,18 +365,23 @@ async fn run(mut cli: Cli) -> Result<ExitStatus> {
             )
             .await
         }
-        Command::Clean => cli::clean(printer),
+        Command::Clean(args) => cli::clean(args.yes, printer).await,
+        Command::Gc(args) => {
+            show_settings!(args);
+
+            cli::gc(cli.globals.config, args.all, printer).await
+        }
         Command::ValidateConfig(args) => {
             show_settings!(args);
 
-            Ok(cli::validate_configs(args.configs))
+            Ok(cli::validate_configs(args.configs, cli.globals.config))
         }
         Command::ValidateManifest(args) => {
             show_settings!(args);
 
             Ok(cli::validate_manifest(args.manifests))
         }
-        Command::SampleConfig(args) => cli::sample_config(args.file, printer),
+        Command::SampleConfig(args) => cli::sample_config(args.file, args.force, printer),
         Command::AutoUpdate(args) => {
             cli::auto_update(
                 cli.globals.config,
@@ -27,6 +27,7 @@
 /// Adapt [axoprocess] to use [`tokio::process::Process`] instead of [`std::process::Command`].
 use std::fmt::Display;
 use std::process::Output;
+use std::sync::{Arc, Mutex};
 use std::{
     ffi::OsStr,
     path::Path,
@@ -108,6 +109,9 @@ pub struct Cmd {
     pub inner: tokio::process::Command,
     summary: String,
     check_status: bool,
+    interactive: bool,
+    kill_on_drop: bool,
+    tee: Option<Arc<Mutex<Vec<u8>>>>,
 }
 
 /// Constructors
@@ -119,6 +123,9 @@ impl Cmd {
             summary: summary.into(),
             inner,
             check_status: true,
+            interactive: false,
+            kill_on_drop: false,
+            tee: None,
         }
     }
 }
@@ -149,6 +156,17 @@ impl Cmd {
         self.check_status = checked;
         self
     }
+
+    /// Set whether the command is connected directly to the terminal.
+    ///
+    /// Defaults to `false`.
+    ///
+    /// If true, [`Cmd::pty_output`][] inherits stdin, stdout and stderr instead of
+    /// capturing them, and returns an empty output.
+    pub fn interactive(&mut self, interactive: bool) -> &mut Self {
+        self.interactive = interactive;
+        self
+    }
 }
 
 /// Execution APIs
@@ -199,23 +217,106 @@ impl Cmd {
         self.inner.stdout(Stdio::piped());
         self.inner.stderr(Stdio::piped());
 
-        let output = self.spawn()?.wait_with_output().await?;
+        // Run the command in its own process group, so the processes it spawns
+        // can be killed together with it.
+        #[cfg(unix)]
+        if self.kill_on_drop {
+            self.inner.process_group(0);
+        }
+
+        let child = self.spawn()?;
+        let guard = self.process_group_guard(&child);
+        let output = child.wait_with_output().await?;
+        guard.disarm();
+        self.maybe_check_output(&output)?;
+        Ok(output)
+    }
+
+    /// Like [`Cmd::output`][], but stdout and stderr share one pipe, so the output keeps
+    /// the order it was written in. The combined output is returned as stdout.
+    pub async fn combined_output(&mut self) -> Result<Output, Error> {
+        use std::io::Read;
+
+        self.log_command();
+
+        let pipe = std::io::pipe().and_then(|(reader, writer)| {
+            let stdout = writer.try_clone()?;
+            Ok((reader, stdout, writer))
+        });
+        let (mut reader, stdout, stderr) = pipe.map_err(|cause| Error::Exec {
+            summary: self.summary.clone(),
+            cause,
+        })?;
+        self.inner.stdout(stdout);
+        self.inner.stderr(stderr);
+
+        #[cfg(unix)]
+        if self.kill_on_drop {
+            self.inner.process_group(0);
+        }
+
+        let spawned = self.spawn();
+        // The command holds the write end of the pipe until replaced, reading would never end.
+        self.inner.stdout(Stdio::null());
+        self.inner.stderr(Stdio::null());
+        let mut child = spawned?;
+        let guard = self.process_group_guard(&child);
+
+        let tee = self.tee.clone();
+        let stdout = tokio::task::spawn_blocking(move || {
+            let mut stdout = Vec::new();
+            let mut buffer = [0u8; 4096];
+            loop {
+                let n = match reader.read(&mut buffer) {
+                    Ok(0) => return Ok(stdout),
+                    Ok(n) => n,
+                    Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                    Err(e) => return Err(e),
+                };
+                stdout.extend_from_slice(&buffer[..n]);
+                if let Some(tee) = &tee {
+                    tee.lock().unwrap().extend_from_slice(&buffer[..n]);
+                }
+            }
+        })
+        .await
+        .map_err(std::io::Error::other)??;
+        let status = child.wait().await?;
+        guard.disarm();
+
+        let output = Output {
+            status,
+            stdout,
+            stderr: Vec::new(),
+        };
         self.maybe_check_output(&output)?;
         Ok(output)
     }
 
+    /// Create a guard that kills the process group of the child when dropped before the child exits.
+    fn process_group_guard(&self, child: &tokio::process::Child) -> ProcessGroupGuard {
+        ProcessGroupGuard(child.id().filter(|_| self.kill_on_drop))
+    }
+
     #[cfg(windows)]
     pub async fn pty_output(&mut self) -> Result<Output, Error> {
-        return self.output().await;
+        if self.interactive {
+            return self.interactive_output().await;
+        }
+        return self.combined_output().await;
     }
 
     #[cfg(not(windows))]
     pub async fn pty_output(&mut self) -> Result<Output, Error> {
         use tokio::io::AsyncReadExt;
 
+        if self.interactive {
+            return self.interactive_output().await;
+        }
+
         // If color is not used, fallback to piped output.
         if !*crate::run::USE_COLOR {
-            return self.output().await;
+            return self.combined_output().await;
         }
 
         let (mut pty, pts) = pty::open()?;
@@ -229,6 +330,8 @@ impl Cmd {
         unsafe { self.inner.pre_exec(session_leader) };
 
         let mut child = self.spawn()?;
+        // The child is a session leader, so it leads its own process group too.
+        let guard = self.process_group_guard(&child);
 
         let mut stdout = Vec::new();
         let mut buffer = [0u8; 4096];
@@ -243,6 +346,9 @@ impl Cmd {
                         }
                         Ok(n) => {
                             stdout.extend_from_slice(&buffer[..n]);
+                            if let Some(tee) = &self.tee {
+                                tee.lock().unwrap().extend_from_slice(&buffer[..n]);
+                            }
                         }
                         Err(e) => {
                             // PTY error, try to get child status
@@ -272,6 +378,7 @@ impl Cmd {
             }
         };
 
+        guard.disarm();
         child.stdin.take();
         child.stdout.take();
         child.stderr.take();
@@ -286,6 +393,20 @@ impl Cmd {
         Ok(output)
     }
 
+    /// Run the command with stdio inherited from the current process, so it can talk to the terminal.
+    async fn interactive_output(&mut self) -> Result<Output, Error> {
+        self.inner.stdin(Stdio::inherit());
+        self.inner.stdout(Stdio::inherit());
+        self.inner.stderr(Stdio::inherit());
+
+        let status = self.status().await?;
+        Ok(Output {
+            status,
+            stdout: Vec::new(),
+            stderr: Vec::new(),
+        })
+    }
+
     /// Equivalent to [`std::process::Command::status`][]
     /// but logged, with the error wrapped, and status checked (by default)
     pub async fn status(&mut self) -> Result<ExitStatus, Error> {
@@ -350,6 +471,23 @@ impl Cmd {
         self
     }
 
+    /// Forwards to [`tokio::process::Command::kill_on_drop`][]
+    ///
+    /// On Unix, [`Cmd::output`][] and [`Cmd::pty_output`][] also kill the processes
+    /// spawned by the command, by running it in its own process group.
+    pub fn kill_on_drop(&mut self, kill_on_drop: bool) -> &mut Self {
+        self.kill_on_drop = kill_on_drop;
+        self.inner.kill_on_drop(kill_on_drop);
+        self
+    }
+
+    /// Also append the output of [`Cmd::pty_output`][] to `sink` while it is read,
+    /// so it is kept when the command is cancelled before it exits.
+    pub fn tee_output(&mut self, sink: Arc<Mutex<Vec<u8>>>) -> &mut Self {
+        self.tee = Some(sink);
+        self
+    }
+
     /// Forwards to [`std::process::Command::current_dir`][]
     pub fn current_dir<P: AsRef<Path>>(&mut self, dir: P) -> &mut Self {
         self.inner.current_dir(dir);
@@ -481,6 +619,31 @@ fn skip_args(cmd: &OsStr, cur: &OsStr, next: Option<&&OsStr>) -> usize {
     0
 }
 
+/// Kills the process group led by a child when dropped, unless disarmed after the child exited.
+///
+/// Dropping a running command only kills its direct child, so this also
+/// terminates the grandchildren, which would otherwise keep running and hold its output open.
+struct ProcessGroupGuard(Option<u32>);
+
+impl ProcessGroupGuard {
+    fn disarm(mut self) {
+        self.0 = None;
+    }
+}
+
+impl Drop for ProcessGroupGuard {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        if let Some(pid) = self.0.and_then(|pid| libc::pid_t::try_from(pid).ok()) {
+            trace!("Killing process group {pid}");
+            // SAFETY: `kill` has no memory safety requirements.
+            unsafe {
+                libc::kill(-pid, libc::SIGKILL);
+            }
+        }
+    }
+}
+
 /// Simplified Command Debug output, with args truncated if they're too long.
 impl Display for Cmd {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
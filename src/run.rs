@@ -1,15 +1,23 @@
 use std::cmp::max;
-use std::ffi::OsString;
+use std::collections::BTreeMap;
+use std::ffi::{OsStr, OsString};
+use std::fmt::Display;
 use std::path::Path;
-use std::sync::LazyLock;
+use std::sync::{Arc, LazyLock, Mutex, OnceLock};
+use std::time::Duration;
 
 use anstream::ColorChoice;
 use futures::StreamExt;
-use tracing::trace;
+use rustc_hash::FxHashMap;
+use tokio::sync::watch;
+use tracing::{debug, trace};
 
 use constants::env_vars::EnvVars;
 
+use crate::config::Stage;
 use crate::hook::Hook;
+use crate::process::Cmd;
+use crate::sandbox::Sandbox;
 
 pub(crate) static USE_COLOR: LazyLock<bool> = LazyLock::new(|| {
     match anstream::Stderr::choice(&std::io::stderr()) {
@@ -20,18 +28,220 @@ pub(crate) static USE_COLOR: LazyLock<bool> = LazyLock::new(|| {
     }
 });
 
+/// The number of hook processes to run at the same time, set by `--jobs` or `PREK_JOBS`.
+///
+/// Defaults to the number of logical CPUs.
 pub(crate) static CONCURRENCY: LazyLock<usize> = LazyLock::new(|| {
     if EnvVars::is_set(EnvVars::PREK_NO_CONCURRENCY) {
-        1
-    } else {
-        std::thread::available_parallelism()
-            .map(std::num::NonZero::get)
-            .unwrap_or(1)
+        return 1;
     }
+    if let Some(jobs) = JOBS
+        .get()
+        .copied()
+        .or_else(|| concurrency_from_env(EnvVars::PREK_JOBS))
+    {
+        return jobs;
+    }
+    std::thread::available_parallelism()
+        .map(std::num::NonZero::get)
+        .unwrap_or(1)
 });
 
-fn target_concurrency(serial: bool) -> usize {
-    if serial { 1 } else { *CONCURRENCY }
+/// Read a concurrency limit from the environment variable `name`.
+///
+/// Values that are not a positive integer are ignored, as if the variable was not set.
+pub(crate) fn concurrency_from_env(name: &str) -> Option<usize> {
+    EnvVars::var(name)
+        .ok()
+        .and_then(|value| value.trim().parse::<usize>().ok())
+        .filter(|value| *value > 0)
+}
+
+/// The number of hook processes to run at the same time, set by `--jobs`.
+static JOBS: OnceLock<usize> = OnceLock::new();
+
+/// Set the number of hook processes to run at the same time.
+pub(crate) fn set_jobs(jobs: usize) {
+    JOBS.get_or_init(|| jobs);
+}
+
+/// The environment variables passed through to hooks, in addition to `PATH` and `PRE_COMMIT_*`.
+///
+/// When unset, hooks inherit the full environment of prek.
+static HOOK_ENV_PASSTHROUGH: OnceLock<Vec<String>> = OnceLock::new();
+
+/// Restrict the environment of hooks to `PATH`, `PRE_COMMIT_*` and the given variables.
+pub(crate) fn set_hook_env_passthrough(vars: Vec<String>) {
+    HOOK_ENV_PASSTHROUGH.get_or_init(|| vars);
+}
+
+/// The environment variables passed through to hooks, or `None` if they inherit
+/// the full environment of prek.
+pub(crate) fn hook_env_passthrough() -> Option<&'static [String]> {
+    HOOK_ENV_PASSTHROUGH.get().map(Vec::as_slice)
+}
+
+/// The environment variables set for all hooks, set by `--env`.
+static HOOK_ENV: OnceLock<Vec<(String, String)>> = OnceLock::new();
+
+/// Set the given environment variables for all hooks, overriding the hooks' own `env`.
+pub(crate) fn set_hook_env(vars: Vec<(String, String)>) {
+    HOOK_ENV.get_or_init(|| vars);
+}
+
+/// The sandbox to run hooks in, set by `--sandbox`.
+static HOOK_SANDBOX: OnceLock<Sandbox> = OnceLock::new();
+
+/// Run all hooks in the given sandbox.
+pub(crate) fn set_hook_sandbox(sandbox: Sandbox) {
+    HOOK_SANDBOX.get_or_init(|| sandbox);
+}
+
+/// Whether hooks run in a sandbox.
+pub(crate) fn hook_sandboxed() -> bool {
+    HOOK_SANDBOX.get().is_some()
+}
+
+/// The concurrency of specific hooks by id, set by `--hook-concurrency`.
+static HOOK_CONCURRENCY: OnceLock<FxHashMap<String, usize>> = OnceLock::new();
+
+/// Override the concurrency of the given hooks.
+pub(crate) fn set_hook_concurrency(limits: FxHashMap<String, usize>) {
+    HOOK_CONCURRENCY.get_or_init(|| limits);
+}
+
+/// The timeout of hooks without a `timeout` of their own, set by `--timeout`.
+static DEFAULT_HOOK_TIMEOUT: OnceLock<Duration> = OnceLock::new();
+
+/// Set the timeout of hooks that don't configure one.
+pub(crate) fn set_default_hook_timeout(timeout: Duration) {
+    DEFAULT_HOOK_TIMEOUT.get_or_init(|| timeout);
+}
+
+/// The maximum duration the hook may run, if any.
+pub(crate) fn hook_timeout(hook: &Hook) -> Option<Duration> {
+    hook.timeout.or_else(|| DEFAULT_HOOK_TIMEOUT.get().copied())
+}
+
+/// The stage hooks are run for, set by `--hook-stage`.
+static HOOK_STAGE: OnceLock<Stage> = OnceLock::new();
+
+/// Set the stage hooks are run for.
+pub(crate) fn set_hook_stage(stage: Stage) {
+    HOOK_STAGE.get_or_init(|| stage);
+}
+
+/// The stage hooks are run for, `pre-commit` unless set.
+pub(crate) fn hook_stage() -> Stage {
+    HOOK_STAGE.get().copied().unwrap_or(Stage::PreCommit)
+}
+
+/// The scheduling state of the batches of the running hook.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct BatchProgress {
+    pub(crate) running: usize,
+    pub(crate) queued: usize,
+    pub(crate) done: usize,
+}
+
+impl BatchProgress {
+    pub(crate) fn total(self) -> usize {
+        self.running + self.queued + self.done
+    }
+}
+
+impl Display for BatchProgress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} running, {} queued, {} done",
+            self.running, self.queued, self.done
+        )
+    }
+}
+
+/// Publishes the [`BatchProgress`] of the running hook.
+static BATCH_PROGRESS: LazyLock<watch::Sender<BatchProgress>> =
+    LazyLock::new(|| watch::Sender::new(BatchProgress::default()));
+
+/// Watch the [`BatchProgress`] of the running hook.
+pub(crate) fn subscribe_batch_progress() -> watch::Receiver<BatchProgress> {
+    BATCH_PROGRESS.subscribe()
+}
+
+/// The output of the running hook, kept while it is read so it survives a timeout.
+static HOOK_OUTPUT: LazyLock<Arc<Mutex<Vec<u8>>>> = LazyLock::new(Arc::default);
+
+/// Take the output the running hook has written so far.
+pub(crate) fn take_hook_output() -> Vec<u8> {
+    std::mem::take(&mut *HOOK_OUTPUT.lock().unwrap())
+}
+
+/// Create a command to run a hook, restricting its environment and running it
+/// in a sandbox if requested.
+///
+/// Interactive hooks are connected directly to the terminal.
+pub(crate) fn hook_cmd(hook: &Hook, program: impl AsRef<OsStr>, summary: impl Into<String>) -> Cmd {
+    let mut cmd = match HOOK_SANDBOX.get() {
+        Some(sandbox) => sandbox.cmd(program.as_ref(), hook.work_dir(), summary.into()),
+        None => Cmd::new(program, summary),
+    };
+    // Hooks are terminated when their run is cancelled, like on `--max-total-duration`.
+    cmd.interactive(hook.interactive)
+        .kill_on_drop(true)
+        .tee_output(HOOK_OUTPUT.clone());
+    if let Some(passthrough) = HOOK_ENV_PASSTHROUGH.get() {
+        let keep = |key: &str| {
+            key.starts_with("PRE_COMMIT")
+                || passthrough.iter().any(|var| var == key)
+                || if cfg!(windows) {
+                    // Windows variable names are case-insensitive, and processes can't start without `SYSTEMROOT`.
+                    key.eq_ignore_ascii_case(EnvVars::PATH)
+                        || key.eq_ignore_ascii_case("SYSTEMROOT")
+                } else {
+                    key == EnvVars::PATH
+                }
+        };
+        cmd.env_clear()
+            .envs(std::env::vars_os().filter(|(key, _)| key.to_str().is_some_and(keep)));
+    }
+    cmd.envs(hook_env(hook));
+    cmd
+}
+
+/// The environment variables set for a hook, from its `env` and from `--env`, sorted by name.
+///
+/// Variables from `--env` take precedence over the hook's `env`.
+pub(crate) fn hook_env(hook: &Hook) -> Vec<(&str, &str)> {
+    let mut vars: BTreeMap<&str, &str> = hook
+        .env
+        .iter()
+        .map(|(key, value)| (key.as_str(), value.as_str()))
+        .collect();
+    if let Some(global) = HOOK_ENV.get() {
+        vars.extend(
+            global
+                .iter()
+                .map(|(key, value)| (key.as_str(), value.as_str())),
+        );
+    }
+    vars.into_iter().collect()
+}
+
+/// The number of batches of files of a hook to process at the same time.
+///
+/// Hooks with `require_serial` process one batch at a time, and get all their files in
+/// a single batch unless they exceed the command line length limit.
+pub(crate) fn target_concurrency(hook: &Hook) -> usize {
+    // Interactive hooks may prompt the user, so run one batch at a time.
+    if hook.require_serial || hook.interactive {
+        return 1;
+    }
+    HOOK_CONCURRENCY
+        .get()
+        .and_then(|limits| limits.get(&hook.id))
+        .copied()
+        .unwrap_or(*CONCURRENCY)
 }
 
 /// Iterator that yields partitions of filenames that fit within the maximum command line length.
@@ -111,10 +321,13 @@ impl<'a> Iterator for Partitions<'a> {
 
         while self.current_index < self.filenames.len() {
             let filename = self.filenames[self.current_index];
-            let length = filename.to_string_lossy().len() + 1;
+            // Paths are passed as OS strings, count their raw length rather than a lossy copy.
+            let length = filename.as_os_str().len() + 1;
 
-            if current_length + length > self.max_cli_length
-                || self.current_index - start_index >= self.max_per_batch
+            // A single path longer than the limit still gets its own batch instead of being dropped.
+            if self.current_index > start_index
+                && (current_length + length > self.max_cli_length
+                    || self.current_index - start_index >= self.max_per_batch)
             {
                 break;
             }
@@ -123,11 +336,7 @@ impl<'a> Iterator for Partitions<'a> {
             self.current_index += 1;
         }
 
-        if self.current_index == start_index {
-            None
-        } else {
-            Some(&self.filenames[start_index..self.current_index])
-        }
+        Some(&self.filenames[start_index..self.current_index])
     }
 }
 
@@ -140,10 +349,10 @@ where
     F: for<'a> AsyncFn(&'a [&'a Path]) -> anyhow::Result<T>,
     T: Send + 'static,
 {
-    let concurrency = target_concurrency(hook.require_serial);
+    let concurrency = target_concurrency(hook);
 
     // Split files into batches
-    let partitions = Partitions::new(hook, filenames, concurrency);
+    let partitions = Partitions::new(hook, filenames, concurrency).collect::<Vec<_>>();
     trace!(
         total_files = filenames.len(),
         concurrency = concurrency,
@@ -151,9 +360,32 @@ where
         hook.id,
     );
 
-    #[allow(clippy::redundant_closure)]
+    // Track the batches as they are scheduled, rather than only once they complete.
+    let progress = Mutex::new(BatchProgress {
+        queued: partitions.len(),
+        ..BatchProgress::default()
+    });
+    let update = |change: fn(&mut BatchProgress)| {
+        let mut progress = progress.lock().unwrap();
+        change(&mut progress);
+        debug!("Batches of `{hook}`: {progress}");
+        BATCH_PROGRESS.send_replace(*progress);
+    };
+    let (run, update) = (&run, &update);
+
     let mut tasks = futures::stream::iter(partitions)
-        .map(|batch| run(batch))
+        .map(|batch| async move {
+            update(|progress| {
+                progress.queued -= 1;
+                progress.running += 1;
+            });
+            let result = run(batch).await;
+            update(|progress| {
+                progress.running -= 1;
+                progress.done += 1;
+            });
+            result
+        })
         .buffered(concurrency);
 
     let mut results = Vec::new();
@@ -0,0 +1,68 @@
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+
+use crate::process::Cmd;
+use crate::warn_user;
+
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum Error {
+    #[error(
+        "No sandbox backend is available for `--sandbox`, install `bwrap` (bubblewrap) or `unshare` (util-linux)"
+    )]
+    NotAvailable,
+}
+
+/// A program used to run hooks in isolation.
+#[derive(Debug)]
+pub(crate) enum Sandbox {
+    /// Bubblewrap: read-only filesystem except for the project directory, and no network.
+    Bwrap(PathBuf),
+    /// Linux namespaces via `unshare`: no network, but the filesystem is not restricted.
+    Unshare(PathBuf),
+}
+
+impl Sandbox {
+    /// Find an available sandbox backend.
+    pub(crate) fn detect() -> Result<Self, Error> {
+        if !cfg!(target_os = "linux") {
+            return Err(Error::NotAvailable);
+        }
+        if let Ok(bwrap) = which::which("bwrap") {
+            return Ok(Self::Bwrap(bwrap));
+        }
+        if let Ok(unshare) = which::which("unshare") {
+            warn_user!(
+                "`bwrap` was not found, `--sandbox` only blocks network access of hooks using `unshare`"
+            );
+            return Ok(Self::Unshare(unshare));
+        }
+        Err(Error::NotAvailable)
+    }
+
+    /// Create a command that runs `program` in the sandbox, with `work_dir` writable.
+    pub(crate) fn cmd(&self, program: &OsStr, work_dir: &Path, summary: String) -> Cmd {
+        match self {
+            Self::Bwrap(bwrap) => {
+                let mut cmd = Cmd::new(bwrap, summary);
+                cmd.args(["--ro-bind", "/", "/"])
+                    .args(["--dev", "/dev"])
+                    .args(["--proc", "/proc"])
+                    .args(["--tmpfs", "/tmp"]);
+                let temp_dir = std::env::temp_dir();
+                for dir in [temp_dir.as_path(), work_dir] {
+                    let dir = OsString::from(dir);
+                    cmd.arg("--bind").arg(&dir).arg(&dir);
+                }
+                cmd.args(["--unshare-net", "--die-with-parent", "--"])
+                    .arg(program);
+                cmd
+            }
+            Self::Unshare(unshare) => {
+                let mut cmd = Cmd::new(unshare, summary);
+                cmd.args(["--user", "--map-root-user", "--net", "--"])
+                    .arg(program);
+                cmd
+            }
+        }
+    }
+}
@@ -1,19 +1,25 @@
 use std::hash::{DefaultHasher, Hash, Hasher};
 use std::io::Write;
 use std::path::{Path, PathBuf};
-use std::sync::{Arc, LazyLock};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, LazyLock, OnceLock};
+use std::time::Duration;
 
 use anyhow::Result;
 use etcetera::BaseStrategy;
+use futures::StreamExt;
+use rustc_hash::{FxHashMap, FxHashSet};
 use thiserror::Error;
 use tracing::debug;
 
 use constants::env_vars::EnvVars;
 
+use crate::cli::parse_duration;
 use crate::config::RemoteRepo;
 use crate::fs::LockedFile;
 use crate::git::clone_repo;
-use crate::hook::InstallInfo;
+use crate::hook::{InstallInfo, Repo};
+use crate::workspace;
 use crate::workspace::HookInitReporter;
 
 #[derive(Debug, Error)]
@@ -26,6 +32,89 @@ pub enum Error {
     Git(#[from] crate::git::Error),
     #[error(transparent)]
     Serde(#[from] serde_json::Error),
+    #[error("repo `{repo}` at rev `{rev}` is not cached and --offline was set")]
+    Offline { repo: String, rev: String },
+}
+
+impl Error {
+    /// Whether the error is likely caused by the network, so trying again may work.
+    fn is_transient(&self) -> bool {
+        match self {
+            Error::Git(err) => err.is_transient(),
+            _ => false,
+        }
+    }
+}
+
+/// How clones failing with a transient error are retried.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct CloneRetry {
+    /// The number of retries after the first attempt.
+    pub(crate) retries: u32,
+    /// The delay before the first retry, doubled before each following retry.
+    pub(crate) backoff: Duration,
+}
+
+impl CloneRetry {
+    /// Read from `--clone-retries` or `PREK_CLONE_RETRIES`, and `PREK_CLONE_RETRY_BACKOFF`,
+    /// defaults to 2 retries starting after 1 second.
+    fn from_env() -> Self {
+        let retries = CLONE_RETRIES
+            .get()
+            .copied()
+            .or_else(|| {
+                EnvVars::var(EnvVars::PREK_CLONE_RETRIES)
+                    .ok()
+                    .and_then(|value| value.parse().ok())
+            })
+            .unwrap_or(2);
+        let backoff = EnvVars::var(EnvVars::PREK_CLONE_RETRY_BACKOFF)
+            .ok()
+            .and_then(|value| parse_duration(&value).ok())
+            .unwrap_or(Duration::from_secs(1));
+        Self { retries, backoff }
+    }
+
+    /// Run `attempt` until it succeeds, retrying transient errors with exponential backoff.
+    ///
+    /// `on_retry` is called with the number of the next attempt before waiting for it.
+    async fn run<T>(
+        self,
+        mut attempt: impl AsyncFnMut() -> Result<T, Error>,
+        mut on_retry: impl FnMut(u32, &Error),
+    ) -> Result<T, Error> {
+        let mut delay = self.backoff;
+        let mut retries = 0;
+        loop {
+            match attempt().await {
+                Err(err) if retries < self.retries && err.is_transient() => {
+                    retries += 1;
+                    on_retry(retries + 1, &err);
+                    tokio::time::sleep(delay).await;
+                    delay = delay.saturating_mul(2);
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// The number of clone retries, set by `--clone-retries`.
+static CLONE_RETRIES: OnceLock<u32> = OnceLock::new();
+
+/// Set the number of retries of clones failing with a transient error.
+pub(crate) fn set_clone_retries(retries: u32) {
+    CLONE_RETRIES.get_or_init(|| retries);
+}
+
+static CLONE_RETRY: LazyLock<CloneRetry> = LazyLock::new(CloneRetry::from_env);
+
+/// Whether cloning is disabled, set by `--offline`.
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+/// Disable cloning, only the repos already in the store can be used.
+pub(crate) fn set_offline() {
+    OFFLINE.store(true, Ordering::Relaxed);
 }
 
 pub(crate) static STORE: LazyLock<Result<Store, Error>> = LazyLock::new(|| {
@@ -87,21 +176,63 @@ impl Store {
             return Ok(target);
         }
 
-        let progress =
-            reporter.map(|reporter| (reporter, reporter.on_clone_start(&format!("{repo}"))));
+        if OFFLINE.load(Ordering::Relaxed) || EnvVars::is_set(EnvVars::PREK_OFFLINE) {
+            return Err(Error::Offline {
+                repo: repo.repo.clone(),
+                rev: repo.rev.clone(),
+            });
+        }
 
         fs_err::tokio::create_dir_all(self.repos_dir()).await?;
 
+        // Another process may be cloning the same repo, wait for it and reuse its clone.
+        let _lock = LockedFile::acquire(target.with_extension("lock"), format!("{repo}")).await?;
+        if target.join(".prek-repo.json").try_exists()? {
+            debug!(?repo, "Repo cloned by another process");
+            return Ok(target);
+        }
+
+        let progress =
+            reporter.map(|reporter| (reporter, reporter.on_clone_start(&format!("{repo}"))));
+
         // Clone and checkout the repo.
         fs_err::tokio::create_dir_all(self.scratch_path()).await?;
-        let temp = tempfile::tempdir_in(self.scratch_path())?;
-
-        debug!(
-            target = %temp.path().display(),
-            ?repo,
-            "Cloning repo",
-        );
-        clone_repo(&repo.repo, &repo.rev, temp.path()).await?;
+        let on_progress = |received, total| {
+            if let Some((reporter, id)) = progress {
+                reporter.on_clone_progress(id, received, total);
+            }
+        };
+        let retry = *CLONE_RETRY;
+        let temp = retry
+            .run(
+                async || {
+                    // Each attempt starts over in a new directory.
+                    let temp = tempfile::tempdir_in(self.scratch_path())?;
+                    debug!(
+                        target = %temp.path().display(),
+                        ?repo,
+                        "Cloning repo",
+                    );
+                    clone_repo(
+                        &repo.repo,
+                        &repo.rev,
+                        temp.path(),
+                        progress.is_some().then_some(&on_progress),
+                    )
+                    .await?;
+                    Ok(temp)
+                },
+                |attempt, err| {
+                    debug!(
+                        ?repo,
+                        attempt, "Retrying clone after transient error: {err}"
+                    );
+                    if let Some((reporter, id)) = progress {
+                        reporter.on_clone_retry(id, &format!("{repo}"), attempt, retry.retries + 1);
+                    }
+                },
+            )
+            .await?;
 
         // TODO: add windows retry
         fs_err::tokio::remove_dir_all(&target).await.ok();
@@ -109,6 +240,7 @@ impl Store {
 
         let content = serde_json::to_string_pretty(&repo)?;
         fs_err::tokio::write(target.join(".prek-repo.json"), content).await?;
+        debug!(?repo, "Cloned repo");
 
         if let Some((reporter, progress)) = progress {
             reporter.on_clone_complete(progress);
@@ -117,6 +249,42 @@ impl Store {
         Ok(target)
     }
 
+    /// Clone remote repos into the store in parallel, at most `jobs` at a time.
+    ///
+    /// Repos are deduplicated by their `repo` and `rev`, so each is cloned only once.
+    pub(crate) async fn clone_repos<'a>(
+        &self,
+        repos: impl IntoIterator<Item = &'a RemoteRepo>,
+        jobs: usize,
+        reporter: Option<&dyn HookInitReporter>,
+    ) -> Result<FxHashMap<&'a RemoteRepo, Arc<Repo>>, workspace::Error> {
+        let mut seen = FxHashSet::default();
+        let repos = repos.into_iter().filter(|repo| seen.insert(*repo));
+
+        let mut tasks = futures::stream::iter(repos)
+            .map(async |repo_config| {
+                let path = self.clone_repo(repo_config, reporter).await.map_err(|e| {
+                    workspace::Error::Store {
+                        repo: repo_config.repo.clone(),
+                        error: Box::new(e),
+                    }
+                })?;
+
+                let repo = Repo::remote(repo_config.repo.clone(), repo_config.rev.clone(), path)?;
+
+                Ok::<_, workspace::Error>((repo_config, Arc::new(repo)))
+            })
+            .buffer_unordered(jobs.max(1));
+
+        let mut cloned = FxHashMap::default();
+        while let Some(result) = tasks.next().await {
+            let (repo_config, repo) = result?;
+            cloned.insert(repo_config, repo);
+        }
+
+        Ok(cloned)
+    }
+
     /// Returns installed hooks in the store.
     pub(crate) fn installed_hooks(&self) -> impl Iterator<Item = Arc<InstallInfo>> {
         fs_err::read_dir(self.hooks_dir())
@@ -141,6 +309,12 @@ impl Store {
     }
 
     /// Returns the path to the cloned repo.
+    /// The path of a remote repo in the store, if it is already cloned.
+    pub(crate) fn cloned_repo(&self, repo: &RemoteRepo) -> Option<PathBuf> {
+        let target = self.repo_path(repo);
+        target.join(".prek-repo.json").is_file().then_some(target)
+    }
+
     fn repo_path(&self, repo: &RemoteRepo) -> PathBuf {
         let mut hasher = DefaultHasher::new();
         repo.hash(&mut hasher);
@@ -221,3 +395,172 @@ impl CacheBucket {
 fn to_hex(num: u64) -> String {
     hex::encode(num.to_le_bytes())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    /// Counts the clones that actually fetched the repo.
+    #[derive(Default)]
+    struct CountingReporter(AtomicUsize);
+
+    impl HookInitReporter for CountingReporter {
+        fn on_clone_start(&self, _repo: &str) -> usize {
+            self.0.fetch_add(1, Ordering::SeqCst)
+        }
+        fn on_clone_retry(&self, _id: usize, _repo: &str, _attempt: u32, _attempts: u32) {}
+        fn on_clone_progress(&self, _id: usize, _received_bytes: u64, _total_bytes: Option<u64>) {}
+        fn on_clone_complete(&self, _id: usize) {}
+        fn on_complete(&self) {}
+    }
+
+    fn git(dir: &Path, args: &[&str]) -> Result<String> {
+        let output = std::process::Command::new("git")
+            .args(["-c", "user.name=prek", "-c", "user.email=prek@example.com"])
+            .args(args)
+            .current_dir(dir)
+            .output()?;
+        anyhow::ensure!(output.status.success(), "git {args:?} failed");
+        Ok(String::from_utf8(output.stdout)?.trim().to_string())
+    }
+
+    #[tokio::test]
+    async fn concurrent_clones_fetch_once() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let remote = dir.path().join("remote");
+        fs_err::create_dir_all(&remote)?;
+        git(&remote, &["init"])?;
+        fs_err::write(remote.join(".pre-commit-hooks.yaml"), "[]\n")?;
+        git(&remote, &["add", "."])?;
+        git(&remote, &["commit", "-m", "init"])?;
+        let rev = git(&remote, &["rev-parse", "HEAD"])?;
+
+        let store = Store::from_path(dir.path().join("store")).init()?;
+        let repo = RemoteRepo {
+            repo: remote.to_string_lossy().into_owned(),
+            rev,
+            hooks: vec![],
+        };
+        let reporter = CountingReporter::default();
+
+        let (first, second) = tokio::join!(
+            store.clone_repo(&repo, Some(&reporter)),
+            store.clone_repo(&repo, Some(&reporter)),
+        );
+
+        assert_eq!(first?, second?);
+        assert_eq!(reporter.0.load(Ordering::SeqCst), 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn clone_fetches_only_pinned_rev() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let remote = dir.path().join("remote");
+        fs_err::create_dir_all(&remote)?;
+        git(&remote, &["init"])?;
+        fs_err::write(remote.join(".pre-commit-hooks.yaml"), "[]\n")?;
+        git(&remote, &["add", "."])?;
+        git(&remote, &["commit", "-m", "first"])?;
+        git(&remote, &["tag", "v1.0.0"])?;
+        let pinned = git(&remote, &["rev-parse", "HEAD"])?;
+        git(&remote, &["commit", "--allow-empty", "-m", "second"])?;
+        let later = git(&remote, &["rev-parse", "HEAD"])?;
+
+        let store = Store::from_path(dir.path().join("store")).init()?;
+        let repo = RemoteRepo {
+            repo: remote.to_string_lossy().into_owned(),
+            rev: "v1.0.0".to_string(),
+            hooks: vec![],
+        };
+        let path = store.clone_repo(&repo, None).await?;
+
+        assert_eq!(git(&path, &["rev-parse", "HEAD"])?, pinned);
+        assert_eq!(
+            git(&path, &["rev-parse", "--is-shallow-repository"])?,
+            "true"
+        );
+        assert!(git(&path, &["cat-file", "-e", &later]).is_err());
+
+        Ok(())
+    }
+
+    /// A `git fetch` failure with the given stderr.
+    fn fetch_error(stderr: &str) -> Error {
+        Error::Git(crate::git::Error::Command(crate::process::Error::Status {
+            summary: "git shallow clone".to_string(),
+            error: crate::process::StatusError {
+                status: std::process::ExitStatus::default(),
+                output: Some(std::process::Output {
+                    status: std::process::ExitStatus::default(),
+                    stdout: Vec::new(),
+                    stderr: stderr.as_bytes().to_vec(),
+                }),
+            },
+        }))
+    }
+
+    #[tokio::test]
+    async fn clone_retries_transient_errors() -> Result<()> {
+        let retry = CloneRetry {
+            retries: 2,
+            backoff: Duration::ZERO,
+        };
+
+        // Fails twice, then succeeds.
+        let mut calls = 0;
+        let mut retries = Vec::new();
+        let result = retry
+            .run(
+                async || {
+                    calls += 1;
+                    if calls < 3 {
+                        Err(fetch_error(
+                            "fatal: unable to access 'https://github.com/x/y/': Could not resolve host: github.com",
+                        ))
+                    } else {
+                        Ok(calls)
+                    }
+                },
+                |attempt, _| retries.push(attempt),
+            )
+            .await?;
+        assert_eq!(result, 3);
+        assert_eq!(retries, [2, 3]);
+
+        // Authoritative errors fail right away.
+        let mut calls = 0;
+        let result = retry
+            .run(
+                async || {
+                    calls += 1;
+                    Err::<(), _>(fetch_error(
+                        "remote: Repository not found.\nfatal: repository 'https://github.com/x/y/' not found",
+                    ))
+                },
+                |_, _| {},
+            )
+            .await;
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+
+        // Gives up after the last retry.
+        let mut calls = 0;
+        let result = retry
+            .run(
+                async || {
+                    calls += 1;
+                    Err::<(), _>(fetch_error("fatal: the remote end hung up unexpectedly"))
+                },
+                |_, _| {},
+            )
+            .await;
+        assert!(result.is_err());
+        assert_eq!(calls, 3);
+
+        Ok(())
+    }
+}
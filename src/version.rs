@@ -26,6 +26,10 @@ use std::fmt;
 
 use serde::Serialize;
 
+/// The version of pre-commit that prek is compatible with,
+/// checked against `minimum_pre_commit_version` in configs.
+pub(crate) const PRE_COMMIT_VERSION: &str = "4.3.0";
+
 /// Information about the git repository where prek was built from.
 #[derive(Serialize)]
 pub(crate) struct CommitInfo {
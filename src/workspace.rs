@@ -2,28 +2,31 @@ use std::borrow::Cow;
 use std::fmt::Display;
 use std::hash::{DefaultHasher, Hash, Hasher};
 use std::path::{Path, PathBuf};
-use std::sync::{Arc, Mutex};
-use std::time::SystemTime;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, LazyLock, Mutex, OnceLock};
+use std::time::{Duration, Instant, SystemTime};
 
 use anyhow::Result;
-use constants::{ALT_CONFIG_FILE, CONFIG_FILE};
-use futures::StreamExt;
+use constants::env_vars::EnvVars;
+use constants::{ALT_CONFIG_FILE, CONFIG_FILE, PREKIGNORE_FILE, WORKSPACE_MANIFEST_FILE};
 use ignore::WalkState;
-use itertools::zip_eq;
+use ignore::gitignore::Gitignore;
+use ignore::overrides::{Override, OverrideBuilder};
+use itertools::{Itertools, zip_eq};
 use owo_colors::OwoColorize;
-use rustc_hash::{FxHashMap, FxHashSet};
+use path_clean::PathClean;
+use rustc_hash::FxHashMap;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use tracing::{debug, error, instrument};
 
 use crate::cli::run::Selectors;
-use crate::config::{self, Config, ManifestHook, read_config};
+use crate::config::{self, Config, ManifestHook, read_config_cached};
 use crate::fs::Simplified;
-use crate::git::GIT_ROOT;
 use crate::hook::{self, Hook, HookBuilder, Repo};
 use crate::store::{CacheBucket, STORE, Store};
 use crate::workspace::Error::MissingPreCommitConfig;
-use crate::{git, store, warn_user};
+use crate::{git, run, store, warn_user};
 
 #[derive(Error, Debug)]
 pub(crate) enum Error {
@@ -44,31 +47,177 @@ pub(crate) enum Error {
     #[error("Hook `{hook}` not present in repo `{repo}`")]
     HookNotFound { hook: String, repo: String },
 
+    #[error("Repo path `{}` is outside of the git repository", path.display())]
+    RepoPathOutsideGitRoot { path: PathBuf },
+
     #[error("Failed to initialize repo `{repo}`")]
     Store {
         repo: String,
         #[source]
         error: Box<store::Error>,
     },
+
+    #[error(
+        "Project `{}` not found in the workspace, available projects are: {available}",
+        path.display()
+    )]
+    ProjectNotFound { path: PathBuf, available: String },
+
+    #[error("Found {} invalid configs:{}", errors.len(), list_config_errors(errors))]
+    InvalidConfigs { errors: Vec<config::Error> },
+
+    #[error("Invalid workspace manifest `{}`", path.user_display())]
+    WorkspaceManifest {
+        path: PathBuf,
+        #[source]
+        error: anyhow::Error,
+    },
+}
+
+/// List each error with its causes on one line, sorted since the parallel walk finds them in any order.
+fn list_config_errors(errors: &[config::Error]) -> String {
+    let mut lines = errors.iter().map(config_error_line).collect::<Vec<_>>();
+    lines.sort_unstable();
+    lines.iter().map(|line| format!("\n  - {line}")).join("")
+}
+
+/// Format a config error with its causes on one line.
+fn config_error_line(error: &config::Error) -> String {
+    let mut line = error.to_string();
+    let mut source = std::error::Error::source(error);
+    while let Some(cause) = source {
+        line = format!("{line}: {cause}");
+        source = std::error::Error::source(cause);
+    }
+    line
+}
+
+/// The `.prek-workspace.yaml` file at the git root, selecting the projects of the workspace.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct WorkspaceManifest {
+    /// Globs of project directories to include, all projects are included if empty.
+    #[serde(default)]
+    include: Vec<String>,
+    /// Globs of directories to exclude, together with all their subdirectories.
+    #[serde(default)]
+    exclude: Vec<String>,
+    /// Whether to walk into symlinked directories.
+    #[serde(default)]
+    follow_symlinks: bool,
+}
+
+/// Decides which directories take part in the workspace, from the workspace manifest.
+struct ProjectFilter {
+    include: Option<Override>,
+    exclude: Option<Override>,
+    follow_symlinks: bool,
+}
+
+impl ProjectFilter {
+    /// Load the workspace manifest from the git root, if any.
+    fn load(git_root: &Path) -> Result<Option<Self>, Error> {
+        let path = git_root.join(WORKSPACE_MANIFEST_FILE);
+        let content = match fs_err::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(config::Error::Io(e).into()),
+        };
+
+        let manifest: WorkspaceManifest = serde_yaml::from_str(&content)
+            .map_err(|e| config::Error::Yaml(path.user_display().to_string(), e))?;
+
+        let build = |globs: &[String]| -> Result<Option<Override>, ignore::Error> {
+            if globs.is_empty() {
+                return Ok(None);
+            }
+            let mut builder = OverrideBuilder::new(git_root);
+            for glob in globs {
+                builder.add(glob)?;
+            }
+            builder.build().map(Some)
+        };
+        let filter = build(&manifest.include)
+            .and_then(|include| Ok((include, build(&manifest.exclude)?)))
+            .map(|(include, exclude)| Self {
+                include,
+                exclude,
+                follow_symlinks: manifest.follow_symlinks,
+            })
+            .map_err(|e| Error::WorkspaceManifest {
+                path,
+                error: e.into(),
+            })?;
+
+        Ok(Some(filter))
+    }
+
+    /// Whether the directory and all its subdirectories are excluded.
+    fn is_excluded(&self, dir: &Path) -> bool {
+        self.exclude
+            .as_ref()
+            .is_some_and(|exclude| exclude.matched(dir, true).is_whitelist())
+    }
+
+    /// Whether a project in the directory takes part in the workspace.
+    fn is_included(&self, dir: &Path) -> bool {
+        self.include
+            .as_ref()
+            .is_none_or(|include| include.matched(dir, true).is_whitelist())
+    }
+}
+
+/// The number of repos to clone concurrently, set by `--no-parallel-clone`.
+static CLONE_CONCURRENCY_OVERRIDE: OnceLock<usize> = OnceLock::new();
+
+/// Set the number of repos to clone concurrently.
+pub(crate) fn set_clone_concurrency(concurrency: usize) {
+    CLONE_CONCURRENCY_OVERRIDE.get_or_init(|| concurrency.max(1));
 }
 
+/// The number of repos to clone concurrently, the first of:
+///
+/// 1. `--no-parallel-clone`, which clones one repo at a time.
+/// 2. `PREK_CLONE_CONCURRENCY`.
+/// 3. `PREK_CONCURRENCY`.
+/// 4. The default of 5.
+///
+/// Both variables are ignored unless they are a positive integer. `--jobs` and `PREK_JOBS`
+/// only limit the hook processes, they don't affect cloning.
+static CLONE_CONCURRENCY: LazyLock<usize> = LazyLock::new(|| {
+    let concurrency = CLONE_CONCURRENCY_OVERRIDE
+        .get()
+        .copied()
+        .or_else(|| run::concurrency_from_env(EnvVars::PREK_CLONE_CONCURRENCY))
+        .or_else(|| run::concurrency_from_env(EnvVars::PREK_CONCURRENCY))
+        .unwrap_or(5);
+    debug!("Cloning up to {concurrency} repos concurrently");
+    concurrency
+});
+
 pub(crate) trait HookInitReporter {
     fn on_clone_start(&self, repo: &str) -> usize;
+    /// Report that a clone failed with a transient error, and is tried again.
+    fn on_clone_retry(&self, id: usize, repo: &str, attempt: u32, attempts: u32);
+    /// Report the bytes received by a clone, `total_bytes` is `None` when the size is unknown.
+    fn on_clone_progress(&self, id: usize, received_bytes: u64, total_bytes: Option<u64>);
     fn on_clone_complete(&self, id: usize);
     fn on_complete(&self);
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 pub(crate) struct Project {
     /// The absolute path of the project directory.
     root: PathBuf,
     /// The absolute path of the configuration file.
     config_path: PathBuf,
+    /// The root of the git repository the project is in.
+    git_root: PathBuf,
     /// The relative path of the project directory from the git root.
     relative_path: PathBuf,
     // The order index of the project in the workspace.
     idx: usize,
-    config: Config,
+    config: Arc<Config>,
     repos: Vec<Arc<Repo>>,
 }
 
@@ -102,13 +251,14 @@ impl Project {
     pub(crate) fn from_config_file(
         config_path: Cow<'_, Path>,
         root: Option<PathBuf>,
+        git_root: &Path,
     ) -> Result<Self, config::Error> {
         debug!(
             path = %config_path.user_display(),
             "Loading project configuration"
         );
 
-        let config = read_config(&config_path)?;
+        let config = read_config_cached(&config_path)?;
         let size = config.repos.len();
 
         let root = root.unwrap_or_else(|| {
@@ -122,6 +272,7 @@ impl Project {
             root,
             config,
             config_path: config_path.into_owned(),
+            git_root: git_root.to_path_buf(),
             idx: 0,
             relative_path: PathBuf::new(),
             repos: Vec::with_capacity(size),
@@ -129,7 +280,7 @@ impl Project {
     }
 
     /// Find the configuration file in the given path.
-    pub(crate) fn from_directory(path: &Path) -> Result<Self, config::Error> {
+    pub(crate) fn from_directory(path: &Path, git_root: &Path) -> Result<Self, config::Error> {
         let main = path.join(CONFIG_FILE);
         let alternate = path.join(ALT_CONFIG_FILE);
         let main_exists = main.is_file();
@@ -143,30 +294,33 @@ impl Project {
             );
         }
         if main_exists {
-            return Self::from_config_file(main.into(), None);
+            return Self::from_config_file(main.into(), None, git_root);
         }
         if alternate_exists {
-            return Self::from_config_file(alternate.into(), None);
+            return Self::from_config_file(alternate.into(), None, git_root);
         }
 
         Err(config::Error::NotFound(main.user_display().to_string()))
     }
 
     /// Discover a project from the give path or search from the given path to the git root.
-    pub(crate) fn discover(config_file: Option<&Path>, dir: &Path) -> Result<Project, Error> {
-        let git_root = GIT_ROOT.as_ref().map_err(|e| Error::Git(e.into()))?;
-
+    pub(crate) fn discover(
+        git_root: &Path,
+        config_file: Option<&Path>,
+        dir: &Path,
+    ) -> Result<Project, Error> {
         if let Some(config) = config_file {
             return Ok(Project::from_config_file(
                 config.into(),
-                Some(git_root.clone()),
+                Some(git_root.to_path_buf()),
+                git_root,
             )?);
         }
 
-        let workspace_root = Workspace::find_root(None, dir)?;
+        let workspace_root = Workspace::find_root(git_root, None, dir)?;
         debug!("Found project root at {}", workspace_root.user_display());
 
-        Ok(Project::from_directory(&workspace_root)?)
+        Ok(Project::from_directory(&workspace_root, git_root)?)
     }
 
     pub(crate) fn with_relative_path(&mut self, relative_path: PathBuf) {
@@ -187,6 +341,11 @@ impl Project {
         &self.config_path
     }
 
+    /// The root of the git repository the project is in.
+    pub(crate) fn git_root(&self) -> &Path {
+        &self.git_root
+    }
+
     /// Get the path to the project directory.
     pub(crate) fn path(&self) -> &Path {
         &self.root
@@ -213,69 +372,46 @@ impl Project {
     }
 
     /// Initialize the project, cloning the repository and preparing hooks.
+    ///
+    /// The project must not be shared yet, the hooks keep a reference to it.
     pub(crate) async fn init_hooks(
-        &mut self,
+        self: &mut Arc<Self>,
         store: &Store,
         reporter: Option<&dyn HookInitReporter>,
     ) -> Result<Vec<Hook>, Error> {
-        self.init_repos(store, reporter).await?;
-        // TODO: avoid clone
-        let project = Arc::new(self.clone());
+        Arc::get_mut(self)
+            .expect("Project should not be shared before initializing its hooks")
+            .init_repos(store, reporter)
+            .await?;
 
-        let hooks = project.internal_init_hooks().await?;
+        let hooks = Arc::clone(self).internal_init_hooks().await?;
 
         Ok(hooks)
     }
 
     /// Initialize remote repositories for the project.
-    #[allow(clippy::mutable_key_type)]
     async fn init_repos(
         &mut self,
         store: &Store,
         reporter: Option<&dyn HookInitReporter>,
     ) -> Result<(), Error> {
-        let remote_repos = Mutex::new(FxHashMap::default());
-
-        let mut seen = FxHashSet::default();
-
-        // Prepare remote repos in parallel.
-        let remotes_iter = self.config.repos.iter().filter_map(|repo| match repo {
-            // Deduplicate remote repos.
-            config::Repo::Remote(repo) if seen.insert(repo) => Some(repo),
-            _ => None,
-        });
-
-        let mut tasks =
-            futures::stream::iter(remotes_iter)
-                .map(async |repo_config| {
-                    let path = store.clone_repo(repo_config, reporter).await.map_err(|e| {
-                        Error::Store {
-                            repo: repo_config.repo.to_string(),
-                            error: Box::new(e),
-                        }
-                    })?;
-
-                    let repo = Arc::new(Repo::remote(
-                        repo_config.repo.clone(),
-                        repo_config.rev.clone(),
-                        path,
-                    )?);
-                    remote_repos
-                        .lock()
-                        .unwrap()
-                        .insert(repo_config, repo.clone());
-
-                    Ok::<(), Error>(())
-                })
-                .buffer_unordered(5);
+        let repos = {
+            let remote_repos = store
+                .clone_repos(self.config.remote_repos(), *CLONE_CONCURRENCY, reporter)
+                .await?;
+            self.resolve_repos(&remote_repos)?
+        };
 
-        while let Some(result) = tasks.next().await {
-            result?;
-        }
+        self.repos = repos;
 
-        drop(tasks);
+        Ok(())
+    }
 
-        let remote_repos = remote_repos.into_inner().unwrap();
+    /// Construct the repos of the project, looking up remote repos in the cloned `remote_repos`.
+    fn resolve_repos(
+        &self,
+        remote_repos: &FxHashMap<&config::RemoteRepo, Arc<Repo>>,
+    ) -> Result<Vec<Arc<Repo>>, Error> {
         let mut repos = Vec::with_capacity(self.config.repos.len());
 
         for repo in &self.config.repos {
@@ -284,6 +420,10 @@ impl Project {
                     let repo = remote_repos.get(repo).expect("repo not found");
                     repos.push(repo.clone());
                 }
+                config::Repo::Path(repo) => {
+                    let repo = Repo::from_path(self.resolve_repo_path(&repo.path)?)?;
+                    repos.push(Arc::new(repo));
+                }
                 config::Repo::Local(repo) => {
                     let repo = Repo::local(repo.hooks.clone());
                     repos.push(Arc::new(repo));
@@ -295,9 +435,24 @@ impl Project {
             }
         }
 
-        self.repos = repos;
+        Ok(repos)
+    }
 
-        Ok(())
+    /// Resolve the path of a path repo against the project root.
+    ///
+    /// The resolved path must be inside the git repository.
+    fn resolve_repo_path(&self, path: &Path) -> Result<PathBuf, Error> {
+        let path = self.root.join(path).clean();
+
+        // Compare canonical paths to be robust against symlinked directories.
+        let canonical = dunce::canonicalize(&path).unwrap_or_else(|_| path.clone());
+        let canonical_root =
+            dunce::canonicalize(&self.git_root).unwrap_or_else(|_| self.git_root.clone());
+        if !canonical.starts_with(&canonical_root) {
+            return Err(Error::RepoPathOutsideGitRoot { path });
+        }
+
+        Ok(path)
     }
 
     /// Load and prepare hooks for the project.
@@ -306,8 +461,15 @@ impl Project {
 
         for (repo_config, repo) in zip_eq(self.config.repos.iter(), self.repos.iter()) {
             match repo_config {
-                config::Repo::Remote(repo_config) => {
-                    for hook_config in &repo_config.hooks {
+                config::Repo::Remote(config::RemoteRepo {
+                    hooks: hook_configs,
+                    ..
+                })
+                | config::Repo::Path(config::PathRepo {
+                    hooks: hook_configs,
+                    ..
+                }) => {
+                    for hook_config in hook_configs {
                         // Check hook id is valid.
                         let Some(hook) = repo.get_hook(&hook_config.id) else {
                             return Err(Error::HookNotFound {
@@ -331,6 +493,7 @@ impl Project {
                         let repo = Arc::clone(repo);
                         let mut builder =
                             HookBuilder::new(self.clone(), repo, hook_config.clone(), hooks.len());
+                        builder.inherit(repo_config);
                         builder.combine(&self.config);
 
                         let hook = builder.build().await?;
@@ -378,15 +541,26 @@ struct WorkspaceCache {
     created_at: SystemTime,
     /// Configuration files with their metadata
     config_files: Vec<CachedConfigFile>,
+    /// Last modification time of the workspace manifest, if any
+    manifest_modified: Option<SystemTime>,
+    /// Whether symlinks were followed because of `--follow-symlinks`
+    #[serde(default)]
+    follow_symlinks: bool,
+    /// Whether directories ignored by git were searched because of `--include-ignored`
+    #[serde(default)]
+    include_ignored: bool,
+    /// Last modification time of the `.prekignore` file at the workspace root, if any
+    #[serde(default)]
+    prekignore_modified: Option<SystemTime>,
 }
 
 impl WorkspaceCache {
-    const CURRENT_VERSION: u32 = 1;
+    const CURRENT_VERSION: u32 = 2;
     /// Maximum cache age before forcing rediscovery (1 hour)
     const MAX_CACHE_AGE: u64 = 60 * 60;
 
     /// Create a new cache from workspace discovery results
-    fn new(workspace_root: PathBuf, projects: &[Arc<Project>]) -> Self {
+    fn new(workspace_root: PathBuf, git_root: &Path, projects: &[Arc<Project>]) -> Self {
         let mut config_files = Vec::new();
 
         for project in projects {
@@ -402,13 +576,31 @@ impl WorkspaceCache {
         Self {
             version: Self::CURRENT_VERSION,
             created_at: SystemTime::now(),
-            workspace_root,
             config_files,
+            manifest_modified: Self::manifest_modified(git_root),
+            follow_symlinks: follow_symlinks(),
+            include_ignored: include_ignored(),
+            prekignore_modified: Self::prekignore_modified(&workspace_root),
+            workspace_root,
         }
     }
 
+    /// Last modification time of the workspace manifest at the git root.
+    fn manifest_modified(git_root: &Path) -> Option<SystemTime> {
+        std::fs::metadata(git_root.join(WORKSPACE_MANIFEST_FILE))
+            .and_then(|metadata| metadata.modified())
+            .ok()
+    }
+
+    /// Last modification time of the `.prekignore` file at the workspace root.
+    fn prekignore_modified(workspace_root: &Path) -> Option<SystemTime> {
+        std::fs::metadata(workspace_root.join(PREKIGNORE_FILE))
+            .and_then(|metadata| metadata.modified())
+            .ok()
+    }
+
     /// Check if the cache is still valid
-    fn is_valid(&self) -> bool {
+    fn is_valid(&self, git_root: &Path) -> bool {
         // Check cache age - invalidate if older than MAX_CACHE_AGE
         if let Ok(elapsed) = self.created_at.elapsed() {
             if elapsed.as_secs() > Self::MAX_CACHE_AGE {
@@ -443,6 +635,27 @@ impl WorkspaceCache {
             }
         }
 
+        // The manifest decides which directories are walked
+        if self.manifest_modified != Self::manifest_modified(git_root) {
+            debug!("Workspace manifest changed, invalidating cache");
+            return false;
+        }
+
+        if self.prekignore_modified != Self::prekignore_modified(&self.workspace_root) {
+            debug!("`{PREKIGNORE_FILE}` changed, invalidating cache");
+            return false;
+        }
+
+        if self.follow_symlinks != follow_symlinks() {
+            debug!("`--follow-symlinks` changed, invalidating cache");
+            return false;
+        }
+
+        if self.include_ignored != include_ignored() {
+            debug!("`--include-ignored` changed, invalidating cache");
+            return false;
+        }
+
         // Check if workspace root still exists
         if !self.workspace_root.exists() {
             debug!("Workspace root no longer exists, invalidating cache");
@@ -474,7 +687,7 @@ impl WorkspaceCache {
     }
 
     /// Load cache from file
-    fn load(workspace_root: &Path, refresh: bool) -> Option<Self> {
+    fn load(workspace_root: &Path, git_root: &Path, refresh: bool) -> Option<Self> {
         if refresh {
             return None;
         }
@@ -487,7 +700,7 @@ impl WorkspaceCache {
         match std::fs::read_to_string(&cache_path) {
             Ok(content) => match serde_json::from_str::<Self>(&content) {
                 Ok(cache) => {
-                    if cache.version == Self::CURRENT_VERSION && cache.is_valid() {
+                    if cache.version == Self::CURRENT_VERSION && cache.is_valid(git_root) {
                         Some(cache)
                     } else {
                         // Invalid cache, remove it
@@ -525,20 +738,109 @@ impl WorkspaceCache {
     }
 }
 
+/// The order in which the projects of a workspace are run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub(crate) enum WorkspaceOrder {
+    /// Run nested projects before their parents, the workspace root last.
+    #[default]
+    Deepest,
+    /// Run the workspace root first, nested projects after their parents.
+    Root,
+}
+
+/// The order of the projects, set by `--workspace-order` or `PREK_WORKSPACE_ORDER`.
+static WORKSPACE_ORDER: OnceLock<WorkspaceOrder> = OnceLock::new();
+
+/// Set the order in which the projects of a workspace are run.
+pub(crate) fn set_workspace_order(order: WorkspaceOrder) {
+    WORKSPACE_ORDER.get_or_init(|| order);
+}
+
+/// Whether symlinked directories are followed, set by `--follow-symlinks`.
+static FOLLOW_SYMLINKS: AtomicBool = AtomicBool::new(false);
+
+/// Follow symlinked directories when discovering projects.
+pub(crate) fn set_follow_symlinks() {
+    FOLLOW_SYMLINKS.store(true, Ordering::Relaxed);
+}
+
+fn follow_symlinks() -> bool {
+    FOLLOW_SYMLINKS.load(Ordering::Relaxed) || EnvVars::is_set(EnvVars::PREK_FOLLOW_SYMLINKS)
+}
+
+/// Whether directories ignored by git are searched, set by `--include-ignored`.
+static INCLUDE_IGNORED: AtomicBool = AtomicBool::new(false);
+
+/// Also discover projects in directories ignored by git.
+pub(crate) fn set_include_ignored() {
+    INCLUDE_IGNORED.store(true, Ordering::Relaxed);
+}
+
+fn include_ignored() -> bool {
+    INCLUDE_IGNORED.load(Ordering::Relaxed) || EnvVars::is_set(EnvVars::PREK_INCLUDE_IGNORED)
+}
+
+/// Whether projects with an invalid config are skipped, set by `--skip-invalid-configs`.
+static SKIP_INVALID_CONFIGS: AtomicBool = AtomicBool::new(false);
+
+/// Skip the projects whose config fails to parse, instead of failing.
+pub(crate) fn set_skip_invalid_configs() {
+    SKIP_INVALID_CONFIGS.store(true, Ordering::Relaxed);
+}
+
+fn skip_invalid_configs() -> bool {
+    SKIP_INVALID_CONFIGS.load(Ordering::Relaxed)
+        || EnvVars::is_set(EnvVars::PREK_SKIP_INVALID_CONFIGS)
+}
+
+/// Statistics of a workspace discovery, to understand its cost in large repositories.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct DiscoveryStats {
+    /// The number of directories walked, zero when the projects were loaded from the cache.
+    pub(crate) directories_walked: usize,
+    /// The number of config files found.
+    pub(crate) configs_found: usize,
+    /// The number of config files left out because they failed to parse.
+    pub(crate) configs_skipped: usize,
+    /// Whether the projects were loaded from the cache.
+    pub(crate) cached: bool,
+    pub(crate) elapsed: Duration,
+}
+
+impl Display for DiscoveryStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "found {} configs ({} skipped) in {:.2?}, walked {} directories",
+            self.configs_found, self.configs_skipped, self.elapsed, self.directories_walked
+        )?;
+        if self.cached {
+            write!(f, " (cached)")?;
+        }
+        Ok(())
+    }
+}
+
 pub(crate) struct Workspace {
     root: PathBuf,
+    git_root: PathBuf,
     projects: Vec<Arc<Project>>,
+    /// The relative paths of all discovered projects, including the ones not selected.
+    project_paths: Vec<PathBuf>,
+    stats: DiscoveryStats,
 }
 
 impl Workspace {
-    /// Find the workspace root.
+    /// Find the workspace root in the git repository at `git_root`.
     /// `dir` must be an absolute path.
-    pub(crate) fn find_root(config_file: Option<&Path>, dir: &Path) -> Result<PathBuf, Error> {
-        let git_root = GIT_ROOT.as_ref().map_err(|e| Error::Git(e.into()))?;
-
+    pub(crate) fn find_root(
+        git_root: &Path,
+        config_file: Option<&Path>,
+        dir: &Path,
+    ) -> Result<PathBuf, Error> {
         if config_file.is_some() {
             // For `--config <path>`, the workspace root is the git root.
-            return Ok(git_root.clone());
+            return Ok(git_root.to_path_buf());
         }
 
         // Walk from the given path up to the git root, to find the workspace root.
@@ -556,27 +858,36 @@ impl Workspace {
     /// Discover the workspace from the given workspace root.
     #[instrument(level = "trace", skip(selectors))]
     pub(crate) fn discover(
+        git_root: &Path,
         root: PathBuf,
         config: Option<PathBuf>,
         selectors: Option<&Selectors>,
         refresh: bool,
     ) -> Result<Self, Error> {
+        let start = Instant::now();
+        let mut stats = DiscoveryStats::default();
+
         if let Some(config) = config {
-            let project = Project::from_config_file(config.into(), Some(root.clone()))?;
+            let project = Project::from_config_file(config.into(), Some(root.clone()), git_root)?;
+            stats.configs_found = 1;
+            stats.elapsed = start.elapsed();
             return Ok(Self {
                 root,
+                git_root: git_root.to_path_buf(),
                 projects: vec![Arc::new(project)],
+                project_paths: vec![],
+                stats,
             });
         }
 
         // Try to load from cache first
-        let projects = if let Some(cache) = WorkspaceCache::load(&root, refresh) {
+        let projects = if let Some(cache) = WorkspaceCache::load(&root, git_root, refresh) {
             debug!("Loaded workspace from cache");
             let projects: Result<Vec<_>, _> = cache
                 .config_files
                 .into_iter()
-                .map(
-                    |config_file| match Project::from_config_file(config_file.path.into(), None) {
+                .map(|config_file| {
+                    match Project::from_config_file(config_file.path.into(), None, git_root) {
                         Ok(mut project) => {
                             let relative_path = project
                                 .config_file()
@@ -591,8 +902,8 @@ impl Workspace {
                             debug!("Failed to load cached project config: {}", e);
                             Err(e)
                         }
-                    },
-                )
+                    }
+                })
                 .collect();
 
             match projects {
@@ -607,35 +918,103 @@ impl Workspace {
         };
 
         let mut projects = if let Some(projects) = projects {
+            stats.cached = true;
             projects
         } else {
             // Cache miss or invalid, perform fresh discovery
             debug!("Performing fresh workspace discovery");
-            let projects = Self::discover_fresh(&root)?;
-
-            // Save to cache
-            let cache = WorkspaceCache::new(root.clone(), &projects);
-            if let Err(e) = cache.save() {
-                debug!("Failed to save workspace cache: {}", e);
+            let filter = ProjectFilter::load(git_root)?;
+            let projects = Self::discover_fresh(&root, git_root, filter.as_ref(), &mut stats)?;
+
+            // Save to cache, unless invalid configs were skipped: the cache would hide them
+            // from the next run.
+            if stats.configs_skipped == 0 {
+                let cache = WorkspaceCache::new(root.clone(), git_root, &projects);
+                if let Err(e) = cache.save() {
+                    debug!("Failed to save workspace cache: {}", e);
+                }
             }
             projects
         };
 
+        Self::inherit_defaults(&mut projects);
+
+        let project_paths: Vec<_> = projects
+            .iter()
+            .map(|project| project.relative_path().to_path_buf())
+            .collect();
         if let Some(selectors) = selectors {
             projects.retain(|p| selectors.matches_path(p.relative_path()));
         }
-        let mut workspace = Self { root, projects };
+        stats.configs_found = project_paths.len();
+        stats.elapsed = start.elapsed();
+
+        let mut workspace = Self {
+            root,
+            git_root: git_root.to_path_buf(),
+            projects,
+            project_paths,
+            stats,
+        };
         workspace.sort_and_index_projects();
+        debug!("Workspace discovery: {}", workspace.stats());
 
         Ok(workspace)
     }
 
-    /// Perform fresh workspace discovery without cache
-    fn discover_fresh(root: &Path) -> Result<Vec<Arc<Project>>, Error> {
-        let projects = Mutex::new(Ok(Vec::new()));
+    /// Merge the defaults of the closest parent project into the projects with `inherit: true`.
+    ///
+    /// Parents are handled first, so inherited defaults cascade down through nested projects.
+    fn inherit_defaults(projects: &mut [Arc<Project>]) {
+        let mut order: Vec<_> = (0..projects.len()).collect();
+        order.sort_by_key(|&i| projects[i].depth());
+
+        for i in order {
+            if projects[i].config.inherit != Some(true) {
+                continue;
+            }
+            let parent = projects
+                .iter()
+                .filter(|parent| {
+                    parent.depth() < projects[i].depth()
+                        && projects[i]
+                            .relative_path()
+                            .starts_with(parent.relative_path())
+                })
+                .max_by_key(|parent| parent.depth())
+                .map(|parent| Arc::clone(&parent.config));
+            let Some(parent) = parent else {
+                continue;
+            };
+
+            let project =
+                Arc::get_mut(&mut projects[i]).expect("projects are not shared during discovery");
+            Arc::make_mut(&mut project.config).inherit_from(&parent);
+        }
+    }
 
+    /// Perform fresh workspace discovery without cache
+    fn discover_fresh(
+        root: &Path,
+        git_root: &Path,
+        filter: Option<&ProjectFilter>,
+        stats: &mut DiscoveryStats,
+    ) -> Result<Vec<Arc<Project>>, Error> {
+        let projects = Mutex::new(Vec::new());
+        // All invalid configs are reported at once, rather than one per run.
+        let errors = Mutex::new(Vec::new());
+        let directories_walked = AtomicUsize::new(0);
+        let follow_symlinks =
+            follow_symlinks() || filter.is_some_and(|filter| filter.follow_symlinks);
+        let prekignore = Self::load_prekignore(root);
+
+        // The walk only starts below the root, so the root is never skipped even if ignored.
+        let respect_gitignore = !include_ignored();
         ignore::WalkBuilder::new(root)
-            .follow_links(false)
+            .follow_links(follow_symlinks)
+            .git_ignore(respect_gitignore)
+            .git_exclude(respect_gitignore)
+            .git_global(respect_gitignore)
             .build_parallel()
             .run(|| {
                 Box::new(|result| {
@@ -648,9 +1027,35 @@ impl Workspace {
                     if !file_type.is_dir() {
                         return WalkState::Continue;
                     }
+                    // Never descend into git directories, even if hidden directories are walked,
+                    // configs in there belong to other repositories.
+                    if entry.file_name() == ".git" {
+                        return WalkState::Skip;
+                    }
+                    directories_walked.fetch_add(1, Ordering::Relaxed);
+                    if entry.depth() > 0 {
+                        if prekignore.matched(entry.path(), true).is_ignore() {
+                            return WalkState::Skip;
+                        }
+                        if let Some(filter) = filter {
+                            if filter.is_excluded(entry.path()) {
+                                return WalkState::Skip;
+                            }
+                            if !filter.is_included(entry.path()) {
+                                return WalkState::Continue;
+                            }
+                        }
+                    }
 
-                    match Project::from_directory(entry.path()) {
+                    match Project::from_directory(entry.path(), git_root) {
                         Ok(mut project) => {
+                            // The project owns its whole subtree.
+                            let state = if project.config().nested == Some(false) {
+                                WalkState::Skip
+                            } else {
+                                WalkState::Continue
+                            };
+
                             let relative_path = entry
                                 .into_path()
                                 .strip_prefix(root)
@@ -658,40 +1063,142 @@ impl Workspace {
                                 .to_path_buf();
                             project.with_relative_path(relative_path);
 
-                            projects
-                                .lock()
-                                .unwrap()
-                                .as_mut()
-                                .unwrap()
-                                .push(Arc::new(project));
+                            projects.lock().unwrap().push(Arc::new(project));
+
+                            return state;
                         }
                         Err(config::Error::NotFound(_)) => {}
-                        Err(e) => {
-                            *projects.lock().unwrap() = Err(e);
-                            return WalkState::Quit;
-                        }
+                        Err(e) => errors.lock().unwrap().push(e),
                     }
 
                     WalkState::Continue
                 })
             });
 
-        let projects = projects.into_inner().unwrap()?;
+        stats.directories_walked = directories_walked.into_inner();
+        let mut errors = errors.into_inner().unwrap();
+        let mut projects = projects.into_inner().unwrap();
+
+        // With `--skip-invalid-configs`, the projects with an invalid config are left out,
+        // unless there is no valid project left to run.
+        if !errors.is_empty() && !projects.is_empty() && skip_invalid_configs() {
+            let mut lines = errors.iter().map(config_error_line).collect::<Vec<_>>();
+            lines.sort_unstable();
+            for line in lines {
+                warn_user!("Skipping project with an invalid config: {line}");
+            }
+            stats.configs_skipped = errors.len();
+            errors.clear();
+        }
+
+        if errors.len() == 1 {
+            return Err(errors.remove(0).into());
+        }
+        if !errors.is_empty() {
+            return Err(Error::InvalidConfigs { errors });
+        }
+
         debug_assert!(!projects.is_empty(), "At least one project should be found");
 
+        Self::dedup_projects(root, &mut projects);
+
         Ok(projects)
     }
 
+    /// Load the `.prekignore` file at the workspace root, the directories it ignores are never walked.
+    fn load_prekignore(root: &Path) -> Gitignore {
+        let path = root.join(PREKIGNORE_FILE);
+        if !path.is_file() {
+            return Gitignore::empty();
+        }
+        let (prekignore, err) = Gitignore::new(path);
+        if let Some(err) = err {
+            warn_user!("Failed to read `{PREKIGNORE_FILE}`: {err}");
+        }
+        prekignore
+    }
+
+    /// Find the config files of the projects under the workspace root, without reading them.
+    pub(crate) fn find_configs(root: &Path) -> Vec<PathBuf> {
+        let prekignore = Self::load_prekignore(root);
+        let respect_gitignore = !include_ignored();
+        let mut configs: Vec<_> = ignore::WalkBuilder::new(root)
+            .git_ignore(respect_gitignore)
+            .git_exclude(respect_gitignore)
+            .git_global(respect_gitignore)
+            .filter_entry(move |entry| {
+                entry.depth() == 0
+                    || !entry
+                        .file_type()
+                        .is_some_and(|file_type| file_type.is_dir())
+                    || !prekignore.matched(entry.path(), true).is_ignore()
+            })
+            .build()
+            .filter_map(Result::ok)
+            .filter(|entry| {
+                entry
+                    .file_type()
+                    .is_some_and(|file_type| file_type.is_dir())
+            })
+            .filter_map(|entry| {
+                [CONFIG_FILE, ALT_CONFIG_FILE]
+                    .into_iter()
+                    .map(|file| entry.path().join(file))
+                    .find(|path| path.is_file())
+            })
+            .collect();
+        configs.sort();
+        configs
+    }
+
+    /// Keep a single project for each project directory reached through more than one path,
+    /// like by following symlinks, preferring the path without symlinks.
+    ///
+    /// Projects are identified by their config path, so duplicates would run twice. Only the
+    /// directory is canonicalized: directories sharing a symlinked config file are distinct projects.
+    fn dedup_projects(root: &Path, projects: &mut Vec<Arc<Project>>) {
+        let canonical_root = dunce::canonicalize(root).unwrap_or_else(|_| root.to_path_buf());
+        let mut keyed: Vec<_> = projects
+            .drain(..)
+            .map(|project| {
+                let dir = dunce::canonicalize(project.path())
+                    .unwrap_or_else(|_| project.path().to_path_buf());
+                let through_symlink = dir != canonical_root.join(project.relative_path());
+                let config_path = dir.join(project.config_path.file_name().unwrap_or_default());
+                ((config_path, through_symlink), project)
+            })
+            .collect();
+        keyed.sort_by(|(a, a_project), (b, b_project)| {
+            a.cmp(b)
+                .then_with(|| a_project.depth().cmp(&b_project.depth()))
+                .then_with(|| a_project.relative_path().cmp(b_project.relative_path()))
+        });
+        keyed.dedup_by(|(key, project), (kept, kept_project)| {
+            let duplicate = key.0 == kept.0;
+            if duplicate {
+                debug!(
+                    "Skipping `{}`, it is the same project as `{}`",
+                    project.relative_path().display(),
+                    kept_project.relative_path().display()
+                );
+            }
+            duplicate
+        });
+        projects.extend(keyed.into_iter().map(|(_, project)| project));
+    }
+
     /// Sort projects by depth and assign indices
     fn sort_and_index_projects(&mut self) {
         // Sort projects by their depth in the directory tree.
-        // The deeper the project comes first.
-        // This is useful for nested projects where we want to prefer the most specific project.
+        // By default the deeper the project comes first, `--workspace-order root` reverses it.
+        let order = WORKSPACE_ORDER.get().copied().unwrap_or_default();
         self.projects.sort_by(|a, b| {
-            b.depth()
-                .cmp(&a.depth())
-                // If depth is the same, sort by relative path to have a deterministic order.
-                .then_with(|| a.relative_path.cmp(&b.relative_path))
+            match order {
+                WorkspaceOrder::Deepest => b.depth().cmp(&a.depth()),
+                WorkspaceOrder::Root => a.depth().cmp(&b.depth()),
+            }
+            // If depth is the same, sort by relative path to have a deterministic order.
+            .then_with(|| a.relative_path.cmp(&b.relative_path))
         });
 
         // Assign index to each project.
@@ -704,87 +1211,107 @@ impl Workspace {
         &self.root
     }
 
+    /// The root of the git repository the workspace is in.
+    pub(crate) fn git_root(&self) -> &Path {
+        &self.git_root
+    }
+
     pub(crate) fn projects(&self) -> &[Arc<Project>] {
         &self.projects
     }
 
+    /// Only keep the projects at the given paths, relative to the workspace root.
+    ///
+    /// Projects that were already excluded by selectors stay excluded.
+    pub(crate) fn retain_projects(&mut self, paths: &[String]) -> Result<(), Error> {
+        let paths = paths
+            .iter()
+            .map(|path| Path::new(path).clean())
+            // `.` is the project at the workspace root.
+            .map(|path| {
+                if path == Path::new(".") {
+                    PathBuf::new()
+                } else {
+                    path
+                }
+            })
+            .collect::<Vec<_>>();
+
+        // A workspace from `--config` has a single project and no discovered paths.
+        let mut available = if self.project_paths.is_empty() {
+            self.projects
+                .iter()
+                .map(|project| project.relative_path().to_path_buf())
+                .collect()
+        } else {
+            self.project_paths.clone()
+        };
+        if let Some(path) = paths.iter().find(|path| !available.contains(path)) {
+            available.sort();
+            let available = available
+                .iter()
+                .map(|path| {
+                    if path.as_os_str().is_empty() {
+                        "`.`".to_string()
+                    } else {
+                        format!("`{}`", path.display())
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            return Err(Error::ProjectNotFound {
+                path: path.clone(),
+                available,
+            });
+        }
+
+        self.projects
+            .retain(|project| paths.iter().any(|path| project.relative_path() == path));
+        Ok(())
+    }
+
+    /// Statistics of the discovery of this workspace.
+    pub(crate) fn stats(&self) -> &DiscoveryStats {
+        &self.stats
+    }
+
+    /// The paths of the projects nested inside the given project, relative to the workspace root.
+    ///
+    /// Projects that were not selected are included, as they still own their files.
+    pub(crate) fn nested_projects(&self, project: &Project) -> Vec<&Path> {
+        self.project_paths
+            .iter()
+            .map(PathBuf::as_path)
+            .filter(|nested| {
+                *nested != project.relative_path() && nested.starts_with(project.relative_path())
+            })
+            .collect()
+    }
+
     /// Initialize remote repositories for all projects.
     async fn init_repos(
         &mut self,
         store: &Store,
         reporter: Option<&dyn HookInitReporter>,
     ) -> Result<(), Error> {
-        #[allow(clippy::mutable_key_type)]
-        let remote_repos = {
-            let remote_repos = Mutex::new(FxHashMap::default());
-
-            let mut seen = FxHashSet::default();
+        let project_repos = {
+            let remote_repos = store
+                .clone_repos(
+                    self.projects
+                        .iter()
+                        .flat_map(|project| project.config.remote_repos()),
+                    *CLONE_CONCURRENCY,
+                    reporter,
+                )
+                .await?;
 
-            // Prepare remote repos in parallel.
-            let remotes_iter = self
-                .projects
+            self.projects
                 .iter()
-                .flat_map(|proj| proj.config.repos.iter())
-                .filter_map(|repo| match repo {
-                    // Deduplicate remote repos.
-                    config::Repo::Remote(repo) if seen.insert(repo) => Some(repo),
-                    _ => None,
-                })
-                .cloned(); // TODO: avoid clone
-
-            let mut tasks = futures::stream::iter(remotes_iter)
-                .map(async |repo_config| {
-                    let path = store
-                        .clone_repo(&repo_config, reporter)
-                        .await
-                        .map_err(|e| Error::Store {
-                            repo: repo_config.repo.to_string(),
-                            error: Box::new(e),
-                        })?;
-
-                    let repo = Arc::new(Repo::remote(
-                        repo_config.repo.clone(),
-                        repo_config.rev.clone(),
-                        path,
-                    )?);
-                    remote_repos
-                        .lock()
-                        .unwrap()
-                        .insert(repo_config, repo.clone());
-
-                    Ok::<(), Error>(())
-                })
-                .buffer_unordered(5);
-
-            while let Some(result) = tasks.next().await {
-                result?;
-            }
-
-            drop(tasks);
-
-            remote_repos.into_inner().unwrap()
+                .map(|project| project.resolve_repos(&remote_repos))
+                .collect::<Result<Vec<_>, _>>()?
         };
 
-        for project in &mut self.projects {
-            let mut repos = Vec::with_capacity(project.config.repos.len());
-
-            for repo in &project.config.repos {
-                match repo {
-                    config::Repo::Remote(repo) => {
-                        let repo = remote_repos.get(repo).expect("repo not found");
-                        repos.push(repo.clone());
-                    }
-                    config::Repo::Local(repo) => {
-                        let repo = Repo::local(repo.hooks.clone());
-                        repos.push(Arc::new(repo));
-                    }
-                    config::Repo::Meta(repo) => {
-                        let repo = Repo::meta(repo.hooks.clone());
-                        repos.push(Arc::new(repo));
-                    }
-                }
-            }
-
+        for (project, repos) in self.projects.iter_mut().zip(project_repos) {
             Arc::get_mut(project).unwrap().repos = repos;
         }
 
@@ -811,33 +1338,50 @@ impl Workspace {
     }
 
     /// Check if all configuration files are staged in git.
-    pub(crate) async fn check_configs_staged(&self) -> Result<()> {
+    ///
+    /// With `allow_unstaged`, unstaged files are reported as a warning instead of an error.
+    pub(crate) async fn check_configs_staged(&self, allow_unstaged: bool) -> Result<()> {
+        // A configuration read from stdin has no file to stage.
         let config_files = self
             .projects
             .iter()
             .map(|project| project.config_file())
+            .filter(|path| !config::is_stdin_config(path))
             .collect::<Vec<_>>();
+        if config_files.is_empty() {
+            return Ok(());
+        }
         let non_staged = git::files_not_staged(&config_files).await?;
 
-        let git_root = GIT_ROOT.as_ref()?;
         if !non_staged.is_empty() {
             let non_staged = non_staged
                 .into_iter()
-                .map(|p| git_root.join(p))
+                .map(|p| self.git_root.join(p))
                 .collect::<Vec<_>>();
-            match non_staged.as_slice() {
-                [filename] => anyhow::bail!(
+            let list = non_staged
+                .iter()
+                .map(|p| format!("  {}", p.user_display()))
+                .collect::<Vec<_>>()
+                .join("\n");
+            match (non_staged.as_slice(), allow_unstaged) {
+                ([filename], false) => anyhow::bail!(
                     "prek configuration file is not staged, run `{}` to stage it",
                     format!("git add {}", filename.user_display()).cyan()
                 ),
-                _ => anyhow::bail!(
-                    "The following configuration files are not staged, `git add` them first:\n{}",
-                    non_staged
-                        .iter()
-                        .map(|p| format!("  {}", p.user_display()))
-                        .collect::<Vec<_>>()
-                        .join("\n")
+                (_, false) => anyhow::bail!(
+                    "The following configuration files are not staged, `git add` them first:\n{list}"
                 ),
+                ([filename], true) => {
+                    warn_user!(
+                        "prek configuration file `{}` is not staged, using its unstaged content",
+                        filename.user_display().cyan()
+                    );
+                }
+                (_, true) => {
+                    warn_user!(
+                        "The following configuration files are not staged, using their unstaged content:\n{list}"
+                    );
+                }
             }
         }
 
@@ -764,6 +764,96 @@ fn auto_update_workspace() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn auto_update_workspace_freeze_specific_repo() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    let repo1_path = create_local_git_repo(&context, "workspace-freeze1", &["v1.0.0", "v1.1.0"])?;
+    let repo2_path = create_local_git_repo(&context, "workspace-freeze2", &["v1.0.0", "v2.0.0"])?;
+
+    context.setup_workspace(&["project-a", "project-b"], "repos: []")?;
+
+    context
+        .work_dir()
+        .child("project-a/.pre-commit-config.yaml")
+        .write_str(&indoc::formatdoc! {r"
+        # Project A
+        repos:
+          - repo: {}
+            rev: v1.0.0  # pinned
+            hooks:
+              - id: test-hook
+          - repo: {}
+            rev: v1.0.0
+            hooks:
+              - id: test-hook
+    ", repo1_path, repo2_path})?;
+
+    context
+        .work_dir()
+        .child("project-b/.pre-commit-config.yaml")
+        .write_str(&indoc::formatdoc! {r"
+        repos:
+          # Only this repo
+          - repo: {}
+            rev: v1.0.0
+            hooks:
+              - id: another-hook
+    ", repo1_path})?;
+
+    context.git_add(".");
+
+    let filters = context
+        .filters()
+        .into_iter()
+        .chain([(r" [a-f0-9]{40}", r" [COMMIT_SHA]")])
+        .collect::<Vec<_>>();
+
+    cmd_snapshot!(filters.clone(), context.auto_update().arg("--freeze").arg("--repo").arg(&repo1_path), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    [[HOME]/test-repos/workspace-freeze1] updating v1.0.0 -> [COMMIT_SHA]
+
+    ----- stderr -----
+    ");
+
+    insta::with_settings!(
+        { filters => filters.clone() },
+        {
+            assert_snapshot!(context.read("project-a/.pre-commit-config.yaml"), @r"
+            # Project A
+            repos:
+              - repo: [HOME]/test-repos/workspace-freeze1
+                rev: [COMMIT_SHA]  # frozen: v1.1.0
+                hooks:
+                  - id: test-hook
+              - repo: [HOME]/test-repos/workspace-freeze2
+                rev: v1.0.0
+                hooks:
+                  - id: test-hook
+            ");
+        }
+    );
+
+    insta::with_settings!(
+        { filters => filters.clone() },
+        {
+            assert_snapshot!(context.read("project-b/.pre-commit-config.yaml"), @r"
+            repos:
+              # Only this repo
+              - repo: [HOME]/test-repos/workspace-freeze1
+                rev: [COMMIT_SHA]  # frozen: v1.1.0
+                hooks:
+                  - id: another-hook
+            ");
+        }
+    );
+
+    Ok(())
+}
+
 // When there are multiple tags pointing to the same object,
 // prek prefer picking a tag with a dot and is closest to the current rev according
 // to Levenshtein distance.
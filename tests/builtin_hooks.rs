@@ -651,6 +651,10 @@ fn builtin_hooks_workspace_mode() -> Result<()> {
       app/eof_multiple_lf.txt
       app/trailing_ws.txt
 
+
+    Summary: 3 passed, 5 failed, 0 skipped across 2 projects
+
+
     ----- stderr -----
     "#);
 
@@ -717,6 +721,10 @@ fn builtin_hooks_workspace_mode() -> Result<()> {
       app/eof_multiple_lf.txt
       app/trailing_ws.txt
 
+
+    Summary: 7 passed, 1 failed, 0 skipped across 2 projects
+
+
     ----- stderr -----
     ");
 
@@ -1,27 +1,163 @@
 use assert_fs::assert::PathAssert;
-use assert_fs::fixture::{PathChild, PathCreateDir};
+use assert_fs::fixture::{FileWriteStr, PathChild, PathCreateDir};
 
 use crate::common::{TestContext, cmd_snapshot};
 
 mod common;
 
+/// `prek clean` removes the whole store, after confirmation.
 #[test]
 fn clean() -> anyhow::Result<()> {
     let context = TestContext::new();
 
+    let home = context.work_dir().child("home");
+    home.child("repos/repo/.prek-repo.json").write_str("{}")?;
+    home.child("hooks/env/.prek-hook.json").write_str("{}")?;
+
+    let filters = context
+        .filters()
+        .into_iter()
+        .chain([(r"freed \d+(\.\d+)? \w+", "freed [SIZE]")])
+        .collect::<Vec<_>>();
+
+    // Without a terminal to confirm, `--yes` is required.
+    cmd_snapshot!(filters.clone(), context.clean().env("PREK_HOME", &*home), @r"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    error: Refusing to remove `home` without confirmation, pass `--yes` to remove it
+    ");
+    home.assert(predicates::path::exists());
+
+    cmd_snapshot!(filters.clone(), context.clean().arg("--yes").env("PREK_HOME", &*home), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    Cleaned `home`, freed [SIZE]
+
+    ----- stderr -----
+    ");
+    home.assert(predicates::path::missing());
+
+    Ok(())
+}
+
+/// `prek clean` refuses to remove a store that is the home directory.
+#[test]
+fn clean_home_dir() -> anyhow::Result<()> {
+    let context = TestContext::new();
+
     let home = context.work_dir().child("home");
     home.create_dir_all()?;
 
-    cmd_snapshot!(context.filters(), context.clean().env("PREK_HOME", &*home), @r#"
+    cmd_snapshot!(context.filters(), context.clean().arg("--yes").env("HOME", &*home).env("PREK_HOME", &*home), @r"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    error: Refusing to remove `[TEMP_DIR]/home`, it does not look like a prek cache directory
+    ");
+    home.assert(predicates::path::exists());
+
+    Ok(())
+}
+
+/// `prek gc` removes the cached repos and environments that the workspace doesn't use anymore.
+#[test]
+fn gc() -> anyhow::Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+    context.write_pre_commit_config(indoc::indoc! {r"
+        repos:
+          - repo: https://github.com/pre-commit/pre-commit-hooks
+            rev: v5.0.0
+            hooks:
+              - id: trailing-whitespace
+    "});
+
+    let home = context.home_dir();
+    let cache_repo = |dir: &str, repo: &str, rev: &str| -> anyhow::Result<()> {
+        home.child(format!("repos/{dir}/.prek-repo.json"))
+            .write_str(&format!(r#"{{"repo":"{repo}","rev":"{rev}"}}"#))?;
+        Ok(())
+    };
+    let install_env = |dir: &str, dependency: &str| -> anyhow::Result<()> {
+        home.child(format!("hooks/{dir}/.prek-hook.json"))
+            .write_str(&format!(
+                r#"{{"language":"python","language_version":"3.12.0","dependencies":["{dependency}"],"env_path":"","toolchain":"","extra":{{}}}}"#
+            ))?;
+        Ok(())
+    };
+    cache_repo(
+        "used",
+        "https://github.com/pre-commit/pre-commit-hooks",
+        "v5.0.0",
+    )?;
+    cache_repo(
+        "stale",
+        "https://github.com/pre-commit/pre-commit-hooks",
+        "v4.0.0",
+    )?;
+    cache_repo("other", "https://github.com/psf/black", "24.1.0")?;
+    install_env(
+        "used-env",
+        "https://github.com/pre-commit/pre-commit-hooks@v5.0.0",
+    )?;
+    install_env(
+        "stale-env",
+        "https://github.com/pre-commit/pre-commit-hooks@v4.0.0",
+    )?;
+    install_env("other-env", "https://github.com/psf/black@24.1.0")?;
+
+    let filters = context
+        .filters()
+        .into_iter()
+        .chain([(r"freed \d+(\.\d+)? \w+", "freed [SIZE]")])
+        .collect::<Vec<_>>();
+
+    // Only the older rev of a repo the workspace uses is removed.
+    cmd_snapshot!(filters.clone(), context.gc(), @r"
     success: true
     exit_code: 0
     ----- stdout -----
-    Cleaned `home`
+    Removed 1 repo and 1 environment, freed [SIZE]
 
     ----- stderr -----
-    "#);
+    ");
+    home.child("repos/stale")
+        .assert(predicates::path::missing());
+    home.child("hooks/stale-env")
+        .assert(predicates::path::missing());
+    home.child("repos/other").assert(predicates::path::exists());
 
-    home.assert(predicates::path::missing());
+    // With `--all`, repos of other workspaces are removed too.
+    cmd_snapshot!(filters.clone(), context.gc().arg("--all"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    Removed 1 repo and 1 environment, freed [SIZE]
+
+    ----- stderr -----
+    ");
+    home.child("repos/other")
+        .assert(predicates::path::missing());
+    home.child("hooks/other-env")
+        .assert(predicates::path::missing());
+    home.child("repos/used").assert(predicates::path::exists());
+    home.child("hooks/used-env")
+        .assert(predicates::path::exists());
+
+    cmd_snapshot!(filters, context.gc().arg("--all"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    Nothing to clean
+
+    ----- stderr -----
+    ");
 
     Ok(())
 }
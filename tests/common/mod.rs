@@ -143,6 +143,12 @@ impl TestContext {
         command
     }
 
+    pub fn gc(&self) -> Command {
+        let mut command = self.command();
+        command.arg("gc");
+        command
+    }
+
     pub fn validate_config(&self) -> Command {
         let mut command = self.command();
         command.arg("validate-config");
@@ -185,6 +191,12 @@ impl TestContext {
         command
     }
 
+    pub fn try_repo(&self) -> Command {
+        let mut command = self.command();
+        command.arg("try-repo");
+        command
+    }
+
     pub fn auto_update(&self) -> Command {
         let mut cmd = self.command();
         cmd.arg("auto-update");
@@ -330,6 +342,36 @@ impl TestContext {
 
         Ok(())
     }
+
+    /// Create a git repository containing the given `.pre-commit-hooks.yaml` manifest,
+    /// tagged as `v1.0.0`. Returns the path to the repository.
+    pub fn create_hook_repo(&self, name: &str, manifest: &str) -> anyhow::Result<ChildPath> {
+        let repo_dir = self.home_dir.child(format!("test-repos/{name}"));
+        repo_dir.create_dir_all()?;
+        repo_dir
+            .child(".pre-commit-hooks.yaml")
+            .write_str(manifest)?;
+
+        let git = |args: &[&str]| {
+            Command::new("git")
+                .args([
+                    "-c",
+                    "user.name=Prek Test",
+                    "-c",
+                    "user.email=test@prek.dev",
+                ])
+                .args(args)
+                .current_dir(&repo_dir)
+                .assert()
+                .success();
+        };
+        git(&["init", "--initial-branch=master"]);
+        git(&["add", "."]);
+        git(&["commit", "-m", "Initial commit"]);
+        git(&["tag", "v1.0.0"]);
+
+        Ok(repo_dir)
+    }
 }
 
 #[doc(hidden)] // Macro and test context only, don't use directly.
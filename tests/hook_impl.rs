@@ -251,6 +251,8 @@ fn workspace_hook_impl_root() -> anyhow::Result<()> {
     - hook id: test-hook
     - duration: [TIME]
       cwd: [TEMP_DIR]/
+
+    Summary: 3 passed, 0 failed, 0 skipped across 3 projects
     ");
 
     Ok(())
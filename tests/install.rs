@@ -49,6 +49,19 @@ fn install() -> anyhow::Result<()> {
         }
     );
 
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        let mode = context
+            .work_dir()
+            .child(".git/hooks/pre-commit")
+            .metadata()?
+            .permissions()
+            .mode();
+        assert_ne!(mode & 0o111, 0, "the hook script should be executable");
+    }
+
     // Install `pre-commit` and `post-commit` hook.
     context
         .work_dir()
@@ -459,6 +472,30 @@ fn init_template_dir() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Installing hooks outside of a git repository fails with a clear error.
+#[test]
+fn install_outside_git_repo() {
+    let context = TestContext::new();
+
+    cmd_snapshot!(context.filters(), context.install(), @r"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    error: Cannot install hooks outside of a git repository
+    ");
+
+    cmd_snapshot!(context.filters(), context.uninstall(), @r"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    error: Cannot uninstall hooks outside of a git repository
+    ");
+}
+
 #[test]
 fn workspace_install() -> anyhow::Result<()> {
     let context = TestContext::new();
@@ -837,3 +874,78 @@ fn workspace_init_template_dir() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+/// In a linked worktree, the workspace is discovered in the worktree, and hooks are installed
+/// into the hooks directory shared by all worktrees.
+#[test]
+fn worktree() -> anyhow::Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+    context.configure_git_author();
+    context.write_pre_commit_config(indoc! {r"
+        repos:
+          - repo: local
+            hooks:
+              - id: echo
+                name: echo
+                entry: echo
+                language: system
+                verbose: true
+    "});
+    context.git_add(".");
+    context.git_commit("Initial commit");
+
+    let worktree = context.home_dir().child("worktree");
+    std::process::Command::new("git")
+        .arg("worktree")
+        .arg("add")
+        .arg(&*worktree)
+        .current_dir(context.work_dir())
+        .assert()
+        .success();
+
+    worktree.child("sub/file.txt").write_str("Hello\n")?;
+    std::process::Command::new("git")
+        .arg("add")
+        .arg(".")
+        .current_dir(&*worktree)
+        .assert()
+        .success();
+
+    cmd_snapshot!(context.filters(), context.run().current_dir(worktree.child("sub")), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    echo.....................................................................Passed
+    - hook id: echo
+    - duration: [TIME]
+      sub/file.txt
+
+    ----- stderr -----
+    ");
+
+    // The configuration of the worktree must be staged in the worktree.
+    worktree
+        .child(CONFIG_FILE)
+        .write_str(&format!("{}fail_fast: true\n", context.read(CONFIG_FILE)))?;
+    cmd_snapshot!(context.filters(), context.run().current_dir(&*worktree), @r"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    error: prek configuration file is not staged, run `git add .pre-commit-config.yaml` to stage it
+    ");
+
+    cmd_snapshot!(context.filters(), context.install().current_dir(&*worktree), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    prek installed at `[TEMP_DIR]/.git/hooks/pre-commit`
+
+    ----- stderr -----
+    ");
+    assert!(context.work_dir().join(".git/hooks/pre-commit").is_file());
+
+    Ok(())
+}
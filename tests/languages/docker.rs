@@ -79,6 +79,10 @@ fn workspace_docker() -> anyhow::Result<()> {
       project1/.pre-commit-config.yaml .pre-commit-config.yaml project2/project2.txt project1/project1.txt
       project2/.pre-commit-config.yaml
 
+
+    Summary: 3 passed, 0 failed, 0 skipped across 3 projects
+
+
     ----- stderr -----
     ");
 
@@ -2,7 +2,7 @@ use anyhow::Result;
 use assert_cmd::Command;
 use assert_fs::fixture::{FileWriteStr, PathChild};
 
-use crate::common::{TestContext, cmd_snapshot};
+use crate::common::{TestContext, cmd_snapshot, remove_bin_from_path};
 
 #[test]
 fn docker_image() -> Result<()> {
@@ -79,3 +79,192 @@ fn docker_image() -> Result<()> {
     "#);
     Ok(())
 }
+
+/// The filenames are passed to the container relative to the mounted project directory.
+#[test]
+fn docker_image_filenames() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    let cwd = context.work_dir();
+    cwd.child("a.txt").write_str("a")?;
+    cwd.child("dir/b.txt").write_str("b")?;
+    cwd.child("c.md").write_str("c")?;
+
+    Command::new("docker")
+        .args(["pull", "docker.io/library/alpine:latest"])
+        .assert()
+        .success();
+
+    context.write_pre_commit_config(indoc::indoc! {r"
+        repos:
+          - repo: local
+            hooks:
+              - id: ls
+                name: List files
+                language: docker_image
+                entry: docker.io/library/alpine:latest ls -1
+                files: \.txt$
+                verbose: true
+    "});
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run(), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    List files...............................................................Passed
+    - hook id: ls
+    - duration: [TIME]
+      a.txt
+      dir/b.txt
+
+    ----- stderr -----
+    ");
+
+    Ok(())
+}
+
+/// Running a docker hook without docker installed fails with an actionable error.
+#[test]
+fn docker_not_installed() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    context.write_pre_commit_config(indoc::indoc! {r"
+        repos:
+          - repo: local
+            hooks:
+              - id: docker-image
+                name: docker-image
+                language: docker_image
+                entry: docker.io/library/alpine:latest echo
+    "});
+    context.git_add(".");
+
+    #[allow(clippy::disallowed_methods)]
+    let path = remove_bin_from_path("docker")?;
+
+    cmd_snapshot!(context.filters(), context.run().env("PATH", path), @r"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+    docker-image.............................................................
+    ----- stderr -----
+    error: Failed to run hook `docker-image`
+      caused by: The `docker` executable was not found in PATH, please install Docker (https://docs.docker.com/get-docker/) to run `docker` and `docker_image` hooks
+      caused by: cannot find binary path
+    ");
+
+    Ok(())
+}
+
+/// A hook that times out stops its container too, not only `docker run`.
+#[test]
+fn docker_image_timeout() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    Command::new("docker")
+        .args(["pull", "docker.io/library/alpine:latest"])
+        .assert()
+        .success();
+
+    context.write_pre_commit_config(indoc::indoc! {r"
+        repos:
+          - repo: local
+            hooks:
+              - id: docker-timeout
+                name: docker-timeout
+                language: docker_image
+                entry: docker.io/library/alpine:latest sleep 60
+                pass_filenames: false
+                always_run: true
+                timeout: 2
+    "});
+    context.git_add(".");
+
+    let start = std::time::Instant::now();
+    cmd_snapshot!(context.filters(), context.run(), @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+    docker-timeout...........................................................Failed
+    - hook id: docker-timeout
+    - timed out after [TIME]
+      timed out after [TIME]
+
+    ----- stderr -----
+    ");
+    assert!(start.elapsed() < std::time::Duration::from_secs(60));
+
+    // The container is killed in the background, give it some time.
+    let running = || -> Result<bool> {
+        let output = Command::new("docker")
+            .args([
+                "ps",
+                "--quiet",
+                "--filter",
+                "label=prek.hook.id=docker-timeout",
+            ])
+            .output()?;
+        Ok(!output.stdout.trim_ascii().is_empty())
+    };
+    for _ in 0..20 {
+        if !running()? {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(500));
+    }
+    assert!(!running()?);
+
+    Ok(())
+}
+
+/// The hook's `env`, `--env` and `--hook-env-passthrough` are set in the container.
+#[test]
+fn docker_image_env() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    Command::new("docker")
+        .args(["pull", "docker.io/library/alpine:latest"])
+        .assert()
+        .success();
+
+    context.write_pre_commit_config(indoc::indoc! {r#"
+        repos:
+          - repo: local
+            hooks:
+              - id: env
+                name: env
+                language: docker_image
+                entry: docker.io/library/alpine:latest sh -c 'echo "$GREETING $NAME $PREK_TEST_PASSED"'
+                env:
+                  GREETING: Hello
+                  NAME: hook
+                pass_filenames: false
+                always_run: true
+                verbose: true
+    "#});
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run()
+        .arg("--env")
+        .arg("NAME=prek")
+        .arg("--hook-env-passthrough")
+        .arg("PREK_TEST_PASSED")
+        .env("PREK_TEST_PASSED", "through"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    env......................................................................Passed
+    - hook id: env
+    - duration: [TIME]
+      Hello prek through
+
+    ----- stderr -----
+    ");
+
+    Ok(())
+}
@@ -0,0 +1,115 @@
+use assert_fs::fixture::{FileWriteStr, PathChild};
+
+use crate::common::{TestContext, cmd_snapshot};
+
+/// Requires the .NET SDK, enabled by the `dotnet` feature.
+#[test]
+fn dotnet_tool() -> anyhow::Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    let tool = context.work_dir().child("hello-tool");
+    tool.child(".pre-commit-hooks.yaml")
+        .write_str(indoc::indoc! {r"
+        - id: hello-dotnet
+          name: hello-dotnet
+          language: dotnet
+          entry: hello-dotnet
+          files: \.txt$
+    "})?;
+    tool.child("HelloTool.csproj").write_str(indoc::indoc! {r#"
+        <Project Sdk="Microsoft.NET.Sdk">
+          <PropertyGroup>
+            <OutputType>Exe</OutputType>
+            <TargetFramework>net8.0</TargetFramework>
+            <PackAsTool>true</PackAsTool>
+            <ToolCommandName>hello-dotnet</ToolCommandName>
+            <PackageId>HelloTool</PackageId>
+            <Version>1.0.0</Version>
+          </PropertyGroup>
+        </Project>
+    "#})?;
+    tool.child("Program.cs").write_str(
+        r#"System.Console.WriteLine($"Hello from dotnet: {string.Join(" ", args)}");"#,
+    )?;
+
+    context.write_pre_commit_config(indoc::indoc! {r"
+        repos:
+          - repo: ./hello-tool
+            hooks:
+              - id: hello-dotnet
+                verbose: true
+    "});
+    context.work_dir().child("file.txt").write_str("Hello")?;
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run(), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    hello-dotnet.............................................................Passed
+    - hook id: hello-dotnet
+    - duration: [TIME]
+      Hello from dotnet: file.txt
+
+    ----- stderr -----
+    ");
+
+    Ok(())
+}
+
+#[test]
+fn dotnet_language_version_not_found() {
+    let context = TestContext::new();
+    context.init_project();
+
+    context.write_pre_commit_config(indoc::indoc! {r"
+        repos:
+          - repo: local
+            hooks:
+              - id: local-dotnet
+                name: local-dotnet
+                language: dotnet
+                entry: dotnet --version
+                language_version: '1.0'
+    "});
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run(), @r"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    error: Failed to install hook `local-dotnet` with `language_version: 1.0`
+      caused by: No installed .NET SDK matches the requested `language_version`
+    ");
+}
+
+/// A failed install does not leave a broken environment behind.
+#[test]
+fn dotnet_failed_install_removes_env() -> anyhow::Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    context.write_pre_commit_config(indoc::indoc! {r"
+        repos:
+          - repo: local
+            hooks:
+              - id: local-dotnet
+                name: local-dotnet
+                language: dotnet
+                entry: dotnet --version
+                additional_dependencies: ['Prek.Missing.Tool:0.0.0']
+    "});
+    context.git_add(".");
+
+    let output = context.run().output()?;
+    assert!(!output.status.success());
+
+    let hooks = context.home_dir().child("hooks");
+    let envs = hooks.read_dir().map(Iterator::count).unwrap_or(0);
+    assert_eq!(envs, 0);
+
+    Ok(())
+}
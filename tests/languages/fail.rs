@@ -23,11 +23,17 @@ fn fail() -> Result<()> {
               entry: changelog filenames must end in .rst
               language: fail
               files: 'changelog/.*(?<!\.rst)$'
+            - id: no-commit-to-main
+              name: don't commit to main
+              entry: don't commit to main
+              language: fail
+              always_run: true
+              pass_filenames: false
     "});
 
     context.git_add(".");
 
-    cmd_snapshot!(context.filters(), context.run(), @r#"
+    cmd_snapshot!(context.filters(), context.run(), @r"
     success: false
     exit_code: 1
     ----- stdout -----
@@ -37,9 +43,13 @@ fn fail() -> Result<()> {
       changelog filenames must end in .rst
 
       changelog/changelog.md
+    don't commit to main.....................................................Failed
+    - hook id: no-commit-to-main
+    - exit code: 1
+      don't commit to main
 
     ----- stderr -----
-    "#);
+    ");
 
     Ok(())
 }
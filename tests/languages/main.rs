@@ -5,10 +5,13 @@ mod common;
 mod docker;
 #[cfg(all(feature = "docker", target_os = "linux"))]
 mod docker_image;
+#[cfg(feature = "dotnet")]
+mod dotnet;
 mod fail;
 mod golang;
 mod node;
 mod pygrep;
 mod python;
 mod script;
+mod system;
 mod unimplemented;
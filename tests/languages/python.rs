@@ -232,6 +232,57 @@ fn additional_dependencies() {
     "#);
 }
 
+/// Hooks with different `additional_dependencies` get their own environments.
+#[test]
+fn additional_dependencies_distinct_envs() -> anyhow::Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    context.write_pre_commit_config(indoc::indoc! {r#"
+        repos:
+          - repo: local
+            hooks:
+              - id: with-deps
+                name: with-deps
+                language: python
+                entry: pyecho with-deps
+                additional_dependencies: ["pyecho-cli"]
+                always_run: true
+                verbose: true
+                pass_filenames: false
+              - id: without-deps
+                name: without-deps
+                language: python
+                entry: python -c 'print("without-deps")'
+                always_run: true
+                verbose: true
+                pass_filenames: false
+    "#});
+
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run(), @r#"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    with-deps................................................................Passed
+    - hook id: with-deps
+    - duration: [TIME]
+      with-deps
+    without-deps.............................................................Passed
+    - hook id: without-deps
+    - duration: [TIME]
+      without-deps
+
+    ----- stderr -----
+    "#);
+
+    // Each hook should have its own environment.
+    assert_eq!(context.home_dir().child("hooks").read_dir()?.count(), 2);
+
+    Ok(())
+}
+
 /// Ensure that stderr from hooks is captured and shown to the user.
 #[test]
 fn hook_stderr() -> anyhow::Result<()> {
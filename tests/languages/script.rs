@@ -101,6 +101,8 @@ mod unix {
         - duration: [TIME]
           Hello, World!
 
+        Summary: 2 passed, 0 failed, 0 skipped across 2 projects
+
         ----- stderr -----
         ");
 
@@ -157,6 +159,50 @@ mod unix {
 
         Ok(())
     }
+
+    /// A script must exist, and without a shebang it must be executable.
+    #[test]
+    fn script_not_runnable() -> Result<()> {
+        let context = TestContext::new();
+        context.init_project();
+        context.write_pre_commit_config(indoc::indoc! {r"
+        repos:
+          - repo: local
+            hooks:
+              - id: script
+                name: script
+                language: script
+                entry: script.sh
+        "});
+        context.git_add(".");
+
+        cmd_snapshot!(context.filters(), context.run(), @r"
+        success: false
+        exit_code: 2
+        ----- stdout -----
+        script...................................................................
+        ----- stderr -----
+        error: Failed to run hook `script`
+          caused by: The script `[TEMP_DIR]/script.sh` does not exist
+        ");
+
+        let script = context.work_dir().child("script.sh");
+        script.write_str("echo 'Hello, World!'\n")?;
+        fs_err::set_permissions(&script, std::fs::Permissions::from_mode(0o644))?;
+        context.git_add(".");
+
+        cmd_snapshot!(context.filters(), context.run(), @r"
+        success: false
+        exit_code: 2
+        ----- stdout -----
+        script...................................................................
+        ----- stderr -----
+        error: Failed to run hook `script`
+          caused by: The script `[TEMP_DIR]/script.sh` is not executable, make it executable or add a shebang line
+        ");
+
+        Ok(())
+    }
 }
 
 /// Test that a script with a shebang line works correctly on Windows.
@@ -0,0 +1,56 @@
+use anyhow::Result;
+
+use crate::common::{TestContext, cmd_snapshot};
+
+/// The entry of a `system` hook is looked up in `PATH`.
+#[test]
+fn system() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+    context.write_pre_commit_config(indoc::indoc! {r"
+        repos:
+          - repo: local
+            hooks:
+              - id: system
+                name: system
+                language: system
+                entry: git --version
+                pass_filenames: false
+                always_run: true
+    "});
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run(), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    system...................................................................Passed
+
+    ----- stderr -----
+    ");
+
+    context.write_pre_commit_config(indoc::indoc! {r"
+        repos:
+          - repo: local
+            hooks:
+              - id: system
+                name: system
+                language: system
+                entry: prek-missing-command --version
+                pass_filenames: false
+                always_run: true
+    "});
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run(), @r"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+    system...................................................................
+    ----- stderr -----
+    error: Failed to run hook `system`
+      caused by: The `prek-missing-command` executable was not found in PATH
+    ");
+
+    Ok(())
+}
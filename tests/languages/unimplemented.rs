@@ -25,3 +25,30 @@ fn unimplemented_language() {
     ----- stderr -----
     "#);
 }
+
+#[test]
+fn unimplemented_language_as_error() {
+    let context = TestContext::new();
+    context.init_project();
+    context.write_pre_commit_config(indoc::indoc! {r"
+        repos:
+          - repo: local
+            hooks:
+            - id: rust-hook
+              name: rust-hook
+              language: rust
+              entry: cargo run
+    "});
+
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run().arg("--treat-unknown-language-as-error"), @"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    error: Hook `rust-hook` is invalid
+      caused by: Language `rust` of hook `rust-hook` from repo `local` is not supported yet. Supported languages are: `docker`, `docker_image`, `dotnet`, `fail`, `golang`, `node`, `python`, `pygrep`, `script`, `system`
+    ");
+}
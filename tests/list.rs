@@ -404,7 +404,10 @@ fn list_json_output() {
           "pre-push",
           "pre-rebase",
           "prepare-commit-msg"
-        ]
+        ],
+        "project": ".",
+        "repo": "local",
+        "rev": null
       },
       {
         "id": "check-json",
@@ -425,7 +428,10 @@ fn list_json_output() {
           "pre-push",
           "pre-rebase",
           "prepare-commit-msg"
-        ]
+        ],
+        "project": ".",
+        "repo": "local",
+        "rev": null
       }
     ]
 
@@ -457,7 +463,10 @@ fn list_json_output() {
           "pre-push",
           "pre-rebase",
           "prepare-commit-msg"
-        ]
+        ],
+        "project": ".",
+        "repo": "local",
+        "rev": null
       }
     ]
 
@@ -532,7 +541,10 @@ fn workspace_list() -> anyhow::Result<()> {
           "pre-push",
           "pre-rebase",
           "prepare-commit-msg"
-        ]
+        ],
+        "project": "nested/project4",
+        "repo": "local",
+        "rev": null
       },
       {
         "id": "show-cwd",
@@ -553,7 +565,10 @@ fn workspace_list() -> anyhow::Result<()> {
           "pre-push",
           "pre-rebase",
           "prepare-commit-msg"
-        ]
+        ],
+        "project": "project3/project5",
+        "repo": "local",
+        "rev": null
       },
       {
         "id": "show-cwd",
@@ -574,7 +589,10 @@ fn workspace_list() -> anyhow::Result<()> {
           "pre-push",
           "pre-rebase",
           "prepare-commit-msg"
-        ]
+        ],
+        "project": "project2",
+        "repo": "local",
+        "rev": null
       },
       {
         "id": "show-cwd",
@@ -595,7 +613,10 @@ fn workspace_list() -> anyhow::Result<()> {
           "pre-push",
           "pre-rebase",
           "prepare-commit-msg"
-        ]
+        ],
+        "project": "project3",
+        "repo": "local",
+        "rev": null
       },
       {
         "id": "show-cwd",
@@ -616,7 +637,10 @@ fn workspace_list() -> anyhow::Result<()> {
           "pre-push",
           "pre-rebase",
           "prepare-commit-msg"
-        ]
+        ],
+        "project": ".",
+        "repo": "local",
+        "rev": null
       }
     ]
 
@@ -656,6 +680,115 @@ fn workspace_list() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// `--projects` lists the projects in the order `run` runs them.
+#[test]
+fn list_projects() -> anyhow::Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    let config = indoc! {r"
+    repos:
+      - repo: local
+        hooks:
+        - id: echo
+          name: Echo
+          language: system
+          entry: echo
+          always_run: true
+          pass_filenames: false
+    "};
+
+    context.setup_workspace(
+        &[
+            "project2",
+            "project3",
+            "nested/project4",
+            "project3/project5",
+        ],
+        config,
+    )?;
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.list().arg("--projects"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    PROJECT            DEPTH  CONFIG                                     HOOKS
+    nested/project4    2      nested/project4/.pre-commit-config.yaml    echo
+    project3/project5  2      project3/project5/.pre-commit-config.yaml  echo
+    project2           1      project2/.pre-commit-config.yaml           echo
+    project3           1      project3/.pre-commit-config.yaml           echo
+    .                  0      .pre-commit-config.yaml                    echo
+
+    ----- stderr -----
+    ");
+
+    cmd_snapshot!(context.filters(), context.run(), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    Running hooks for `nested/project4`:
+    Echo.....................................................................Passed
+
+    Running hooks for `project3/project5`:
+    Echo.....................................................................Passed
+
+    Running hooks for `project2`:
+    Echo.....................................................................Passed
+
+    Running hooks for `project3`:
+    Echo.....................................................................Passed
+
+    Running hooks for `.`:
+    Echo.....................................................................Passed
+
+    Summary: 5 passed, 0 failed, 0 skipped across 5 projects
+
+    ----- stderr -----
+    ");
+
+    cmd_snapshot!(context.filters(), context.list().arg("--projects").arg("project3/"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    PROJECT            DEPTH  CONFIG                                     HOOKS
+    project3/project5  2      project3/project5/.pre-commit-config.yaml  echo
+    project3           1      project3/.pre-commit-config.yaml           echo
+
+    ----- stderr -----
+    ");
+
+    let mut filters = context.filters();
+    filters.push((r"\\/", "/")); // Normalize Windows path separators in JSON output
+    cmd_snapshot!(filters, context.list().arg("--projects").arg("--json").arg("project3/"), @r#"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    [
+      {
+        "path": "project3/project5",
+        "depth": 2,
+        "config": "project3/project5/.pre-commit-config.yaml",
+        "hooks": [
+          "echo"
+        ]
+      },
+      {
+        "path": "project3",
+        "depth": 1,
+        "config": "project3/.pre-commit-config.yaml",
+        "hooks": [
+          "echo"
+        ]
+      }
+    ]
+
+    ----- stderr -----
+    "#);
+
+    Ok(())
+}
+
 #[test]
 fn list_with_selectors() -> anyhow::Result<()> {
     let context = TestContext::new();
@@ -802,3 +935,112 @@ fn list_with_selectors() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn list_table() -> anyhow::Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    let repo = context.create_hook_repo(
+        "hook-repo",
+        indoc! {r"
+            - id: echo
+              name: echo
+              language: system
+              entry: echo
+        "},
+    )?;
+
+    context.setup_workspace(
+        &["project2"],
+        indoc! {r"
+        repos:
+          - repo: local
+            hooks:
+              - id: lint
+                name: lint
+                language: system
+                entry: echo lint
+                stages: [pre-push]
+        "},
+    )?;
+    context.write_pre_commit_config(&indoc::formatdoc! {r"
+        repos:
+          - repo: {}
+            rev: v1.0.0
+            hooks:
+              - id: echo
+                alias: say
+          - repo: local
+            hooks:
+              - id: format
+                name: format
+                language: python
+                entry: python -m format
+    ", repo.display()});
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.list().arg("--output-format").arg("table"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    Project `project2`:
+    ID    ALIAS  LANGUAGE  STAGES    REPO
+    lint  -      system    pre-push  local
+
+    Project `.`:
+    ID      ALIAS  LANGUAGE  STAGES  REPO
+    echo    say    system    all     [HOME]/test-repos/hook-repo@v1.0.0
+    format  -      python    all     local
+
+    ----- stderr -----
+    ");
+
+    cmd_snapshot!(context.filters(), context.list().arg("--output-format").arg("table").arg("--stage").arg("pre-commit"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    Project `.`:
+    ID      ALIAS  LANGUAGE  STAGES  REPO
+    echo    say    system    all     [HOME]/test-repos/hook-repo@v1.0.0
+    format  -      python    all     local
+
+    ----- stderr -----
+    ");
+
+    cmd_snapshot!(context.filters(), context.list().arg("--json").arg("--language").arg("system").arg("--skip").arg("project2/"), @r#"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    [
+      {
+        "id": "echo",
+        "full_id": ".:echo",
+        "name": "echo",
+        "alias": "say",
+        "language": "system",
+        "description": null,
+        "stages": [
+          "manual",
+          "commit-msg",
+          "post-checkout",
+          "post-commit",
+          "post-merge",
+          "post-rewrite",
+          "pre-commit",
+          "pre-merge-commit",
+          "pre-push",
+          "pre-rebase",
+          "prepare-commit-msg"
+        ],
+        "project": ".",
+        "repo": "[HOME]/test-repos/hook-repo",
+        "rev": "v1.0.0"
+      }
+    ]
+
+    ----- stderr -----
+    "#);
+
+    Ok(())
+}
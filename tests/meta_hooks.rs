@@ -45,7 +45,7 @@ fn meta_hooks() -> anyhow::Result<()> {
     Check hooks apply........................................................Failed
     - hook id: check-hooks-apply
     - exit code: 1
-      match-no-files does not apply to this repository
+      match-no-files (repo: local) does not apply to this repository
     Check useless excludes...................................................Failed
     - hook id: check-useless-excludes
     - exit code: 1
@@ -67,6 +67,76 @@ fn meta_hooks() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// `check-hooks-apply` ignores hooks that always run or belong to another stage.
+#[test]
+fn check_hooks_apply_stages() -> anyhow::Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    context.write_pre_commit_config(indoc::indoc! {r"
+        repos:
+          - repo: meta
+            hooks:
+              - id: check-hooks-apply
+          - repo: local
+            hooks:
+              - id: matches
+                name: matches
+                language: system
+                entry: echo
+                files: \.txt$
+              - id: matches-nothing
+                name: matches nothing
+                language: system
+                entry: echo
+                files: ^nonexistent$
+              - id: always
+                name: always
+                language: system
+                entry: echo
+                files: ^nonexistent$
+                always_run: true
+              - id: manual-only
+                name: manual only
+                language: system
+                entry: echo
+                files: ^nonexistent$
+                stages: [manual]
+    "});
+    context
+        .work_dir()
+        .child("file.txt")
+        .write_str("Hello, world!\n")?;
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run().arg("check-hooks-apply"), @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+    Check hooks apply........................................................Failed
+    - hook id: check-hooks-apply
+    - exit code: 1
+      matches-nothing (repo: local) does not apply to this repository
+
+    ----- stderr -----
+    ");
+
+    cmd_snapshot!(context.filters(), context.run().arg("check-hooks-apply").arg("--hook-stage").arg("manual"), @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+    Check hooks apply........................................................Failed
+    - hook id: check-hooks-apply
+    - exit code: 1
+      matches-nothing (repo: local) does not apply to this repository
+      manual-only (repo: local) does not apply to this repository
+
+    ----- stderr -----
+    ");
+
+    Ok(())
+}
+
 #[test]
 fn check_useless_excludes_remote() -> anyhow::Result<()> {
     let context = TestContext::new();
@@ -162,7 +232,7 @@ fn meta_hooks_workspace() -> anyhow::Result<()> {
     Check hooks apply........................................................Failed
     - hook id: check-hooks-apply
     - exit code: 1
-      match-no-files does not apply to this repository
+      match-no-files (repo: local) does not apply to this repository
     Check useless excludes...................................................Failed
     - hook id: check-useless-excludes
     - exit code: 1
@@ -183,3 +253,54 @@ fn meta_hooks_workspace() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+/// Meta hooks of a project only consider the files of that project.
+#[test]
+fn meta_hooks_project_files() -> anyhow::Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    let config = indoc::indoc! {r"
+        repos:
+          - repo: meta
+            hooks:
+              - id: check-hooks-apply
+          - repo: local
+            hooks:
+              - id: root-only
+                name: root only
+                language: system
+                entry: python3 -c 'import sys; sys.exit(0)'
+                files: ^root\.txt$
+    "};
+    context.write_pre_commit_config(config);
+    context.work_dir().child("root.txt").write_str("root\n")?;
+
+    let app = context.work_dir().child("app");
+    app.create_dir_all()?;
+    app.child(CONFIG_FILE).write_str(config)?;
+    app.child("app.txt").write_str("app\n")?;
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run().arg("--all-files"), @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+    Running hooks for `app`:
+    Check hooks apply........................................................Failed
+    - hook id: check-hooks-apply
+    - exit code: 1
+      root-only (repo: local) does not apply to this repository
+    root only............................................(no files to check)Skipped
+
+    Running hooks for `.`:
+    Check hooks apply........................................................Passed
+    root only................................................................Passed
+
+    Summary: 2 passed, 1 failed, 1 skipped across 2 projects
+
+    ----- stderr -----
+    ");
+
+    Ok(())
+}
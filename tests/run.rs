@@ -4,6 +4,7 @@ use std::process::Command;
 use anyhow::Result;
 use assert_cmd::assert::OutputAssertExt;
 use assert_fs::prelude::*;
+use constants::env_vars::EnvVars;
 use constants::{ALT_CONFIG_FILE, CONFIG_FILE};
 use insta::assert_snapshot;
 
@@ -94,8 +95,8 @@ fn invalid_config() {
             hooks:
               - id: trailing-whitespace
                 name: trailing-whitespace
-                language: dotnet
-                additional_dependencies: ["dotnet@6"]
+                language: system
+                additional_dependencies: ["foo"]
                 entry: echo Hello, world!
     "#});
     context.git_add(".");
@@ -107,7 +108,7 @@ fn invalid_config() {
 
     ----- stderr -----
     error: Hook `trailing-whitespace` is invalid
-      caused by: Hook specified `additional_dependencies` `dotnet@6` but the language `dotnet` does not support installing dependencies for now
+      caused by: Hook specified `additional_dependencies` `foo` but the language `system` does not support installing dependencies for now
     "#);
 
     context.write_pre_commit_config(indoc::indoc! {r"
@@ -208,6 +209,65 @@ fn local() {
     "#);
 }
 
+/// A failed hook prints its output even if not verbose, with stdout and stderr in the order they were written.
+#[test]
+fn failure_output() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    context.write_pre_commit_config(indoc::indoc! {r#"
+        repos:
+          - repo: local
+            hooks:
+              - id: interleaved
+                name: interleaved
+                language: system
+                entry: sh -c 'echo out1; echo err1 >&2; echo out2; printf "bad byte \377\n" >&2; exit 1'
+                always_run: true
+                pass_filenames: false
+              - id: large
+                name: large
+                language: system
+                entry: sh -c 'seq 100000; exit 1'
+                always_run: true
+                pass_filenames: false
+              - id: quiet
+                name: quiet
+                language: system
+                entry: echo hidden
+                always_run: true
+    "#});
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run().arg("interleaved").arg("quiet"), @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+    interleaved..............................................................Failed
+    - hook id: interleaved
+    - exit code: 1
+      out1
+      err1
+      out2
+      bad byte �
+    quiet....................................................................Passed
+
+    ----- stderr -----
+    ");
+
+    // Large outputs are not truncated.
+    let output = context.run().arg("large").output()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("\n  1\n"));
+    assert!(stdout.contains("\n  100000\n"));
+    assert_eq!(
+        stdout.lines().filter(|line| line.starts_with("  ")).count(),
+        100_000
+    );
+
+    Ok(())
+}
+
 /// Test multiple hook IDs scenarios.
 #[test]
 fn multiple_hook_ids() {
@@ -269,6 +329,7 @@ fn multiple_hook_ids() {
     ----- stderr -----
     warning: selector `nonexistent-hook` did not match any hooks
     error: No hooks found after filtering with the given selectors
+    hint: Available hooks are: `hook1`, `hook2`, `shared-name`, `shared-name-2`
     ");
 
     // Multiple hook_ids match nothing
@@ -282,6 +343,7 @@ fn multiple_hook_ids() {
       - `nonexistent-hook`
       - `nonexistent-hook-2`
     error: No hooks found after filtering with the given selectors
+    hint: Available hooks are: `hook1`, `hook2`, `shared-name`, `shared-name-2`
     ");
 
     // Hook-id matches one hook
@@ -359,6 +421,56 @@ fn config_not_staged() -> Result<()> {
     error: prek configuration file is not staged, run `git add .pre-commit-config.yaml` to stage it
     "#);
 
+    // The unstaged configuration is used with a warning.
+    let filters: Vec<_> = context
+        .filters()
+        .into_iter()
+        .chain([(r"/\d+-\d+.patch", "/[TIME]-[PID].patch")])
+        .collect();
+    cmd_snapshot!(filters, context.run().arg("--allow-unstaged-config"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    trailing-whitespace......................................................Passed
+
+    ----- stderr -----
+    warning: prek configuration file `.pre-commit-config.yaml` is not staged, using its unstaged content
+    Non-staged changes detected, saving to `[HOME]/patches/[TIME]-[PID].patch`
+
+    Restored working tree changes from `[HOME]/patches/[TIME]-[PID].patch`
+    ");
+
+    Ok(())
+}
+
+/// Dumb terminals never get colored output under `--color auto`.
+#[test]
+fn dumb_terminal_no_color() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    context.work_dir().child(CONFIG_FILE).touch()?;
+    context.git_add(".");
+    context.write_pre_commit_config("repos: []\n");
+
+    let output = context
+        .run()
+        .arg("--color")
+        .arg("always")
+        .env("TERM", "dumb")
+        .output()?;
+    assert!(output.stderr.contains(&b'\x1b'));
+
+    let output = context
+        .run()
+        .arg("--color")
+        .arg("auto")
+        .env("TERM", "dumb")
+        .env("CI", "true")
+        .output()?;
+    assert!(!output.stderr.contains(&b'\x1b'));
+    assert_snapshot!(String::from_utf8(output.stderr)?, @"error: prek configuration file is not staged, run `git add .pre-commit-config.yaml` to stage it");
+
     Ok(())
 }
 
@@ -402,6 +514,54 @@ fn config_outside_repo() -> Result<()> {
     Ok(())
 }
 
+/// `--config -` reads the configuration from stdin, like a configuration file.
+#[test]
+fn config_from_stdin() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    let config = indoc::indoc! {r"
+        repos:
+          - repo: local
+            hooks:
+              - id: show
+                name: show
+                language: system
+                entry: echo
+                verbose: true
+    "};
+    context.work_dir().child("file.txt").write_str("Hello\n")?;
+    context.work_dir().child("c.yaml").write_str(config)?;
+    context.git_add("file.txt");
+
+    cmd_snapshot!(context.filters(), context.run().arg("--config").arg("c.yaml"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    show.....................................................................Passed
+    - hook id: show
+    - duration: [TIME]
+      file.txt
+
+    ----- stderr -----
+    ");
+
+    // The configuration is not a file, so there is nothing to stage.
+    cmd_snapshot!(context.filters(), context.run().arg("--config").arg("-").pass_stdin(config), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    show.....................................................................Passed
+    - hook id: show
+    - duration: [TIME]
+      file.txt
+
+    ----- stderr -----
+    ");
+
+    Ok(())
+}
+
 /// Test the output format for a hook with a CJK name.
 #[test]
 fn cjk_hook_name() {
@@ -467,6 +627,7 @@ fn skips() {
     trailing-whitespace......................................................Failed
     - hook id: trailing-whitespace
     - exit code: 1
+    fix end of files........................................................Skipped
     check json...............................................................Failed
     - hook id: check-json
     - exit code: 1
@@ -478,12 +639,67 @@ fn skips() {
     success: false
     exit_code: 1
     ----- stdout -----
+    trailing-whitespace.....................................................Skipped
+    fix end of files........................................................Skipped
     check json...............................................................Failed
     - hook id: check-json
     - exit code: 1
 
     ----- stderr -----
     ");
+
+    // The run passes when all hooks that are not skipped pass.
+    cmd_snapshot!(context.filters(), context.run().arg("--skip").arg("trailing-whitespace").arg("--skip").arg("end-of-file-fixer").arg("--skip").arg("check-json"), @"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    trailing-whitespace.....................................................Skipped
+    fix end of files........................................................Skipped
+    check json..............................................................Skipped
+
+    ----- stderr -----
+    ");
+}
+
+/// Test `--exclude-hook` runs all hooks except the excluded ones.
+#[test]
+fn exclude_hook() {
+    let context = TestContext::new();
+    context.init_project();
+
+    context.write_pre_commit_config(indoc::indoc! {r#"
+        repos:
+          - repo: local
+            hooks:
+              - id: trailing-whitespace
+                name: trailing-whitespace
+                language: system
+                entry: python3 -c "exit(1)"
+                always_run: true
+              - id: end-of-file-fixer
+                name: fix end of files
+                language: system
+                entry: python3 -c "exit(0)"
+                always_run: true
+              - id: check-json
+                name: check json
+                language: system
+                entry: python3 -c "exit(1)"
+                always_run: true
+    "#});
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run().arg("--exclude-hook").arg("trailing-whitespace").arg("--exclude-hook").arg("check-json").arg("--exclude-hook").arg("no-such-hook"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    trailing-whitespace...........................................(excluded)Skipped
+    fix end of files.........................................................Passed
+    check json....................................................(excluded)Skipped
+
+    ----- stderr -----
+    warning: hook `no-such-hook` passed to `--exclude-hook` does not exist
+    ");
 }
 
 /// Run hooks with matched `stage`.
@@ -534,18 +750,103 @@ fn stage() {
     ----- stderr -----
     "#);
 
+    // Hooks selected by id that don't run in the stage get a hint.
+    cmd_snapshot!(context.filters(), context.run().arg("manual-stage"), @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    error: No hooks found for stage `pre-commit` after filtering
+    hint: Hook `manual-stage` only runs in stage `manual`, use `--hook-stage manual` to run it
+    ");
+
+    cmd_snapshot!(context.filters(), context.run().arg("manual-stage").arg("default-stage"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    default-stage............................................................Passed
+
+    ----- stderr -----
+    warning: Hook `manual-stage` only runs in stage `manual`, use `--hook-stage manual` to run it
+    ");
+
+    cmd_snapshot!(context.filters(), context.run().arg("--hook-stage").arg("manual").arg("manual-stage"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    manual-stage.............................................................Passed
+
+    ----- stderr -----
+    ");
+
     // Run hooks with `post-commit` stage.
     cmd_snapshot!(context.filters(), context.run().arg("--hook-stage").arg("post-commit"), @r#"
     success: true
     exit_code: 0
     ----- stdout -----
     default-stage........................................(no files to check)Skipped
-    post-commit-stage....................................(no files to check)Skipped
+    post-commit-stage........................................................Passed
 
     ----- stderr -----
     "#);
 }
 
+/// Legacy stage names are normalized, and `default_stages` applies to hooks without `stages`.
+#[test]
+fn stage_aliases_and_default_stages() {
+    let context = TestContext::new();
+    context.init_project();
+    context.write_pre_commit_config(indoc::indoc! {r"
+        default_stages: [ manual ]
+        repos:
+          - repo: local
+            hooks:
+              - id: default-stages
+                name: default-stages
+                language: system
+                entry: echo default-stages
+              - id: legacy-commit
+                name: legacy-commit
+                language: system
+                entry: echo legacy-commit
+                stages: [ commit ]
+              - id: legacy-push
+                name: legacy-push
+                language: system
+                entry: echo legacy-push
+                stages: [ push ]
+    "});
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run(), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    legacy-commit............................................................Passed
+
+    ----- stderr -----
+    ");
+
+    cmd_snapshot!(context.filters(), context.run().arg("--hook-stage").arg("manual"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    default-stages...........................................................Passed
+
+    ----- stderr -----
+    ");
+
+    cmd_snapshot!(context.filters(), context.run().arg("--hook-stage").arg("pre-push").arg("--all-files"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    legacy-push..............................................................Passed
+
+    ----- stderr -----
+    ");
+}
+
 /// Test global `files`, `exclude`, and hook level `files`, `exclude`.
 #[test]
 fn files_and_exclude() -> Result<()> {
@@ -713,74 +1014,242 @@ fn file_types() -> Result<()> {
     Ok(())
 }
 
-/// Abort the run if a hook fails.
+/// Test file types detected from the content: binary files and executable scripts with a shebang.
+#[cfg(unix)]
 #[test]
-fn fail_fast() {
+fn file_types_from_content() -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
     let context = TestContext::new();
     context.init_project();
 
-    context.write_pre_commit_config(indoc::indoc! {r#"
+    let cwd = context.work_dir();
+    cwd.child("config.yaml").write_str("key: value\n")?;
+    cwd.child("blob.dat")
+        .write_binary(&[0x00, 0xff, 0x10, 0x00, 0x7f])?;
+    cwd.child("script")
+        .write_str("#!/usr/bin/env python3\nprint('hi')\n")?;
+    fs_err::set_permissions(cwd.join("script"), std::fs::Permissions::from_mode(0o755))?;
+
+    context.write_pre_commit_config(indoc::indoc! {r"
         repos:
           - repo: local
             hooks:
-              - id: trailing-whitespace
-                name: trailing-whitespace
-                language: system
-                entry: python3 -c 'print("Fixing files"); exit(1)'
-                always_run: true
-                fail_fast: false
-              - id: trailing-whitespace
-                name: trailing-whitespace
+              - id: python
+                name: python
                 language: system
-                entry: python3 -c 'print("Fixing files"); exit(1)'
-                always_run: true
-                fail_fast: true
-              - id: trailing-whitespace
-                name: trailing-whitespace
+                entry: echo
+                types: [python]
+                verbose: true
+              - id: not-binary
+                name: not-binary
                 language: system
-                entry: python3 -V
-                always_run: true
-              - id: trailing-whitespace
-                name: trailing-whitespace
+                entry: echo
+                exclude_types: [binary, yaml]
+                verbose: true
+              - id: executable-text
+                name: executable-text
                 language: system
-                entry: python3 -V
-                always_run: true
-    "#});
+                entry: echo
+                types: [executable, text]
+                verbose: true
+    "});
     context.git_add(".");
 
-    cmd_snapshot!(context.filters(), context.run(), @r#"
-    success: false
-    exit_code: 1
+    cmd_snapshot!(context.filters(), context.run(), @r"
+    success: true
+    exit_code: 0
     ----- stdout -----
-    trailing-whitespace......................................................Failed
-    - hook id: trailing-whitespace
-    - exit code: 1
-      Fixing files
-    trailing-whitespace......................................................Failed
-    - hook id: trailing-whitespace
-    - exit code: 1
-      Fixing files
+    python...................................................................Passed
+    - hook id: python
+    - duration: [TIME]
+      script
+    not-binary...............................................................Passed
+    - hook id: not-binary
+    - duration: [TIME]
+      script
+    executable-text..........................................................Passed
+    - hook id: executable-text
+    - duration: [TIME]
+      script
 
     ----- stderr -----
-    "#);
+    ");
+
+    Ok(())
 }
 
-/// Run from a subdirectory. File arguments should be fixed to be relative to the root.
+/// Test `always_run` and `pass_filenames: false` together with `files` and `types` filters.
 #[test]
-fn subdirectory() -> Result<()> {
+fn always_run_and_pass_filenames() -> Result<()> {
     let context = TestContext::new();
     context.init_project();
 
-    let cwd = context.work_dir();
-    let child = cwd.child("foo/bar/baz");
-    child.create_dir_all()?;
-    child.child("file.txt").write_str("Hello, world!\n")?;
-
-    context.write_pre_commit_config(indoc::indoc! {r"
+    let config = indoc::indoc! {r"
         repos:
           - repo: local
             hooks:
-              - id: trailing-whitespace
+              - id: always-run-no-match
+                name: always-run-no-match
+                language: system
+                entry: python3 -c 'import sys; print(sys.argv[1:])'
+                files: ^nonexistent$
+                always_run: true
+                verbose: true
+              - id: always-run-types
+                name: always-run-types
+                language: system
+                entry: python3 -c 'import sys; print(sys.argv[1:])'
+                types: [json]
+                always_run: true
+                verbose: true
+              - id: no-filenames
+                name: no-filenames
+                language: system
+                entry: python3 -c 'import sys; print(sys.argv[1:])'
+                files: \.txt$
+                pass_filenames: false
+                verbose: true
+              - id: no-filenames-no-match
+                name: no-filenames-no-match
+                language: system
+                entry: python3 -c 'import sys; print(sys.argv[1:])'
+                files: ^nonexistent$
+                pass_filenames: false
+              - id: once-per-project
+                name: once-per-project
+                language: system
+                entry: python3 -c 'import os; print(os.path.basename(os.getcwd()))'
+                always_run: true
+                pass_filenames: false
+                verbose: true
+    "};
+    context.setup_workspace(&["project2"], config)?;
+    let cwd = context.work_dir();
+    cwd.child("file.txt").write_str("Hello, world!\n")?;
+    cwd.child("data.json").write_str("{}\n")?;
+    cwd.child("project2/file.txt")
+        .write_str("Hello, world!\n")?;
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run(), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    Running hooks for `project2`:
+    always-run-no-match......................................................Passed
+    - hook id: always-run-no-match
+    - duration: [TIME]
+      []
+    always-run-types.........................................................Passed
+    - hook id: always-run-types
+    - duration: [TIME]
+      []
+    no-filenames.............................................................Passed
+    - hook id: no-filenames
+    - duration: [TIME]
+      []
+    no-filenames-no-match................................(no files to check)Skipped
+    once-per-project.........................................................Passed
+    - hook id: once-per-project
+    - duration: [TIME]
+      project2
+
+    Running hooks for `.`:
+    always-run-no-match......................................................Passed
+    - hook id: always-run-no-match
+    - duration: [TIME]
+      []
+    always-run-types.........................................................Passed
+    - hook id: always-run-types
+    - duration: [TIME]
+      ['data.json']
+    no-filenames.............................................................Passed
+    - hook id: no-filenames
+    - duration: [TIME]
+      []
+    no-filenames-no-match................................(no files to check)Skipped
+    once-per-project.........................................................Passed
+    - hook id: once-per-project
+    - duration: [TIME]
+      temp
+
+    Summary: 8 passed, 0 failed, 2 skipped across 2 projects
+
+    ----- stderr -----
+    ");
+
+    Ok(())
+}
+
+/// Abort the run if a hook fails.
+#[test]
+fn fail_fast() {
+    let context = TestContext::new();
+    context.init_project();
+
+    context.write_pre_commit_config(indoc::indoc! {r#"
+        repos:
+          - repo: local
+            hooks:
+              - id: trailing-whitespace
+                name: trailing-whitespace
+                language: system
+                entry: python3 -c 'print("Fixing files"); exit(1)'
+                always_run: true
+                fail_fast: false
+              - id: trailing-whitespace
+                name: trailing-whitespace
+                language: system
+                entry: python3 -c 'print("Fixing files"); exit(1)'
+                always_run: true
+                fail_fast: true
+              - id: trailing-whitespace
+                name: trailing-whitespace
+                language: system
+                entry: python3 -V
+                always_run: true
+              - id: trailing-whitespace
+                name: trailing-whitespace
+                language: system
+                entry: python3 -V
+                always_run: true
+    "#});
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run(), @r#"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+    trailing-whitespace......................................................Failed
+    - hook id: trailing-whitespace
+    - exit code: 1
+      Fixing files
+    trailing-whitespace......................................................Failed
+    - hook id: trailing-whitespace
+    - exit code: 1
+      Fixing files
+
+    ----- stderr -----
+    "#);
+}
+
+/// Run from a subdirectory. File arguments should be fixed to be relative to the root.
+#[test]
+fn subdirectory() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    let cwd = context.work_dir();
+    let child = cwd.child("foo/bar/baz");
+    child.create_dir_all()?;
+    child.child("file.txt").write_str("Hello, world!\n")?;
+
+    context.write_pre_commit_config(indoc::indoc! {r"
+        repos:
+          - repo: local
+            hooks:
+              - id: trailing-whitespace
                 name: trailing-whitespace
                 language: system
                 entry: python3 -c 'import sys; print(sys.argv[1]); exit(1)'
@@ -801,7 +1270,8 @@ fn subdirectory() -> Result<()> {
     ----- stderr -----
     "#);
 
-    cmd_snapshot!(context.filters(), context.run().arg("--cd").arg(&*child).arg("--files").arg("file.txt"), @r#"
+    // `--files` stays relative to the current directory with `--cd`.
+    cmd_snapshot!(context.filters(), context.run().arg("--cd").arg(&*child).arg("--files").arg("foo/bar/baz/file.txt"), @r#"
     success: false
     exit_code: 1
     ----- stdout -----
@@ -850,6 +1320,41 @@ fn log_file() {
     assert_eq!(log, "Fixing files");
 }
 
+/// Colors in the output of hooks are kept on the terminal, but not written to log files.
+#[test]
+fn log_file_strips_colors() {
+    let context = TestContext::new();
+    context.init_project();
+
+    context.write_pre_commit_config(indoc::indoc! {r#"
+        repos:
+          - repo: local
+            hooks:
+              - id: colored
+                name: colored
+                language: system
+                entry: sh -c 'printf "\033[31mred\033[0m\n"; exit 1'
+                pass_filenames: false
+                always_run: true
+                log_file: log.txt
+    "#});
+    context.git_add(".");
+
+    let output = context
+        .run()
+        .arg("--color")
+        .arg("always")
+        .arg("--log-file")
+        .arg("run.log")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+
+    assert_eq!(context.read("log.txt"), "red");
+    let log = context.read("run.log");
+    assert!(log.ends_with("\nred\n"), "{log}");
+}
+
 /// Pass pre-commit environment variables to the hook.
 #[test]
 fn pass_env_vars() {
@@ -990,6 +1495,114 @@ fn restore_on_interrupt() -> Result<()> {
     Ok(())
 }
 
+/// When a hook stages changes that conflict with the non-staged changes, the changes are
+/// kept in the patch, with instructions to recover them.
+#[test]
+fn restore_conflict() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+    context.configure_git_author();
+    context.disable_auto_crlf();
+    context.write_pre_commit_config(indoc::indoc! {r"
+        repos:
+          - repo: local
+            hooks:
+              - id: rewrite-and-stage
+                name: rewrite-and-stage
+                language: system
+                entry: sh -c 'echo Rewritten > file.txt && git add file.txt'
+                pass_filenames: false
+    "});
+
+    context.work_dir().child("file.txt").write_str("Hello\n")?;
+    context.git_add(".");
+    context.git_commit("Initial commit");
+
+    context.work_dir().child("file.txt").write_str("Staged\n")?;
+    context.git_add(".");
+    context
+        .work_dir()
+        .child("file.txt")
+        .write_str("Not staged\n")?;
+
+    let mut filters = context.filters();
+    filters.push((r"patches/\d+-\d+\.patch", "patches/[PATCH].patch"));
+
+    cmd_snapshot!(filters, context.run(), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    rewrite-and-stage........................................................Passed
+
+    ----- stderr -----
+    Non-staged changes detected, saving to `[HOME]/patches/[PATCH].patch`
+    Failed to apply the patch, rolling back changes
+    Failed to restore working tree changes: The changes conflict with the current working tree, they are kept in `[HOME]/patches/[PATCH].patch`, resolve the conflicts with `git apply --3way [HOME]/patches/[PATCH].patch`
+    ");
+
+    Ok(())
+}
+
+/// `--stash-unstaged` hides the non-staged changes from hooks when running on all files.
+#[test]
+fn stash_unstaged_all_files() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+    context.write_pre_commit_config(indoc::indoc! {r"
+        repos:
+          - repo: local
+            hooks:
+              - id: cat
+                name: cat
+                language: system
+                entry: cat
+                verbose: true
+                files: ^file\.txt$
+    "});
+
+    context.work_dir().child("file.txt").write_str("Staged")?;
+    context.git_add(".");
+    context
+        .work_dir()
+        .child("file.txt")
+        .write_str("Not staged")?;
+
+    let mut filters = context.filters();
+    filters.push((r"patches/\d+-\d+\.patch", "patches/[PATCH].patch"));
+
+    cmd_snapshot!(filters.clone(), context.run().arg("--all-files"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    cat......................................................................Passed
+    - hook id: cat
+    - duration: [TIME]
+      Not staged
+
+    ----- stderr -----
+    ");
+
+    cmd_snapshot!(filters, context.run().arg("--all-files").arg("--stash-unstaged"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    cat......................................................................Passed
+    - hook id: cat
+    - duration: [TIME]
+      Staged
+
+    ----- stderr -----
+    Non-staged changes detected, saving to `[HOME]/patches/[PATCH].patch`
+
+    Restored working tree changes from `[HOME]/patches/[PATCH].patch`
+    ");
+
+    let content = context.read("file.txt");
+    assert_snapshot!(content, @"Not staged");
+
+    Ok(())
+}
+
 /// When in merge conflict, runs on files that have conflicts fixed.
 #[test]
 fn merge_conflicts() -> Result<()> {
@@ -1158,14 +1771,15 @@ fn init_nonexistent_repo() {
         ])
         .collect::<Vec<_>>();
 
-    cmd_snapshot!(filters, context.run(), @r"
+    // A host that does not resolve is a network error, which does not fall back to a full clone.
+    cmd_snapshot!(filters, context.run().env(EnvVars::PREK_CLONE_RETRIES, "0"), @r"
     success: false
     exit_code: 2
     ----- stdout -----
 
     ----- stderr -----
     error: Failed to initialize repo `https://notexistentatallnevergonnahappen.com/nonexistent/repo`
-      caused by: command `git full clone` exited with an error:
+      caused by: command `git shallow clone` exited with an error:
 
     [status]
     exit status: 128
@@ -1423,7 +2037,8 @@ fn run_directory() -> Result<()> {
     ----- stderr -----
     "#);
 
-    cmd_snapshot!(context.filters(), context.run().arg("--cd").arg("dir1").arg("--directory").arg("."), @r#"
+    // `--directory` stays relative to the current directory with `--cd`.
+    cmd_snapshot!(context.filters(), context.run().arg("--cd").arg("dir1").arg("--directory").arg("dir1"), @r#"
     success: true
     exit_code: 0
     ----- stdout -----
@@ -1696,7 +2311,7 @@ fn selectors_completion() -> Result<()> {
     // Unrelated non-project dir should not appear in subdir suggestions
     cwd.child("scratch").create_dir_all()?;
 
-    cmd_snapshot!(context.filters(), context.run().env("COMPLETE", "fish").arg("--").arg("prek").arg(""), @r"
+    cmd_snapshot!(context.filters(), context.run().env("COMPLETE", "fish").arg("--").arg("prek").arg(""), @"
     success: true
     exit_code: 0
     ----- stdout -----
@@ -1709,10 +2324,10 @@ fn selectors_completion() -> Result<()> {
     validate-manifest	Validate `.pre-commit-hooks.yaml` files
     sample-config	Produce a sample `.pre-commit-config.yaml` file
     auto-update	Auto-update pre-commit config to the latest repos' versions
-    gc	Clean unused cached repos
-    clean	Clean out pre-commit files
+    gc	Clean unused cached repos, keeping the ones still in use
+    clean	Remove the whole cache: all cached repos and hook environments
     init-template-dir	Install hook script in a directory intended for use with `git config init.templateDir`
-    try-repo	Try the pre-commit hooks in the current repo
+    try-repo	Try the hooks of a repository without adding it to the configuration
     self	`prek` self management
     app/
     app:
@@ -1720,19 +2335,57 @@ fn selectors_completion() -> Result<()> {
     lib-hook	Lib Hook
     root-hook	Root Hook
     --skip	Skip the specified hooks or projects
+    --project	Only run the hooks of the project at the given path, relative to the workspace root
     --all-files	Run on all files in the repo
     --files	Specific filenames to run hooks on
     --directory	Run hooks on all files in the specified directories
-    --from-ref	The original ref in a `<from_ref>...<to_ref>` diff expression. Files changed in this diff will be run through the hooks
-    --to-ref	The destination ref in a `from_ref...to_ref` diff expression. Defaults to `HEAD` if `from_ref` is specified
+    --from-ref	The original ref in a `<from_ref>...<to_ref>` diff expression. Files changed in this diff will be run through the hooks, files deleted in it are excluded. Must be specified together with `--to-ref`
+    --to-ref	The destination ref in a `from_ref...to_ref` diff expression. Must be specified together with `--from-ref`
     --last-commit	Run hooks against the last commit. Equivalent to `--from-ref HEAD~1 --to-ref HEAD`
     --hook-stage	The stage during which the hook is fired
     --show-diff-on-failure	When hooks fail, run `git diff` directly afterward
     --dry-run	Do not run the hooks, but print the hooks that would have been run
+    --no-cache	Always run the hooks, instead of reusing the results of hooks that already passed on the same files
+    --hook-repeat	Run the selected hook this many times on the same files, and fail if the results differ between runs
+    --report-unmatched-files	After running, list the files that no hook was run on, grouped by extension
+    --include-nested-project-files	Also pass the files of nested projects to the hooks of their parent projects
+    --max-total-duration	Stop the run once it has taken longer than this duration, for example `10m`
+    --per-project-status	Write the pass/fail status of each project to the given file as JSON
+    --project-order	Run the projects in the order listed in the given file, one project per line
+    --refresh-configs	Re-read all configuration files, ignoring the cached workspace discovery results
+    --allow-unstaged-config	Warn instead of failing when a configuration file has unstaged changes
+    --repo	Run hooks in the given git repository instead of the current one
+    --output-dir	Collect the files matching each hook's `output_artifacts` glob into this directory
+    --output-format	The output format
+    --output	The file to write the report to, required with `--output-format junit`
+    --log-file	Append the full output of each hook to this file, whether or not it is verbose
+    --summary-json-only	Only print a single JSON object summarizing the run once all hooks have run
+    --treat-unknown-language-as-error	Fail if a hook uses a `language` that prek does not support yet, instead of skipping the hook
+    --clean-env	Run hooks with a minimal environment, containing only `PATH` and the `PRE_COMMIT_*` variables
+    --hook-env-passthrough	Environment variables to pass through to hooks, separated by commas
+    --env	Set an environment variable for all hooks, as `<KEY>=<VALUE>`
+    --sandbox	Run each hook in a sandbox without network access, where only the project directory and the temporary directory are writable
+    --jobs	The maximum number of hook processes to run at the same time
+    --hook-concurrency	Limit how many invocations of a hook run at the same time, as `<HOOK_ID>=<N>`
+    --timeout	Kill a hook and mark it as failed if it runs longer than this duration
+    --no-parallel-clone	Clone remote repos one at a time instead of in parallel
+    --clone-retries	Retry cloning a remote repo up to this many times when it fails with a network error
+    --print-resolved-revs	Print the commit that each remote repo's `rev` resolved to, instead of running hooks
+    --interactive-hook	Run the hook with the given id interactively, connected directly to the terminal
+    --no-buffer	Stream the output of hooks to the terminal as they run, instead of printing it under each hook once it completes
+    --exclude-hook	Run all hooks except the hook with the given id, can be specified multiple times
+    --watch	Keep running after the hooks finish, and run them again when files change
+    --hook	Run only the hook with the given id or alias, on the file given by `--on-file`
+    --on-file	The file to run the hook given by `--hook` on
     --config	Path to alternate config file
     --cd	Change to directory before running
     --color	Whether to use color in output
     --refresh	Refresh all cached data
+    --offline	Disable network access when cloning repos
+    --strict	Reject unknown keys in config files instead of ignoring them
+    --follow-symlinks	Follow symlinked directories when discovering the projects of a workspace
+    --include-ignored	Also discover the projects of a workspace in directories ignored by git
+    --workspace-order	The order in which the projects of a workspace run
     --help	Display the concise help for this command
     --no-progress	Hide all progress outputs
     --quiet	Do not print any output
@@ -1869,6 +2522,76 @@ fn reuse_env() -> Result<()> {
     Ok(())
 }
 
+/// `--refresh-hooks` installs the hook environments again, instead of reusing them.
+#[test]
+fn refresh_hooks() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+    context.write_pre_commit_config(indoc::indoc! {r#"
+    repos:
+      - repo: local
+        hooks:
+          - id: hello
+            name: hello
+            language: python
+            entry: python -c 'print("Hello")'
+            pass_filenames: false
+            always_run: true
+    "#});
+    context.git_add(".");
+
+    let env_dirs = || -> Result<Vec<_>> {
+        Ok(context
+            .home_dir()
+            .child("hooks")
+            .read_dir()?
+            .map(|entry| entry.map(|entry| entry.file_name()))
+            .collect::<std::io::Result<_>>()?)
+    };
+
+    context.run().assert().success();
+    let installed = env_dirs()?;
+    assert_eq!(installed.len(), 1);
+
+    // A normal run reuses the environment.
+    context.run().assert().success();
+    assert_eq!(env_dirs()?, installed);
+
+    // The environments of other hooks are not refreshed.
+    context
+        .run()
+        .arg("--refresh-hooks=other")
+        .assert()
+        .success();
+    assert_eq!(env_dirs()?, installed);
+
+    cmd_snapshot!(context.filters(), context.run().arg("--refresh-hooks"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    hello....................................................................Passed
+
+    ----- stderr -----
+    ");
+
+    // The environment is installed again, in a new directory.
+    let refreshed = env_dirs()?;
+    assert_eq!(refreshed.len(), 1);
+    assert_ne!(refreshed, installed);
+
+    cmd_snapshot!(context.filters(), context.run().arg("--refresh-hooks=hello"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    hello....................................................................Passed
+
+    ----- stderr -----
+    ");
+    assert_ne!(env_dirs()?, refreshed);
+
+    Ok(())
+}
+
 #[test]
 fn dry_run() {
     let context = TestContext::new();
@@ -1884,6 +2607,18 @@ fn dry_run() {
     "});
     context.git_add(".");
 
+    // Without `-v`, only the number of files is shown.
+    cmd_snapshot!(context.filters(), context.run().arg("--dry-run"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    fail.....................................................................Dry Run
+    - hook id: fail
+      `fail` would be run on 1 files
+
+    ----- stderr -----
+    ");
+
     // Run with `--dry-run`
     cmd_snapshot!(context.filters(), context.run().arg("--dry-run").arg("-v"), @r"
     success: true
@@ -1897,17 +2632,41 @@ fn dry_run() {
 
     ----- stderr -----
     ");
-}
-
-/// Supports reading `pre-commit-config.yml` as well.
-#[test]
-fn alternate_config_file() -> Result<()> {
-    let context = TestContext::new();
-    context.init_project();
 
-    context
-        .work_dir()
-        .child(ALT_CONFIG_FILE)
+    // Hook environments are not installed.
+    context.write_pre_commit_config(indoc::indoc! {r"
+        repos:
+          - repo: local
+            hooks:
+              - id: python
+                name: python
+                entry: python
+                language: python
+                additional_dependencies: ['prek-package-that-does-not-exist']
+    "});
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run().arg("--dry-run"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    python...................................................................Dry Run
+    - hook id: python
+      `python` would be run on 1 files
+
+    ----- stderr -----
+    ");
+}
+
+/// Supports reading `pre-commit-config.yml` as well.
+#[test]
+fn alternate_config_file() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    context
+        .work_dir()
+        .child(ALT_CONFIG_FILE)
         .write_str(indoc::indoc! {r#"
         repos:
           - repo: local
@@ -2056,6 +2815,8 @@ fn show_diff_on_failure() -> Result<()> {
     modify...................................................................Failed
     - hook id: modify
     - files were modified by this hook
+
+    Summary: 0 passed, 2 failed, 0 skipped across 2 projects
     All changes made by hooks:
     diff --git a/app/file.txt b/app/file.txt
     index [OLD]..[NEW] 100644
@@ -2079,3 +2840,2427 @@ fn show_diff_on_failure() -> Result<()> {
 
     Ok(())
 }
+
+/// `--show-diff-on-failure` only shows the changes made by hooks, not earlier local changes,
+/// and shows nothing if hooks didn't change any file.
+#[test]
+fn show_diff_on_failure_only_hook_changes() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+    context.configure_git_author();
+    context.disable_auto_crlf();
+
+    context.write_pre_commit_config(indoc::indoc! {r"
+        repos:
+          - repo: local
+            hooks:
+              - id: append
+                name: append
+                language: system
+                entry: sh -c 'echo Added line >> file.txt'
+                pass_filenames: false
+              - id: fail
+                name: fail
+                language: fail
+                entry: failed
+                files: other
+    "});
+    context
+        .work_dir()
+        .child("file.txt")
+        .write_str("Original line\n")?;
+    context
+        .work_dir()
+        .child("other.txt")
+        .write_str("Original line\n")?;
+    context.git_add(".");
+    context.git_commit("Initial commit");
+
+    // An unstaged change, which is not stashed with `--all-files`.
+    context
+        .work_dir()
+        .child("other.txt")
+        .write_str("Local change\n")?;
+
+    let mut filters = context.filters();
+    filters.push((r"index \w{7}\.\.\w{7} \d{6}", "index [OLD]..[NEW] 100644"));
+
+    cmd_snapshot!(filters.clone(), context.run().arg("--all-files").arg("--show-diff-on-failure"), @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+    append...................................................................Failed
+    - hook id: append
+    - files were modified by this hook
+    fail.....................................................................Failed
+    - hook id: fail
+    - exit code: 1
+      failed
+
+      other.txt
+    All changes made by hooks:
+    diff --git a/file.txt b/file.txt
+    index [OLD]..[NEW] 100644
+    --- a/file.txt
+    +++ b/file.txt
+    @@ -1 +1,2 @@
+     Original line
+    +Added line
+
+    ----- stderr -----
+    ");
+
+    cmd_snapshot!(filters.clone(), context.run().arg("--all-files").arg("--show-diff-on-failure").arg("--skip").arg("append"), @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+    append..................................................................Skipped
+    fail.....................................................................Failed
+    - hook id: fail
+    - exit code: 1
+      failed
+
+      other.txt
+
+    ----- stderr -----
+    ");
+
+    // Without a failure, no diff is shown even if the working tree is dirty.
+    cmd_snapshot!(filters.clone(), context.run().arg("--all-files").arg("--show-diff-on-failure").arg("--skip").arg("append").arg("--skip").arg("fail"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    append..................................................................Skipped
+    fail....................................................................Skipped
+
+    ----- stderr -----
+    ");
+
+    Ok(())
+}
+
+/// Use a repo on the local filesystem without cloning it.
+#[test]
+fn path_repo() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    let cwd = context.work_dir();
+    cwd.child("hooks/.pre-commit-hooks.yaml")
+        .write_str(indoc::indoc! {r"
+        - id: echo
+          name: echo
+          language: system
+          entry: echo
+          files: \.txt$
+        "})?;
+    cwd.child("file.txt").write_str("Hello, world!\n")?;
+
+    context.write_pre_commit_config(indoc::indoc! {r"
+        repos:
+          - repo: ./hooks
+            hooks:
+              - id: echo
+                verbose: true
+    "});
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run(), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    echo.....................................................................Passed
+    - hook id: echo
+    - duration: [TIME]
+      file.txt
+
+    ----- stderr -----
+    ");
+
+    // The repo must contain a manifest.
+    context.write_pre_commit_config(indoc::indoc! {r"
+        repos:
+          - repo: ./missing
+            hooks:
+              - id: echo
+    "});
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run(), @r"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    error: Failed to read manifest of `[TEMP_DIR]/missing`
+      caused by: failed to open file `[TEMP_DIR]/missing/.pre-commit-hooks.yaml`: No such file or directory (os error 2)
+    ");
+
+    // The repo must not escape the git repository.
+    context.write_pre_commit_config(indoc::indoc! {r"
+        repos:
+          - repo: ../home
+            hooks:
+              - id: echo
+    "});
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run(), @r"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    error: Repo path `[HOME]/` is outside of the git repository
+    ");
+
+    Ok(())
+}
+
+/// Test `--offline` only uses repos that are already cloned.
+#[test]
+fn offline() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    let manifest = indoc::indoc! {r"
+        - id: echo
+          name: echo
+          language: system
+          entry: echo
+          pass_filenames: false
+          always_run: true
+    "};
+    let cached = context.create_hook_repo("cached", manifest)?;
+    let uncached = context.create_hook_repo("uncached", manifest)?;
+
+    context.write_pre_commit_config(&indoc::formatdoc! {r"
+        repos:
+          - repo: {}
+            rev: v1.0.0
+            hooks:
+              - id: echo
+    ", cached.display()});
+    context.git_add(".");
+
+    // Clone the repo into the store.
+    cmd_snapshot!(context.filters(), context.run(), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    echo.....................................................................Passed
+
+    ----- stderr -----
+    ");
+
+    // Cached repos can be used offline.
+    cmd_snapshot!(context.filters(), context.run().arg("--offline"), @"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    echo.....................................................................Passed
+
+    ----- stderr -----
+    ");
+
+    context.write_pre_commit_config(&indoc::formatdoc! {r"
+        repos:
+          - repo: {}
+            rev: v1.0.0
+            hooks:
+              - id: echo
+          - repo: {}
+            rev: v1.0.0
+            hooks:
+              - id: echo
+    ", cached.display(), uncached.display()});
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run().arg("--offline"), @"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    error: Failed to initialize repo `[HOME]/test-repos/uncached`
+      caused by: repo `[HOME]/test-repos/uncached` at rev `v1.0.0` is not cached and --offline was set
+    ");
+
+    // `PREK_OFFLINE` works as well.
+    cmd_snapshot!(context.filters(), context.run().env(EnvVars::PREK_OFFLINE, "1"), @"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    error: Failed to initialize repo `[HOME]/test-repos/uncached`
+      caused by: repo `[HOME]/test-repos/uncached` at rev `v1.0.0` is not cached and --offline was set
+    ");
+
+    Ok(())
+}
+
+/// A cloned repo without a valid manifest reports where the clone is.
+#[test]
+fn invalid_remote_manifest() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    let repo = context.create_hook_repo(
+        "broken-repo",
+        indoc::indoc! {r"
+            - id: echo
+              name: echo
+              language: system
+        "},
+    )?;
+
+    context.write_pre_commit_config(&indoc::formatdoc! {r"
+        repos:
+          - repo: {}
+            rev: v1.0.0
+            hooks:
+              - id: echo
+    ", repo.display()});
+    context.git_add(".");
+
+    let filters = context
+        .filters()
+        .into_iter()
+        .chain([(r"repos/[0-9a-f]+", "repos/[HASH]")])
+        .collect::<Vec<_>>();
+
+    cmd_snapshot!(filters, context.run(), @"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    error: repo `[HOME]/test-repos/broken-repo` at rev `v1.0.0` has no valid .pre-commit-hooks.yaml (at [HOME]/repos/[HASH])
+      caused by: Failed to parse `[HOME]/repos/[HASH]/.pre-commit-hooks.yaml`
+      caused by: .[0]: missing field `entry` at line 1 column 3
+    ");
+
+    Ok(())
+}
+
+/// `--files` runs hooks on the given files, each checked by the project containing it.
+#[test]
+fn files_across_projects() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    let config = indoc::indoc! {r"
+        repos:
+          - repo: local
+            hooks:
+              - id: show
+                name: Show
+                language: system
+                entry: echo
+                verbose: true
+    "};
+    context.setup_workspace(&["project2"], config)?;
+    let cwd = context.work_dir();
+    cwd.child("file.txt").write_str("root")?;
+    cwd.child("other.txt").write_str("root")?;
+    cwd.child("project2/file.txt").write_str("project2")?;
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run().arg("--files").arg("file.txt").arg("--files").arg("project2/file.txt"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    Running hooks for `project2`:
+    Show.....................................................................Passed
+    - hook id: show
+    - duration: [TIME]
+      file.txt
+
+    Running hooks for `.`:
+    Show.....................................................................Passed
+    - hook id: show
+    - duration: [TIME]
+      file.txt
+
+    Summary: 2 passed, 0 failed, 0 skipped across 2 projects
+
+    ----- stderr -----
+    ");
+
+    // Files outside of the git repository are rejected.
+    let outside = context.home_dir().child("outside.txt");
+    outside.write_str("outside")?;
+    cmd_snapshot!(context.filters(), context.run().arg("--files").arg(outside.path()), @r"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    error: File `[HOME]/outside.txt` is outside of the git repository `[TEMP_DIR]/`
+    ");
+
+    Ok(())
+}
+
+/// `--files` outside of every project of a workspace nested in the git repository are ignored.
+#[test]
+fn files_outside_projects() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    let config = indoc::indoc! {r"
+        repos:
+          - repo: local
+            hooks:
+              - id: show
+                name: Show
+                language: system
+                entry: echo
+                verbose: true
+    "};
+    let cwd = context.work_dir();
+    cwd.child("tool/.pre-commit-config.yaml")
+        .write_str(config)?;
+    cwd.child("tool/nested/.pre-commit-config.yaml")
+        .write_str(config)?;
+    cwd.child("tool/file.txt").write_str("tool")?;
+    cwd.child("tool/nested/file.txt").write_str("nested")?;
+    cwd.child("readme.txt").write_str("root")?;
+    cwd.child("docs/guide.txt").write_str("root")?;
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run().current_dir(cwd.child("tool")).arg("--files").arg("file.txt").arg("--files").arg("nested/file.txt").arg("--files").arg("../readme.txt").arg("--files").arg("../docs/guide.txt"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    Running hooks for `nested`:
+    Show.....................................................................Passed
+    - hook id: show
+    - duration: [TIME]
+      file.txt
+
+    Running hooks for `.`:
+    Show.....................................................................Passed
+    - hook id: show
+    - duration: [TIME]
+      file.txt
+
+    Summary: 2 passed, 0 failed, 0 skipped across 2 projects
+
+    ----- stderr -----
+    warning: These files are not in any project of the workspace, they will be ignored: `docs/guide.txt, readme.txt`
+    ");
+
+    Ok(())
+}
+
+/// Test `--from-ref` and `--to-ref` run hooks on files changed between two refs.
+#[test]
+fn from_ref_to_ref() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+    context.configure_git_author();
+
+    let config = indoc::indoc! {r"
+        repos:
+          - repo: local
+            hooks:
+              - id: show
+                name: Show
+                language: system
+                entry: echo
+                verbose: true
+    "};
+    context.setup_workspace(&["project2"], config)?;
+    let cwd = context.work_dir();
+    cwd.child("unchanged.txt").write_str("unchanged")?;
+    cwd.child("deleted.txt").write_str("deleted")?;
+    cwd.child("project2/modified.txt").write_str("before")?;
+    context.git_add(".");
+    context.git_commit("Initial commit");
+
+    cwd.child("added.txt").write_str("added")?;
+    cwd.child("project2/modified.txt").write_str("after")?;
+    context.git_rm("deleted.txt");
+    context.git_add(".");
+    context.git_commit("Second commit");
+
+    cmd_snapshot!(context.filters(), context.run().arg("--from-ref").arg("HEAD~1").arg("--to-ref").arg("HEAD"), @"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    Running hooks for `project2`:
+    Show.....................................................................Passed
+    - hook id: show
+    - duration: [TIME]
+      modified.txt
+
+    Running hooks for `.`:
+    Show.....................................................................Passed
+    - hook id: show
+    - duration: [TIME]
+      added.txt
+
+    Summary: 2 passed, 0 failed, 0 skipped across 2 projects
+
+    ----- stderr -----
+    ");
+
+    // Files deleted from the working tree are not passed to hooks.
+    context.git_rm("added.txt");
+    cmd_snapshot!(context.filters(), context.run().arg("--from-ref").arg("HEAD~1").arg("--to-ref").arg("HEAD"), @"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    Running hooks for `project2`:
+    Show.....................................................................Passed
+    - hook id: show
+    - duration: [TIME]
+      modified.txt
+
+    Running hooks for `.`:
+    Show.................................................(no files to check)Skipped
+
+    Summary: 1 passed, 0 failed, 1 skipped across 2 projects
+
+    ----- stderr -----
+    ");
+
+    // Both refs must be given together.
+    cmd_snapshot!(context.filters(), context.run().arg("--from-ref").arg("HEAD~1"), @"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    error: the following required arguments were not provided:
+      --to-ref <TO_REF>
+
+    Usage: prek run --from-ref <FROM_REF> --to-ref <TO_REF> [HOOK|PROJECT]...
+
+    For more information, try '--help'.
+    ");
+
+    Ok(())
+}
+
+/// Test `--clean-env` and `--hook-env-passthrough` restrict the environment of hooks.
+#[test]
+fn clean_env() {
+    let context = TestContext::new();
+    context.init_project();
+
+    context.write_pre_commit_config(indoc::indoc! {r#"
+        repos:
+          - repo: local
+            hooks:
+              - id: env
+                name: env
+                language: system
+                entry: sh -c 'echo "home=$${HOME:+set} secret=$${PREK_TEST_SECRET:-unset} pre_commit=$${PRE_COMMIT:-unset}"'
+                pass_filenames: false
+                always_run: true
+                verbose: true
+    "#});
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run().env("PREK_TEST_SECRET", "secret"), @"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    env......................................................................Passed
+    - hook id: env
+    - duration: [TIME]
+      home=set secret=secret pre_commit=1
+
+    ----- stderr -----
+    ");
+
+    cmd_snapshot!(context.filters(), context.run()
+        .env("PREK_TEST_SECRET", "secret")
+        .arg("--clean-env")
+        .arg("--hook-env-passthrough")
+        .arg("HOME"), @"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    env......................................................................Passed
+    - hook id: env
+    - duration: [TIME]
+      home=set secret=unset pre_commit=1
+
+    ----- stderr -----
+    ");
+}
+
+/// Hooks get the variables of their `env`, overridden by `--env`, on top of the inherited environment.
+#[test]
+fn hook_env() {
+    let context = TestContext::new();
+    context.init_project();
+
+    context.write_pre_commit_config(indoc::indoc! {r#"
+        repos:
+          - repo: local
+            hooks:
+              - id: env
+                name: env
+                language: system
+                entry: sh -c 'echo "greeting=$${PREK_TEST_GREETING:-unset} name=$${PREK_TEST_NAME:-unset} inherited=$${PREK_TEST_INHERITED:-unset}"'
+                pass_filenames: false
+                always_run: true
+                verbose: true
+                env:
+                  PREK_TEST_GREETING: hello
+                  PREK_TEST_NAME: config
+    "#});
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run().env("PREK_TEST_INHERITED", "yes"), @"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    env......................................................................Passed
+    - hook id: env
+    - duration: [TIME]
+      greeting=hello name=config inherited=yes
+
+    ----- stderr -----
+    ");
+
+    cmd_snapshot!(context.filters(), context.run()
+        .env("PREK_TEST_INHERITED", "yes")
+        .arg("--env")
+        .arg("PREK_TEST_NAME=cli"), @"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    env......................................................................Passed
+    - hook id: env
+    - duration: [TIME]
+      greeting=hello name=cli inherited=yes
+
+    ----- stderr -----
+    ");
+
+    cmd_snapshot!(context.filters(), context.run().arg("--env").arg("PREK_TEST_NAME"), @"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    error: invalid value 'PREK_TEST_NAME' for '--env <KEY=VALUE>': expected `<KEY>=<VALUE>`, got `PREK_TEST_NAME`
+
+    For more information, try '--help'.
+    ");
+}
+
+/// Hooks of the `post-commit` stage run without any files.
+#[test]
+fn post_commit_stage() {
+    let context = TestContext::new();
+    context.init_project();
+
+    context.write_pre_commit_config(indoc::indoc! {r"
+        repos:
+          - repo: local
+            hooks:
+              - id: post-commit
+                name: post-commit
+                language: system
+                entry: echo post-commit
+                stages: [post-commit]
+                verbose: true
+              - id: pre-commit
+                name: pre-commit
+                language: system
+                entry: echo pre-commit
+                stages: [pre-commit]
+                verbose: true
+    "});
+    context
+        .work_dir()
+        .child("file.txt")
+        .write_str("Hello")
+        .unwrap();
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run().arg("--hook-stage").arg("post-commit"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    post-commit..............................................................Passed
+    - hook id: post-commit
+    - duration: [TIME]
+      post-commit
+
+    ----- stderr -----
+    ");
+}
+
+/// Hooks can be selected by their `alias`, which must be unique.
+#[test]
+fn hook_alias() {
+    let context = TestContext::new();
+    context.init_project();
+
+    context.write_pre_commit_config(indoc::indoc! {r"
+        repos:
+          - repo: local
+            hooks:
+              - id: echo
+                name: echo
+                language: system
+                entry: echo
+                args: [first]
+                alias: echo-first
+                pass_filenames: false
+                verbose: true
+              - id: echo
+                name: echo
+                language: system
+                entry: echo
+                args: [second]
+                alias: echo-second
+                pass_filenames: false
+                verbose: true
+    "});
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run().arg("echo-second"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    echo.....................................................................Passed
+    - hook id: echo
+    - hook alias: echo-second
+    - duration: [TIME]
+      second
+
+    ----- stderr -----
+    ");
+
+    context.write_pre_commit_config(indoc::indoc! {r"
+        repos:
+          - repo: local
+            hooks:
+              - id: echo
+                name: echo
+                language: system
+                entry: echo
+                alias: echo-twice
+              - id: echo
+                name: echo
+                language: system
+                entry: echo
+                alias: echo-twice
+    "});
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run(), @r"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    error: Duplicate hook alias `echo-twice` in `.pre-commit-config.yaml`, each alias can only be used once
+    ");
+}
+
+/// Hooks sharing an id are told apart by their alias.
+#[test]
+fn duplicate_hook_ids_with_aliases() {
+    let context = TestContext::new();
+    context.init_project();
+
+    context.write_pre_commit_config(indoc::indoc! {r"
+        repos:
+          - repo: local
+            hooks:
+              - id: black
+                name: black
+                language: system
+                entry: echo
+                alias: black-src
+                files: ^src/
+                verbose: true
+              - id: black
+                name: black
+                language: system
+                entry: echo
+                alias: black-tests
+                files: ^tests/
+                verbose: true
+    "});
+    context
+        .work_dir()
+        .child("src/main.py")
+        .write_str("")
+        .unwrap();
+    context
+        .work_dir()
+        .child("tests/test_main.py")
+        .write_str("")
+        .unwrap();
+    context.git_add(".");
+
+    let filters = context
+        .filters()
+        .into_iter()
+        .chain([(r#""duration_ms":\d+"#, r#""duration_ms":[TIME]"#)])
+        .collect::<Vec<_>>();
+
+    cmd_snapshot!(filters.clone(), context.run(), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    black....................................................................Passed
+    - hook id: black
+    - hook alias: black-src
+    - duration: [TIME]
+      src/main.py
+    black....................................................................Passed
+    - hook id: black
+    - hook alias: black-tests
+    - duration: [TIME]
+      tests/test_main.py
+
+    ----- stderr -----
+    ");
+
+    cmd_snapshot!(filters.clone(), context.run().arg("black-tests"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    black....................................................................Passed
+    - hook id: black
+    - hook alias: black-tests
+    - duration: [TIME]
+      tests/test_main.py
+
+    ----- stderr -----
+    ");
+
+    cmd_snapshot!(filters, context.run().arg("black").arg("--output-format").arg("json"), @r#"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    {"project":"","hook_id":"black","alias":"black-src","name":"black","status":"passed","duration_ms":[TIME],"files":["src/main.py"],"exit_code":0,"timed_out_after_ms":null,"files_modified":false,"cached":false,"stdout":"src/main.py/n"}
+    {"project":"","hook_id":"black","alias":"black-tests","name":"black","status":"passed","duration_ms":[TIME],"files":["tests/test_main.py"],"exit_code":0,"timed_out_after_ms":null,"files_modified":false,"cached":false,"stdout":"tests/test_main.py/n"}
+    {"summary":{"total":2,"passed":2,"failed":0,"skipped":0,"projects":1}}
+
+    ----- stderr -----
+    "#);
+}
+
+/// `${VAR}` in `entry` and `args` is expanded from the environment.
+#[test]
+fn entry_env_vars() {
+    let context = TestContext::new();
+    context.init_project();
+
+    context.write_pre_commit_config(indoc::indoc! {r"
+        repos:
+          - repo: local
+            hooks:
+              - id: env
+                name: env
+                language: system
+                entry: echo ${PREK_TEST_BIN} $${PREK_TEST_BIN}
+                args: ['--config=${PREK_TEST_CONFIG:-default.toml}']
+                pass_filenames: false
+                always_run: true
+                verbose: true
+    "});
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run()
+        .env("PREK_TEST_BIN", "my linter")
+        .env("PREK_TEST_CONFIG", "custom.toml"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    env......................................................................Passed
+    - hook id: env
+    - duration: [TIME]
+      my linter ${PREK_TEST_BIN} --config=custom.toml
+
+    ----- stderr -----
+    ");
+
+    cmd_snapshot!(context.filters(), context.run().env("PREK_TEST_BIN", "my linter"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    env......................................................................Passed
+    - hook id: env
+    - duration: [TIME]
+      my linter ${PREK_TEST_BIN} --config=default.toml
+
+    ----- stderr -----
+    ");
+
+    cmd_snapshot!(context.filters(), context.run(), @r"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    error: Hook `env` is invalid
+      caused by: Environment variable `PREK_TEST_BIN` is not set, use `${PREK_TEST_BIN:-<default>}` to provide a default
+    ");
+}
+
+/// The manifest of a hook repository is not expanded, only the `args` set in the config are.
+#[test]
+fn entry_env_vars_not_expanded_in_manifest() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    context
+        .work_dir()
+        .child("hook-repo")
+        .child(".pre-commit-hooks.yaml")
+        .write_str(indoc::indoc! {r#"
+        - id: first-arg
+          name: first-arg
+          language: system
+          entry: sh -c 'echo "first: ${1}"' --
+          pass_filenames: false
+          always_run: true
+          verbose: true
+    "#})?;
+    context.write_pre_commit_config(indoc::indoc! {r"
+        repos:
+          - repo: ./hook-repo
+            hooks:
+              - id: first-arg
+                args: ['${PREK_TEST_ARG}']
+    "});
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run().env("PREK_TEST_ARG", "expanded"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    first-arg................................................................Passed
+    - hook id: first-arg
+    - duration: [TIME]
+      first: expanded
+
+    ----- stderr -----
+    ");
+
+    Ok(())
+}
+
+/// Test `--output-format json` prints one JSON line per hook result.
+#[test]
+fn output_format_json() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    let config = indoc::indoc! {r"
+        repos:
+          - repo: local
+            hooks:
+              - id: pass
+                name: Pass
+                language: system
+                entry: echo passed
+                pass_filenames: false
+                always_run: true
+              - id: fail
+                name: Fail
+                language: fail
+                entry: always fails
+                always_run: true
+              - id: no-files
+                name: No files
+                language: system
+                entry: echo
+                files: \.nothing$
+    "};
+    context.setup_workspace(&["project2"], config)?;
+    context.git_add(".");
+
+    let filters = context
+        .filters()
+        .into_iter()
+        .chain([(r#""duration_ms":\d+"#, r#""duration_ms":[TIME]"#)])
+        .collect::<Vec<_>>();
+
+    cmd_snapshot!(filters, context.run().arg("--output-format").arg("json"), @r#"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+    {"project":"project2","hook_id":"pass","name":"Pass","status":"passed","duration_ms":[TIME],"files":[],"exit_code":0,"timed_out_after_ms":null,"files_modified":false,"cached":false,"stdout":"passed/n"}
+    {"project":"project2","hook_id":"fail","name":"Fail","status":"failed","duration_ms":[TIME],"files":[".pre-commit-config.yaml"],"exit_code":1,"timed_out_after_ms":null,"files_modified":false,"cached":false,"stdout":"always fails/n/n.pre-commit-config.yaml/n/n"}
+    {"project":"project2","hook_id":"no-files","name":"No files","status":"skipped","duration_ms":[TIME],"files":[],"exit_code":null,"timed_out_after_ms":null,"files_modified":false,"cached":false,"stdout":""}
+    {"project":"","hook_id":"pass","name":"Pass","status":"passed","duration_ms":[TIME],"files":[],"exit_code":0,"timed_out_after_ms":null,"files_modified":false,"cached":false,"stdout":"passed/n"}
+    {"project":"","hook_id":"fail","name":"Fail","status":"failed","duration_ms":[TIME],"files":[".pre-commit-config.yaml"],"exit_code":1,"timed_out_after_ms":null,"files_modified":false,"cached":false,"stdout":"always fails/n/n.pre-commit-config.yaml/n/n"}
+    {"project":"","hook_id":"no-files","name":"No files","status":"skipped","duration_ms":[TIME],"files":[],"exit_code":null,"timed_out_after_ms":null,"files_modified":false,"cached":false,"stdout":""}
+    {"summary":{"total":6,"passed":2,"failed":2,"skipped":2,"projects":2}}
+
+    ----- stderr -----
+    "#);
+
+    Ok(())
+}
+
+/// `--summary-json-only` prints a single JSON object summarizing the run, and nothing else.
+#[test]
+fn summary_json_only() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    let config = indoc::indoc! {r"
+        repos:
+          - repo: local
+            hooks:
+              - id: pass
+                name: Pass
+                language: system
+                entry: echo passed
+                pass_filenames: false
+                always_run: true
+                verbose: true
+              - id: fail
+                name: Fail
+                language: fail
+                entry: always fails
+                always_run: true
+              - id: no-files
+                name: No files
+                language: system
+                entry: echo
+                files: \.nothing$
+    "};
+    context.setup_workspace(&["project2"], config)?;
+    context.git_add(".");
+
+    let filters = context
+        .filters()
+        .into_iter()
+        .chain([(r#""duration_ms":\d+"#, r#""duration_ms":[TIME]"#)])
+        .collect::<Vec<_>>();
+
+    cmd_snapshot!(filters, context.run().arg("--summary-json-only").arg("--show-diff-on-failure"), @r#"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+    {"version":1,"total":6,"passed":2,"failed":2,"skipped":2,"duration_ms":[TIME],"projects":{".":{"total":3,"passed":1,"failed":1,"skipped":1},"project2":{"total":3,"passed":1,"failed":1,"skipped":1}}}
+
+    ----- stderr -----
+    "#);
+
+    Ok(())
+}
+
+/// `--output-format junit` writes a JUnit XML report, in addition to the text output.
+#[test]
+fn output_format_junit() {
+    let context = TestContext::new();
+    context.init_project();
+
+    context.write_pre_commit_config(indoc::indoc! {r"
+        repos:
+          - repo: local
+            hooks:
+              - id: pass
+                name: Pass
+                language: system
+                entry: echo passed
+                pass_filenames: false
+                always_run: true
+              - id: fail
+                name: Fail
+                language: fail
+                entry: always <fails>
+                always_run: true
+              - id: no-files
+                name: No files
+                language: system
+                entry: echo
+                files: \.nothing$
+    "});
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run().arg("--output-format").arg("junit").arg("--output").arg("reports/junit.xml"), @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+    Pass.....................................................................Passed
+    Fail.....................................................................Failed
+    - hook id: fail
+    - exit code: 1
+      always <fails>
+
+      .pre-commit-config.yaml
+    No files.............................................(no files to check)Skipped
+
+    ----- stderr -----
+    ");
+
+    insta::with_settings!(
+        { filters => vec![(r#"time="\d+\.\d+""#, r#"time="[TIME]""#)] },
+        {
+            insta::assert_snapshot!(context.read("reports/junit.xml"), @r#"
+            <?xml version="1.0" encoding="UTF-8"?>
+            <testsuites name="prek" tests="3" failures="1" skipped="1" time="[TIME]">
+              <testsuite name="." tests="3" failures="1" skipped="1" time="[TIME]">
+                <testcase name="pass" classname="." time="[TIME]"/>
+                <testcase name="fail" classname="." time="[TIME]">
+                  <failure message="hook failed">always &lt;fails&gt;
+
+            .pre-commit-config.yaml</failure>
+                </testcase>
+                <testcase name="no-files" classname="." time="[TIME]">
+                  <skipped message="no files to check"/>
+                </testcase>
+              </testsuite>
+            </testsuites>
+            "#);
+        }
+    );
+
+    cmd_snapshot!(context.filters(), context.run().arg("--output").arg("report.xml"), @r"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    error: `--output` is only supported with `--output-format junit`
+    ");
+}
+
+/// `--log-file` appends the output of every hook to a file, without changing the terminal output.
+#[test]
+fn run_log_file() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    let config = indoc::indoc! {r"
+        repos:
+          - repo: local
+            hooks:
+              - id: pass
+                name: Pass
+                language: system
+                entry: echo passed
+                pass_filenames: false
+                always_run: true
+              - id: fail
+                name: Fail
+                language: fail
+                entry: always fails
+                always_run: true
+              - id: no-files
+                name: No files
+                language: system
+                entry: echo
+                files: \.nothing$
+    "};
+    context.setup_workspace(&["project2"], config)?;
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run().arg("--log-file").arg("logs/hooks.log"), @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+    Running hooks for `project2`:
+    Pass.....................................................................Passed
+    Fail.....................................................................Failed
+    - hook id: fail
+    - exit code: 1
+      always fails
+
+      .pre-commit-config.yaml
+    No files.............................................(no files to check)Skipped
+
+    Running hooks for `.`:
+    Pass.....................................................................Passed
+    Fail.....................................................................Failed
+    - hook id: fail
+    - exit code: 1
+      always fails
+
+      .pre-commit-config.yaml
+    No files.............................................(no files to check)Skipped
+
+    Summary: 2 passed, 2 failed, 2 skipped across 2 projects
+
+    ----- stderr -----
+    ");
+
+    insta::with_settings!(
+        { filters => vec![(r"\d+ms\)", "[TIME])")] },
+        {
+            insta::assert_snapshot!(context.read("logs/hooks.log"), @r"
+            ==> project2:pass (passed, exit code 0, [TIME]) <==
+            passed
+            ==> project2:fail (failed, exit code 1, [TIME]) <==
+            always fails
+
+            .pre-commit-config.yaml
+
+            ==> .:pass (passed, exit code 0, [TIME]) <==
+            passed
+            ==> .:fail (failed, exit code 1, [TIME]) <==
+            always fails
+
+            .pre-commit-config.yaml
+            ");
+        }
+    );
+
+    Ok(())
+}
+
+/// Test interactive hooks are connected to the terminal instead of having their output captured.
+#[test]
+fn interactive_hooks() {
+    let context = TestContext::new();
+    context.init_project();
+
+    context.write_pre_commit_config(indoc::indoc! {r#"
+        repos:
+          - repo: local
+            hooks:
+              - id: prompt
+                name: prompt
+                language: system
+                entry: sh -c 'read answer; echo "first answer is $answer"'
+                pass_filenames: false
+                always_run: true
+                interactive: true
+              - id: prompt-cli
+                name: prompt-cli
+                language: system
+                entry: sh -c 'read answer; echo "second answer is $answer"; exit 1'
+                pass_filenames: false
+                always_run: true
+    "#});
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run().arg("--interactive-hook").arg("prompt-cli").pass_stdin("yes\nno\n"), @"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+    first answer is yes
+    prompt...................................................................Passed
+    second answer is no
+    prompt-cli...............................................................Failed
+    - hook id: prompt-cli
+    - exit code: 1
+
+    ----- stderr -----
+    ");
+}
+
+/// Hook output is printed under each hook once it completes, or streamed with `--no-buffer`.
+#[test]
+fn no_buffer() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    let config = indoc::indoc! {r"
+        repos:
+          - repo: local
+            hooks:
+              - id: first
+                name: first
+                language: system
+                entry: echo first
+                pass_filenames: false
+                always_run: true
+                verbose: true
+              - id: second
+                name: second
+                language: system
+                entry: echo second
+                pass_filenames: false
+                always_run: true
+                verbose: true
+    "};
+    context.setup_workspace(&["project2"], config)?;
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run(), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    Running hooks for `project2`:
+    first....................................................................Passed
+    - hook id: first
+    - duration: [TIME]
+      first
+    second...................................................................Passed
+    - hook id: second
+    - duration: [TIME]
+      second
+
+    Running hooks for `.`:
+    first....................................................................Passed
+    - hook id: first
+    - duration: [TIME]
+      first
+    second...................................................................Passed
+    - hook id: second
+    - duration: [TIME]
+      second
+
+    Summary: 4 passed, 0 failed, 0 skipped across 2 projects
+
+    ----- stderr -----
+    ");
+
+    cmd_snapshot!(context.filters(), context.run().arg("--no-buffer"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    Running hooks for `project2`:
+    first
+    first....................................................................Passed
+    - hook id: first
+    - duration: [TIME]
+    second
+    second...................................................................Passed
+    - hook id: second
+    - duration: [TIME]
+
+    Running hooks for `.`:
+    first
+    first....................................................................Passed
+    - hook id: first
+    - duration: [TIME]
+    second
+    second...................................................................Passed
+    - hook id: second
+    - duration: [TIME]
+
+    Summary: 4 passed, 0 failed, 0 skipped across 2 projects
+
+    ----- stderr -----
+    ");
+
+    Ok(())
+}
+
+/// Test `--no-parallel-clone` clones repos one at a time.
+#[test]
+fn no_parallel_clone() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    let manifest = indoc::indoc! {r"
+        - id: echo
+          name: echo
+          language: system
+          entry: echo
+          pass_filenames: false
+          always_run: true
+    "};
+    let repos = ["repo1", "repo2", "repo3"]
+        .into_iter()
+        .map(|name| context.create_hook_repo(name, manifest))
+        .collect::<Result<Vec<_>>>()?;
+
+    let config = repos
+        .iter()
+        .map(|repo| {
+            indoc::formatdoc! {r"
+              - repo: {}
+                rev: v1.0.0
+                hooks:
+                  - id: echo
+            ", repo.display()}
+        })
+        .collect::<String>();
+    context.write_pre_commit_config(&format!("repos:\n{config}"));
+    context.git_add(".");
+
+    let output = context
+        .run()
+        .arg("--no-parallel-clone")
+        .arg("-vv")
+        .output()?;
+    assert!(output.status.success());
+
+    // Each clone must complete before the next one starts.
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let events = stderr
+        .lines()
+        .filter_map(|line| {
+            if line.contains("Cloning repo") {
+                Some("start")
+            } else if line.contains("Cloned repo") {
+                Some("complete")
+            } else {
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(events, ["start", "complete"].repeat(repos.len()));
+
+    Ok(())
+}
+
+/// Test `PREK_CONCURRENCY` limits how many repos are cloned at the same time.
+#[test]
+fn concurrency_env() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    let manifest = indoc::indoc! {r"
+        - id: echo
+          name: echo
+          language: system
+          entry: echo
+          pass_filenames: false
+          always_run: true
+    "};
+    let repos = ["repo1", "repo2", "repo3"]
+        .into_iter()
+        .map(|name| context.create_hook_repo(name, manifest))
+        .collect::<Result<Vec<_>>>()?;
+
+    let config = repos
+        .iter()
+        .map(|repo| {
+            indoc::formatdoc! {r"
+              - repo: {}
+                rev: v1.0.0
+                hooks:
+                  - id: echo
+            ", repo.display()}
+        })
+        .collect::<String>();
+    context.write_pre_commit_config(&format!("repos:\n{config}"));
+    context.git_add(".");
+
+    let output = context
+        .run()
+        .env(EnvVars::PREK_CONCURRENCY, "1")
+        .arg("-vv")
+        .output()?;
+    assert!(output.status.success());
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Cloning up to 1 repos concurrently"));
+    let events = stderr
+        .lines()
+        .filter_map(|line| {
+            if line.contains("Cloning repo") {
+                Some("start")
+            } else if line.contains("Cloned repo") {
+                Some("complete")
+            } else {
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(events, ["start", "complete"].repeat(repos.len()));
+
+    // Invalid values fall back to the default.
+    let output = context
+        .run()
+        .env(EnvVars::PREK_CONCURRENCY, "lots")
+        .arg("-vv")
+        .output()?;
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Cloning up to 5 repos concurrently"));
+
+    Ok(())
+}
+
+/// Test `--print-resolved-revs` prints the commit a tag-pinned repo resolved to.
+#[test]
+fn print_resolved_revs() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    let repo = context.create_hook_repo(
+        "tagged-repo",
+        indoc::indoc! {r"
+            - id: echo
+              name: echo
+              language: system
+              entry: echo
+        "},
+    )?;
+    let output = Command::new("git")
+        .args(["rev-parse", "v1.0.0^{commit}"])
+        .current_dir(&repo)
+        .output()?;
+    let sha = String::from_utf8(output.stdout)?.trim().to_string();
+
+    context.write_pre_commit_config(&indoc::formatdoc! {r"
+        repos:
+          - repo: {}
+            rev: v1.0.0
+            hooks:
+              - id: echo
+    ", repo.display()});
+    context.git_add(".");
+
+    let filters = context
+        .filters()
+        .into_iter()
+        .chain([(sha.as_str(), "[SHA]")])
+        .collect::<Vec<_>>();
+
+    cmd_snapshot!(filters.clone(), context.run().arg("--print-resolved-revs"), @"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    [[HOME]/test-repos/tagged-repo] v1.0.0 -> [SHA]
+
+    ----- stderr -----
+    ");
+
+    cmd_snapshot!(filters, context.run().arg("--print-resolved-revs").arg("--output-format").arg("json"), @r#"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    [
+      {
+        "repo": "[HOME]/test-repos/tagged-repo",
+        "rev": "v1.0.0",
+        "resolved_rev": "[SHA]"
+      }
+    ]
+
+    ----- stderr -----
+    "#);
+
+    Ok(())
+}
+
+/// Test `--clone-retries` retries a clone failing with a network error.
+#[cfg(unix)]
+#[test]
+fn clone_retries() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    let repo = context.create_hook_repo(
+        "flaky-repo",
+        indoc::indoc! {r"
+            - id: echo
+              name: echo
+              language: system
+              entry: echo
+        "},
+    )?;
+
+    // An `ssh` that refuses the first connection, then serves the repo locally.
+    let marker = context.home_dir().child("connected");
+    let ssh = context.home_dir().child("flaky-ssh");
+    ssh.write_str(&indoc::formatdoc! {r#"
+        # Git probes the `ssh` variant with `-G` first.
+        if [ "$1" = "-G" ]; then
+            exit 0
+        fi
+        if [ ! -f {marker} ]; then
+            touch {marker}
+            echo "ssh: connect to host example.com port 22: Connection refused" >&2
+            exit 255
+        fi
+        for command; do :; done
+        eval "$command"
+    "#, marker = marker.display()})?;
+
+    context.write_pre_commit_config(&indoc::formatdoc! {r"
+        repos:
+          - repo: ssh://example.com{}
+            rev: v1.0.0
+            hooks:
+              - id: echo
+                always_run: true
+    ", repo.display()});
+    context.git_add(".");
+
+    let filters = context
+        .filters()
+        .into_iter()
+        .chain([(r"exit code: ", "exit status: ")])
+        .collect::<Vec<_>>();
+
+    cmd_snapshot!(filters.clone(), context.run()
+        .env("GIT_SSH_COMMAND", format!("sh {}", ssh.display()))
+        .arg("--clone-retries").arg("0"), @r"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    error: Failed to initialize repo `ssh://example.com[HOME]/test-repos/flaky-repo`
+      caused by: command `git shallow clone` exited with an error:
+
+    [status]
+    exit status: 128
+
+    [stderr]
+    ssh: connect to host example.com port 22: Connection refused
+    fatal: Could not read from remote repository.
+    Please make sure you have the correct access rights
+    and the repository exists.
+    ");
+
+    fs_err::remove_file(&marker)?;
+    cmd_snapshot!(filters, context.run()
+        .env("GIT_SSH_COMMAND", format!("sh {}", ssh.display()))
+        .env(EnvVars::PREK_CLONE_RETRY_BACKOFF, "0")
+        .arg("--clone-retries").arg("1"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    echo.....................................................................Passed
+
+    ----- stderr -----
+    ");
+
+    Ok(())
+}
+
+/// Test `--repo` runs hooks in multiple git repositories.
+#[test]
+fn multiple_repos() -> Result<()> {
+    let context = TestContext::new();
+    let cwd = context.work_dir();
+
+    for (name, entry) in [("repo-a", "echo"), ("repo-b", "false")] {
+        let repo = cwd.child(name);
+        repo.create_dir_all()?;
+        Command::new("git")
+            .arg("init")
+            .current_dir(&repo)
+            .assert()
+            .success();
+        repo.child(CONFIG_FILE).write_str(&indoc::formatdoc! {r"
+            repos:
+              - repo: local
+                hooks:
+                  - id: check
+                    name: check
+                    language: system
+                    entry: '{entry}'
+                    always_run: true
+                    pass_filenames: false
+            "})?;
+        Command::new("git")
+            .arg("add")
+            .arg(".")
+            .current_dir(&repo)
+            .assert()
+            .success();
+    }
+
+    cmd_snapshot!(context.filters(), context.run().arg("--repo").arg("repo-a").arg("--repo").arg("repo-b"), @"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+    Running hooks in repo `repo-a`:
+    check....................................................................Passed
+
+    Running hooks in repo `repo-b`:
+    check....................................................................Failed
+    - hook id: check
+    - exit code: 1
+
+    ----- stderr -----
+    ");
+
+    cmd_snapshot!(context.filters(), context.run().arg("--repo").arg("missing"), @"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    error: Repository `missing` does not exist
+    ");
+
+    Ok(())
+}
+
+/// `--hook-concurrency` limits how many invocations of a hook run at the same time.
+#[test]
+fn hook_concurrency() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    // Fails if another invocation of the hook holds the lock.
+    context.write_pre_commit_config(indoc::indoc! {r"
+        repos:
+          - repo: local
+            hooks:
+              - id: lint
+                name: lint
+                language: system
+                entry: sh -c 'mkdir .lock || exit 1; sleep 0.2; rmdir .lock; echo $# files' --
+                files: \.txt$
+                verbose: true
+    "});
+    for i in 0..32 {
+        context
+            .work_dir()
+            .child(format!("file{i}.txt"))
+            .write_str("text")?;
+    }
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run().arg("--hook-concurrency").arg("lint=1"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    lint.....................................................................Passed
+    - hook id: lint
+    - duration: [TIME]
+      32 files
+
+    ----- stderr -----
+    ");
+
+    cmd_snapshot!(context.filters(), context.run().arg("--hook-concurrency").arg("lint=0"), @r"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    error: invalid value 'lint=0' for '--hook-concurrency <HOOK_ID=N>': invalid concurrency `0`, expected a positive integer
+
+    For more information, try '--help'.
+    ");
+
+    Ok(())
+}
+
+/// `--jobs` limits how many hook processes run at the same time, for all hooks.
+#[test]
+fn jobs() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    // Fails if another invocation of a hook holds the lock.
+    context.write_pre_commit_config(indoc::indoc! {r"
+        repos:
+          - repo: local
+            hooks:
+              - id: lint
+                name: lint
+                language: system
+                entry: sh -c 'mkdir .lock || exit 1; sleep 0.2; rmdir .lock; echo $# files' --
+                files: \.txt$
+                verbose: true
+              - id: format
+                name: format
+                language: system
+                entry: sh -c 'mkdir .lock || exit 1; sleep 0.2; rmdir .lock; echo $# files' --
+                files: \.txt$
+                verbose: true
+    "});
+    for i in 0..12 {
+        context
+            .work_dir()
+            .child(format!("file{i:02}.txt"))
+            .write_str("text")?;
+    }
+    context.git_add(".");
+
+    // A single process per hook, with all its files.
+    cmd_snapshot!(context.filters(), context.run().arg("--jobs").arg("1"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    lint.....................................................................Passed
+    - hook id: lint
+    - duration: [TIME]
+      12 files
+    format...................................................................Passed
+    - hook id: format
+    - duration: [TIME]
+      12 files
+
+    ----- stderr -----
+    ");
+
+    cmd_snapshot!(context.filters(), context.run().env(EnvVars::PREK_JOBS, "1"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    lint.....................................................................Passed
+    - hook id: lint
+    - duration: [TIME]
+      12 files
+    format...................................................................Passed
+    - hook id: format
+    - duration: [TIME]
+      12 files
+
+    ----- stderr -----
+    ");
+
+    cmd_snapshot!(context.filters(), context.run().arg("--jobs").arg("0"), @r"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    error: invalid value '0' for '--jobs <N>': 0 is not in 1..18446744073709551615
+
+    For more information, try '--help'.
+    ");
+
+    Ok(())
+}
+
+/// The batches of a hook are logged as they are scheduled and complete.
+#[test]
+fn batch_progress() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    context.write_pre_commit_config(indoc::indoc! {r"
+        repos:
+          - repo: local
+            hooks:
+              - id: lint
+                name: lint
+                language: system
+                entry: echo
+                files: \.txt$
+    "});
+    for i in 0..8 {
+        context
+            .work_dir()
+            .child(format!("file{i}.txt"))
+            .write_str("text")?;
+    }
+    context.git_add(".");
+
+    let output = context.run().arg("--jobs").arg("2").arg("-vv").output()?;
+    assert!(output.status.success());
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let progress: Vec<_> = stderr
+        .lines()
+        .filter_map(|line| {
+            line.split_once("Batches of `lint`: ")
+                .map(|(_, progress)| progress)
+        })
+        .collect();
+    assert_eq!(progress.len(), 4, "{stderr}");
+    assert_eq!(progress[0], "1 running, 1 queued, 0 done");
+    assert_eq!(progress[3], "0 running, 0 queued, 2 done");
+
+    Ok(())
+}
+
+/// Files that do not fit in one command line are split across several invocations,
+/// even for `require_serial` hooks, and the failures of all batches are reported.
+#[test]
+fn batch_long_command_line() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    context.write_pre_commit_config(indoc::indoc! {r#"
+        repos:
+          - repo: local
+            hooks:
+              - id: count
+                name: count
+                language: system
+                entry: sh -c 'echo "$# files"; exit 1' --
+                require_serial: true
+                files: \.txt$
+    "#});
+    let padding = "x".repeat(200);
+    for i in 0..1000 {
+        context
+            .work_dir()
+            .child(format!("{padding}{i}.txt"))
+            .write_str("text")?;
+    }
+    context.git_add(".");
+
+    let output = context.run().output()?;
+    assert!(!output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let counts: Vec<usize> = stdout
+        .lines()
+        .filter_map(|line| line.trim().strip_suffix(" files"))
+        .map(|count| count.parse().unwrap())
+        .collect();
+    assert!(counts.len() > 1, "{stdout}");
+    assert_eq!(counts.iter().sum::<usize>(), 1000, "{stdout}");
+
+    Ok(())
+}
+
+/// Paths that are not valid UTF-8 are matched and passed to hooks unchanged.
+#[cfg(unix)]
+#[test]
+fn non_utf8_filenames() -> Result<()> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let context = TestContext::new();
+    context.init_project();
+
+    context.write_pre_commit_config(indoc::indoc! {r#"
+        repos:
+          - repo: local
+            hooks:
+              - id: exists
+                name: exists
+                language: system
+                entry: sh -c 'for f in "$@"; do test -f "$f" && echo "found $#"; done; exit 1' --
+                files: \.txt$
+    "#});
+    let name = std::ffi::OsStr::from_bytes(b"bad\xff.txt");
+    std::fs::write(context.work_dir().join(name), "text")?;
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run(), @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+    exists...................................................................Failed
+    - hook id: exists
+    - exit code: 1
+      found 1
+
+    ----- stderr -----
+    ");
+
+    Ok(())
+}
+
+/// `--max-total-duration` terminates the running hook and reports the hooks that did not run.
+#[test]
+fn max_total_duration() {
+    let context = TestContext::new();
+    context.init_project();
+
+    context.write_pre_commit_config(indoc::indoc! {r"
+        repos:
+          - repo: local
+            hooks:
+              - id: fast
+                name: fast
+                language: system
+                entry: echo
+                pass_filenames: false
+              - id: slow-1
+                name: slow-1
+                language: system
+                entry: sleep 10
+                pass_filenames: false
+              - id: slow-2
+                name: slow-2
+                language: system
+                entry: sleep 10
+                pass_filenames: false
+              - id: slow-3
+                name: slow-3
+                language: system
+                entry: sleep 10
+                pass_filenames: false
+    "});
+    context.git_add(".");
+
+    let start = std::time::Instant::now();
+    cmd_snapshot!(context.filters(), context.run().arg("--max-total-duration").arg("1s"), @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+    fast.....................................................................Passed
+    slow-1...................................................................Failed
+    - hook id: slow-1
+      The hook was terminated, the run exceeded `--max-total-duration`.
+
+    ----- stderr -----
+    error: The run exceeded `--max-total-duration` of [TIME], 2 hooks did not run:
+      - slow-2
+      - slow-3
+    ");
+    assert!(start.elapsed() < std::time::Duration::from_secs(10));
+
+    cmd_snapshot!(context.filters(), context.run().arg("--max-total-duration").arg("1d"), @r"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    error: invalid value '1d' for '--max-total-duration <DURATION>': invalid duration unit `d`, expected `ms`, `s`, `m` or `h`
+
+    For more information, try '--help'.
+    ");
+}
+
+/// Files that no hook would run on are listed by `--report-unmatched-files`.
+#[test]
+fn report_unmatched_files() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    context.write_pre_commit_config(indoc::indoc! {r"
+        repos:
+          - repo: local
+            hooks:
+              - id: python
+                name: python
+                language: system
+                entry: echo
+                types: [python]
+              - id: config
+                name: config
+                language: system
+                entry: echo
+                files: ^config/
+    "});
+
+    let cwd = context.work_dir();
+    cwd.child("main.py").write_str("print(1)")?;
+    cwd.child("config/app.toml").write_str("[app]")?;
+    cwd.child("pyproject.toml").write_str("[project]")?;
+    cwd.child("Cargo.toml").write_str("[package]")?;
+    cwd.child("LICENSE").write_str("MIT")?;
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run().arg("--report-unmatched-files"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    python...................................................................Passed
+    config...................................................................Passed
+    4 files not matched by any hook:
+      (no extension):
+        LICENSE
+      .toml:
+        Cargo.toml
+        pyproject.toml
+      .yaml:
+        .pre-commit-config.yaml
+
+    ----- stderr -----
+    ");
+
+    // With JSON output, the files are part of the summary.
+    // `--no-cache` runs the hooks again rather than reporting the cached results.
+    let filters = context
+        .filters()
+        .into_iter()
+        .chain([(r#""duration_ms":\d+"#, r#""duration_ms":[TIME]"#)])
+        .collect::<Vec<_>>();
+    cmd_snapshot!(filters, context.run().arg("--report-unmatched-files").arg("--no-cache").arg("--output-format").arg("json"), @r#"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    {"project":"","hook_id":"python","name":"python","status":"passed","duration_ms":[TIME],"files":["main.py"],"exit_code":0,"timed_out_after_ms":null,"files_modified":false,"cached":false,"stdout":"main.py/n"}
+    {"project":"","hook_id":"config","name":"config","status":"passed","duration_ms":[TIME],"files":["config/app.toml"],"exit_code":0,"timed_out_after_ms":null,"files_modified":false,"cached":false,"stdout":"config/app.toml/n"}
+    {"summary":{"total":2,"passed":2,"failed":0,"skipped":0,"projects":1,"unmatched_files":{"(no extension)":["LICENSE"],".toml":["Cargo.toml","pyproject.toml"],".yaml":[".pre-commit-config.yaml"]}}}
+
+    ----- stderr -----
+    "#);
+
+    Ok(())
+}
+
+/// Hooks that already passed on the same files are not run again.
+#[test]
+fn result_cache() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    let repo = context.create_hook_repo(
+        "cache-repo",
+        indoc::indoc! {r"
+            - id: count
+              name: count
+              language: system
+              entry: sh -c 'echo run >> ../runs.log'
+              files: \.txt$
+        "},
+    )?;
+
+    let config = |args: &str| {
+        indoc::formatdoc! {r"
+            repos:
+              - repo: {}
+                rev: v1.0.0
+                hooks:
+                  - id: count
+                    args: [{}]
+        ", repo.display(), args}
+    };
+    context.work_dir().child("project").create_dir_all()?;
+    context
+        .work_dir()
+        .child("project/.pre-commit-config.yaml")
+        .write_str(&config(""))?;
+    context.work_dir().child("project/a.txt").write_str("a")?;
+    context.git_add(".");
+
+    let run = || {
+        let mut cmd = context.run();
+        cmd.current_dir(context.work_dir().child("project"));
+        cmd
+    };
+
+    cmd_snapshot!(context.filters(), run(), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    count....................................................................Passed
+
+    ----- stderr -----
+    ");
+
+    // Nothing changed, the result is reused.
+    cmd_snapshot!(context.filters(), run(), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    count............................................................(cached)Passed
+
+    ----- stderr -----
+    ");
+
+    // `--no-cache` always runs the hook.
+    cmd_snapshot!(context.filters(), run().arg("--no-cache"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    count....................................................................Passed
+
+    ----- stderr -----
+    ");
+
+    // Changed files invalidate the cache.
+    context.work_dir().child("project/a.txt").write_str("b")?;
+    context.git_add(".");
+    cmd_snapshot!(context.filters(), run(), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    count....................................................................Passed
+
+    ----- stderr -----
+    ");
+
+    // Changed hook config invalidates the cache.
+    context
+        .work_dir()
+        .child("project/.pre-commit-config.yaml")
+        .write_str(&config("--verbose"))?;
+    context.git_add(".");
+    cmd_snapshot!(context.filters(), run(), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    count....................................................................Passed
+
+    ----- stderr -----
+    ");
+
+    assert_eq!(context.read("runs.log").lines().count(), 4);
+
+    Ok(())
+}
+
+/// `--hook-repeat` fails if the results of a hook differ between runs.
+#[test]
+fn hook_repeat() {
+    let context = TestContext::new();
+    context.init_project();
+
+    context.write_pre_commit_config(indoc::indoc! {r"
+        repos:
+          - repo: local
+            hooks:
+              - id: deterministic
+                name: deterministic
+                language: system
+                entry: echo checked
+              - id: nondeterministic
+                name: nondeterministic
+                language: system
+                entry: sh -c 'echo run >> .runs; wc -l < .runs'
+                pass_filenames: false
+    "});
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run().arg("deterministic").arg("--hook-repeat").arg("3"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    deterministic............................................................Passed
+
+    ----- stderr -----
+    ");
+
+    cmd_snapshot!(context.filters(), context.run().arg("nondeterministic").arg("--hook-repeat").arg("3"), @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+    nondeterministic.........................................................Failed
+    - hook id: nondeterministic
+      1
+
+      Results of the hook differ between 3 runs:
+      - run 2 printed a different output than run 1
+      - run 3 printed a different output than run 1
+
+    ----- stderr -----
+    ");
+
+    // A single hook must be selected.
+    cmd_snapshot!(context.filters(), context.run().arg("--hook-repeat").arg("3"), @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    error: `--hook-repeat` requires selecting a single hook, but 2 hooks were selected
+    ");
+}
+
+/// Hooks run with `--sandbox` have no network access.
+#[cfg(target_os = "linux")]
+#[test]
+fn sandbox_blocks_network() {
+    let context = TestContext::new();
+    context.init_project();
+
+    // A new network namespace only has the loopback interface.
+    context.write_pre_commit_config(indoc::indoc! {r"
+        repos:
+          - repo: local
+            hooks:
+              - id: interfaces
+                name: interfaces
+                language: system
+                entry: sh -c 'tail -n +3 /proc/net/dev | tr -d [:blank:] | cut -f1 -d:'
+                pass_filenames: false
+                verbose: true
+    "});
+    context.git_add(".");
+
+    let filters = context
+        .filters()
+        .into_iter()
+        .chain([(r"warning: `bwrap` was not found.*\n", "")])
+        .collect::<Vec<_>>();
+
+    cmd_snapshot!(filters, context.run().arg("--sandbox"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    interfaces...............................................................Passed
+    - hook id: interfaces
+    - duration: [TIME]
+      lo
+
+    ----- stderr -----
+    ");
+}
+
+/// A hook running longer than its `timeout` is killed together with the processes it spawned,
+/// the output it wrote until then is kept.
+#[cfg(unix)]
+#[test]
+fn hook_timeout() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    context.write_pre_commit_config(indoc::indoc! {r"
+        timeout: 1
+        repos:
+          - repo: local
+            hooks:
+              - id: slow
+                name: slow
+                language: system
+                entry: sh -c 'echo started; sleep 10 & echo $! > sleep.pid; wait'
+                pass_filenames: false
+              - id: fast
+                name: fast
+                language: system
+                entry: echo done
+                pass_filenames: false
+                verbose: true
+    "});
+    context.git_add(".");
+
+    let start = std::time::Instant::now();
+    cmd_snapshot!(context.filters(), context.run(), @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+    slow.....................................................................Failed
+    - hook id: slow
+    - timed out after [TIME]
+      started
+      timed out after [TIME]
+    fast.....................................................................Passed
+    - hook id: fast
+    - duration: [TIME]
+      done
+
+    ----- stderr -----
+    ");
+    assert!(start.elapsed() < std::time::Duration::from_secs(10));
+
+    // The background `sleep` spawned by the hook was killed too,
+    // it's either gone or a zombie waiting to be reaped by init.
+    let pid = context.read("sleep.pid");
+    let output = Command::new("ps")
+        .args(["-o", "stat=", "-p", pid.trim()])
+        .output()?;
+    let state = String::from_utf8_lossy(&output.stdout);
+    assert!(state.trim().is_empty() || state.trim().starts_with('Z'));
+
+    // `--timeout` applies to hooks without a timeout of their own.
+    context.write_pre_commit_config(indoc::indoc! {r"
+        repos:
+          - repo: local
+            hooks:
+              - id: slow
+                name: slow
+                language: system
+                entry: sleep 10
+                pass_filenames: false
+    "});
+    context.git_add(".");
+
+    let filters = context
+        .filters()
+        .into_iter()
+        .chain([(r#""duration_ms":\d+"#, r#""duration_ms":[TIME]"#)])
+        .collect::<Vec<_>>();
+    cmd_snapshot!(filters, context.run().arg("--timeout").arg("500ms").arg("--output-format").arg("json"), @r#"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+    {"project":"","hook_id":"slow","name":"slow","status":"failed","duration_ms":[TIME],"files":[],"exit_code":null,"timed_out_after_ms":500,"files_modified":false,"cached":false,"stdout":"timed out after [TIME]/n"}
+    {"summary":{"total":1,"passed":0,"failed":1,"skipped":0,"projects":1}}
+
+    ----- stderr -----
+    "#);
+
+    Ok(())
+}
+
+/// `--hook` with `--on-file` runs a single hook on a single file, in the project containing it.
+#[test]
+fn run_hook_on_file() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    context.write_pre_commit_config(indoc::indoc! {r"
+        repos:
+          - repo: local
+            hooks:
+              - id: show
+                name: show
+                language: system
+                entry: echo root
+    "});
+    let app = context.work_dir().child("app");
+    app.create_dir_all()?;
+    app.child(CONFIG_FILE).write_str(indoc::indoc! {r"
+        repos:
+          - repo: local
+            hooks:
+              - id: show
+                name: show
+                language: system
+                entry: echo app
+                files: \.py$
+              - id: other
+                name: other
+                language: system
+                entry: 'false'
+    "})?;
+    app.child("main.py").write_str("print('hello')\n")?;
+    app.child("README.md").write_str("# app\n")?;
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run().arg("--hook").arg("show").arg("--on-file").arg("app/main.py"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    app main.py
+
+    ----- stderr -----
+    Running hook `show` in `app`: echo app main.py
+    ");
+
+    // The hook runs even if the file doesn't match its `files`.
+    cmd_snapshot!(context.filters(), context.run().arg("--hook").arg("show").arg("--on-file").arg("app/README.md"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    app README.md
+
+    ----- stderr -----
+    warning: `app/README.md` does not match the files, types or excludes of hook `show`, running it anyway
+    Running hook `show` in `app`: echo app README.md
+    ");
+
+    cmd_snapshot!(context.filters(), context.run().arg("--hook").arg("missing").arg("--on-file").arg("app/main.py"), @r"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    error: Hook `missing` not found in project `app`, which contains `app/main.py`
+    ");
+
+    Ok(())
+}
+
+/// A `require_serial` hook is invoked once with all files, even with many files to check.
+#[test]
+fn require_serial() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    let log = context.home_dir().child("serial.log");
+    context.write_pre_commit_config(&indoc::formatdoc! {r#"
+        repos:
+          - repo: local
+            hooks:
+              - id: serial
+                name: serial
+                language: system
+                entry: sh -c 'echo "start $#" >> "{log}"; sleep 0.2; echo end >> "{log}"' sh
+                files: \.txt$
+                require_serial: true
+    "#, log = log.path().display()});
+    for i in 0..64 {
+        context
+            .work_dir()
+            .child(format!("file{i}.txt"))
+            .write_str("content\n")?;
+    }
+    context.git_add(".");
+
+    // Even if a higher concurrency is requested for the hook.
+    cmd_snapshot!(context.filters(), context.run().arg("--hook-concurrency").arg("serial=8"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    serial...................................................................Passed
+
+    ----- stderr -----
+    ");
+
+    assert_eq!(fs_err::read_to_string(log.path())?, "start 64\nend\n");
+
+    Ok(())
+}
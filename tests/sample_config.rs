@@ -8,7 +8,7 @@ mod common;
 fn sample_config() -> anyhow::Result<()> {
     let context = TestContext::new();
 
-    cmd_snapshot!(context.filters(), context.sample_config(), @r##"
+    cmd_snapshot!(context.filters(), context.sample_config(), @r"
     success: true
     exit_code: 0
     ----- stdout -----
@@ -22,9 +22,21 @@ fn sample_config() -> anyhow::Result<()> {
           - id: end-of-file-fixer
           - id: check-yaml
           - id: check-added-large-files
+      - repo: local
+        hooks:
+          - id: forbid-merge-leftovers
+            name: forbid merge leftovers
+            entry: Remove `.orig` and `.rej` files left over from merges and patches
+            language: fail
+            files: '\.(orig|rej)$'
+          - id: no-nocommit
+            name: no NOCOMMIT markers
+            entry: NOCOMMIT
+            language: pygrep
+            types: [text]
 
     ----- stderr -----
-    "##);
+    ");
 
     cmd_snapshot!(context.filters(), context.sample_config().arg("-f"), @r#"
     success: true
@@ -35,7 +47,7 @@ fn sample_config() -> anyhow::Result<()> {
     ----- stderr -----
     "#);
 
-    insta::assert_snapshot!(context.read(CONFIG_FILE), @r##"
+    insta::assert_snapshot!(context.read(CONFIG_FILE), @r"
     # See https://pre-commit.com for more information
     # See https://pre-commit.com/hooks.html for more hooks
     repos:
@@ -46,7 +58,37 @@ fn sample_config() -> anyhow::Result<()> {
           - id: end-of-file-fixer
           - id: check-yaml
           - id: check-added-large-files
-    "##);
+      - repo: local
+        hooks:
+          - id: forbid-merge-leftovers
+            name: forbid merge leftovers
+            entry: Remove `.orig` and `.rej` files left over from merges and patches
+            language: fail
+            files: '\.(orig|rej)$'
+          - id: no-nocommit
+            name: no NOCOMMIT markers
+            entry: NOCOMMIT
+            language: pygrep
+            types: [text]
+    ");
+
+    cmd_snapshot!(context.filters(), context.sample_config().arg("--write"), @r"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    error: File `.pre-commit-config.yaml` already exists, use `--force` to overwrite it
+    ");
+
+    cmd_snapshot!(context.filters(), context.sample_config().arg("--write").arg("--force"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    Written to `.pre-commit-config.yaml`
+
+    ----- stderr -----
+    ");
 
     cmd_snapshot!(context.filters(), context.sample_config().arg("-f").arg("sample.yaml"), @r#"
     success: true
@@ -57,7 +99,7 @@ fn sample_config() -> anyhow::Result<()> {
     ----- stderr -----
     "#);
 
-    insta::assert_snapshot!(context.read("sample.yaml"), @r##"
+    insta::assert_snapshot!(context.read("sample.yaml"), @r"
     # See https://pre-commit.com for more information
     # See https://pre-commit.com/hooks.html for more hooks
     repos:
@@ -68,7 +110,19 @@ fn sample_config() -> anyhow::Result<()> {
           - id: end-of-file-fixer
           - id: check-yaml
           - id: check-added-large-files
-    "##);
+      - repo: local
+        hooks:
+          - id: forbid-merge-leftovers
+            name: forbid merge leftovers
+            entry: Remove `.orig` and `.rej` files left over from merges and patches
+            language: fail
+            files: '\.(orig|rej)$'
+          - id: no-nocommit
+            name: no NOCOMMIT markers
+            entry: NOCOMMIT
+            language: pygrep
+            types: [text]
+    ");
 
     let child = context.work_dir().join("child");
     std::fs::create_dir(&child)?;
@@ -81,7 +135,7 @@ fn sample_config() -> anyhow::Result<()> {
 
     ----- stderr -----
     "#);
-    insta::assert_snapshot!(context.read("child/sample.yaml"), @r##"
+    insta::assert_snapshot!(context.read("child/sample.yaml"), @r"
     # See https://pre-commit.com for more information
     # See https://pre-commit.com/hooks.html for more hooks
     repos:
@@ -92,7 +146,19 @@ fn sample_config() -> anyhow::Result<()> {
           - id: end-of-file-fixer
           - id: check-yaml
           - id: check-added-large-files
-    "##);
+      - repo: local
+        hooks:
+          - id: forbid-merge-leftovers
+            name: forbid merge leftovers
+            entry: Remove `.orig` and `.rej` files left over from merges and patches
+            language: fail
+            files: '\.(orig|rej)$'
+          - id: no-nocommit
+            name: no NOCOMMIT markers
+            entry: NOCOMMIT
+            language: pygrep
+            types: [text]
+    ");
 
     Ok(())
 }
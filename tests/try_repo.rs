@@ -0,0 +1,99 @@
+use assert_fs::prelude::*;
+
+use crate::common::{TestContext, cmd_snapshot};
+
+mod common;
+
+#[test]
+fn try_repo() -> anyhow::Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    let repo = context.create_hook_repo(
+        "try-repo",
+        indoc::indoc! {r"
+            - id: echo
+              name: echo
+              entry: echo
+              language: system
+            - id: always-fail
+              name: always fail
+              entry: this hook always fails
+              language: fail
+        "},
+    )?;
+
+    context
+        .work_dir()
+        .child("file.txt")
+        .write_str("TODO: write\n")?;
+    context.git_add(".");
+
+    let mut filters = context.filters();
+    filters.push((r"rev: [0-9a-f]{40}", "rev: [SHA]"));
+
+    // All hooks of the repository run against the staged files.
+    cmd_snapshot!(filters.clone(), context.try_repo().arg(&*repo), @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+    echo.....................................................................Passed
+    always fail..............................................................Failed
+    - hook id: always-fail
+    - exit code: 1
+      this hook always fails
+
+      file.txt
+
+    ----- stderr -----
+    Using config:
+      repos:
+      - repo: [HOME]/test-repos/try-repo
+        rev: [SHA]
+        hooks:
+        - id: echo
+        - id: always-fail
+    ");
+
+    // Only the selected hook runs, from the given revision.
+    cmd_snapshot!(filters.clone(), context.try_repo().arg(&*repo).arg("echo").arg("--rev").arg("v1.0.0"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    echo.....................................................................Passed
+
+    ----- stderr -----
+    Using config:
+      repos:
+      - repo: [HOME]/test-repos/try-repo
+        rev: v1.0.0
+        hooks:
+        - id: echo
+        - id: always-fail
+    ");
+
+    // Uncommitted changes of a local repository are tried as well.
+    repo.child(".pre-commit-hooks.yaml")
+        .write_str(indoc::indoc! {r"
+        - id: echo
+          name: echo changed
+          entry: echo
+          language: system
+    "})?;
+    cmd_snapshot!(filters.clone(), context.try_repo().arg(&*repo), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    echo changed.............................................................Passed
+
+    ----- stderr -----
+    Using config:
+      repos:
+      - repo: [HOME]/test-repos/try-repo
+        rev: [SHA]
+        hooks:
+        - id: echo
+    ");
+
+    Ok(())
+}
@@ -1,5 +1,6 @@
 use assert_fs::fixture::{FileWriteStr, PathChild};
 use constants::CONFIG_FILE;
+use constants::env_vars::EnvVars;
 
 use crate::common::{TestContext, cmd_snapshot};
 
@@ -58,6 +59,121 @@ fn validate_config() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn validate_config_problems() {
+    let context = TestContext::new();
+
+    // The config of the current directory is validated by default.
+    context.write_pre_commit_config(indoc::indoc! {r"
+        repos: []
+    "});
+    cmd_snapshot!(context.filters(), context.validate_config(), @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    error: No repos in `.pre-commit-config.yaml`, no hooks would run
+    ");
+
+    // All the problems are listed, not only the first one.
+    context.write_pre_commit_config(indoc::indoc! {r"
+        exclude: (unclosed
+        repos:
+          - repo: local
+            hooks:
+              - id: no-entry
+                name: No Entry
+                language: system
+              - id: bad-files
+                name: Bad Files
+                entry: echo
+                language: system
+                files: '[a-'
+    "});
+    cmd_snapshot!(context.filters(), context.validate_config(), @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    error: Failed to parse `.pre-commit-config.yaml`
+      caused by: invalid regex `(unclosed`: Parsing error at position 9: Opening parenthesis without closing parenthesis
+      all problems:
+        - `exclude`: invalid regex: Parsing error at position 9: Opening parenthesis without closing parenthesis
+        - `repos.0.hooks.0`: missing field `entry`
+        - `repos.0.hooks.1.files`: invalid regex: Parsing error at position 3: Invalid character class
+    ");
+}
+
+/// All the configs of the workspace are validated, and problems that would only show up
+/// when running the hooks are reported too.
+#[test]
+fn validate_config_workspace() -> anyhow::Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    context
+        .work_dir()
+        .child("tools/.pre-commit-hooks.yaml")
+        .write_str(indoc::indoc! {r"
+            - id: format
+              name: format
+              language: system
+              entry: echo
+        "})?;
+    context.write_pre_commit_config(indoc::indoc! {r"
+        repos:
+          - repo: ./tools
+            hooks:
+              - id: format
+              - id: lint
+          - repo: local
+            hooks:
+              - id: echo
+                name: echo
+                language: system
+                entry: echo
+              - id: echo
+                name: echo again
+                language: system
+                entry: echo again
+              - id: echo
+                name: echo aliased
+                language: system
+                entry: echo aliased
+                alias: echo-aliased
+    "});
+    context
+        .work_dir()
+        .child("project2/.pre-commit-config.yaml")
+        .write_str(indoc::indoc! {r"
+            repos:
+              - repo: https://github.com/pre-commit/pre-commit-hooks
+                hooks:
+                  - id: trailing-whitespace
+                    files: '[a-'
+        "})?;
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.validate_config(), @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    error: Hook id `echo` is used by 2 hooks without an `alias` in `.pre-commit-config.yaml`, give each of them an `alias`
+    error: Hook `lint` not present in repo `./tools`, used in `.pre-commit-config.yaml`
+    error: Failed to parse `project2/.pre-commit-config.yaml`
+      caused by: repos: Invalid remote repo: invalid regex `[a-`: Parsing error at position 3: Invalid character class at line 2 column 3
+      all problems:
+        - `repos.0`: missing field `rev`
+        - `repos.0.hooks.0.files`: invalid regex: Parsing error at position 3: Invalid character class
+    ");
+
+    Ok(())
+}
+
 #[test]
 fn validate_manifest() -> anyhow::Result<()> {
     let context = TestContext::new();
@@ -136,13 +252,99 @@ fn unexpected_keys_warning() {
         minimum_pre_commit_version: 1.0.0
     "});
 
-    // TODO: warning about `unexpected_key_in_hook` currently not working
-    cmd_snapshot!(context.filters(), context.validate_config().arg(CONFIG_FILE), @r#"
+    cmd_snapshot!(context.filters(), context.validate_config().arg(CONFIG_FILE), @r"
     success: true
     exit_code: 0
     ----- stdout -----
 
     ----- stderr -----
-    warning: Ignored unexpected keys in `.pre-commit-config.yaml`: `unexpected_key`, `another_unknown`
-    "#);
+    warning: Ignored unexpected keys in `.pre-commit-config.yaml`: `unexpected_key`, `another_unknown`, `repos.0.hooks.0.unexpected_key_in_hook`
+    ");
+}
+
+#[test]
+fn strict_unexpected_keys() {
+    let context = TestContext::new();
+    context.init_project();
+
+    context.write_pre_commit_config(indoc::indoc! {r"
+        repos:
+          - repo: local
+            hooks:
+              - id: test-hook
+                name: Test Hook
+                entry: echo test
+                language: system
+                excludes: ^docs/
+    "});
+    context.git_add(".");
+
+    // Unknown keys are ignored by default.
+    cmd_snapshot!(context.filters(), context.validate_config().arg(CONFIG_FILE), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+
+    ----- stderr -----
+    warning: Ignored unexpected keys in `.pre-commit-config.yaml`: `repos.0.hooks.0.excludes`
+    ");
+
+    cmd_snapshot!(context.filters(), context.validate_config().arg("--strict").arg(CONFIG_FILE), @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    error: Unexpected keys in `.pre-commit-config.yaml`: `repos.0.hooks.0.excludes`
+    ");
+
+    cmd_snapshot!(context.filters(), context.run().env(EnvVars::PREK_STRICT, "1"), @r"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    error: Unexpected keys in `.pre-commit-config.yaml`: `repos.0.hooks.0.excludes`
+    ");
+}
+
+#[test]
+fn invalid_stage() {
+    let context = TestContext::new();
+
+    context.write_pre_commit_config(indoc::indoc! {r"
+        repos:
+          - repo: local
+            hooks:
+              - id: test-hook
+                name: Test Hook
+                entry: echo test
+                language: system
+                stages: [pre-comit]
+    "});
+
+    cmd_snapshot!(context.filters(), context.validate_config().arg(CONFIG_FILE), @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    error: Failed to parse `.pre-commit-config.yaml`
+      caused by: repos: Invalid local repo: unknown stage `pre-comit`, did you mean `pre-commit`? Valid stages are: `manual`, `commit-msg`, `post-checkout`, `post-commit`, `post-merge`, `post-rewrite`, `pre-commit`, `pre-merge-commit`, `pre-push`, `pre-rebase`, `prepare-commit-msg` at line 2 column 3
+    ");
+
+    context.write_pre_commit_config(indoc::indoc! {r"
+        default_stages: [unknown]
+        repos: []
+    "});
+
+    cmd_snapshot!(context.filters(), context.validate_config().arg(CONFIG_FILE), @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    error: Failed to parse `.pre-commit-config.yaml`
+      caused by: default_stages: unknown stage `unknown`, valid stages are: `manual`, `commit-msg`, `post-checkout`, `post-commit`, `post-merge`, `post-rewrite`, `pre-commit`, `pre-merge-commit`, `pre-push`, `pre-rebase`, `prepare-commit-msg` at line 1 column 17
+    ");
 }
@@ -1,6 +1,10 @@
 mod common;
 
+use std::process::Command;
+
 use anyhow::Result;
+use assert_cmd::assert::OutputAssertExt;
+use assert_fs::prelude::*;
 use constants::env_vars::EnvVars;
 use indoc::indoc;
 
@@ -65,16 +69,18 @@ fn basic_discovery() -> Result<()> {
     - hook id: show-cwd
     - duration: [TIME]
       [TEMP_DIR]/project3
-      ['project5/.pre-commit-config.yaml', '.pre-commit-config.yaml']
+      ['.pre-commit-config.yaml']
 
     Running hooks for `.`:
     Show CWD.................................................................Passed
     - hook id: show-cwd
     - duration: [TIME]
       [TEMP_DIR]/
-      ['nested/project4/.pre-commit-config.yaml', '.pre-commit-config.yaml', 'project3/project5/.pre-commit-config.yaml', 'project2/.pre-commit-config.yaml']
-      [TEMP_DIR]/
-      ['project3/.pre-commit-config.yaml']
+      ['.pre-commit-config.yaml']
+
+
+    Summary: 5 passed, 0 failed, 0 skipped across 5 projects
+
 
     ----- stderr -----
     ");
@@ -122,7 +128,11 @@ fn basic_discovery() -> Result<()> {
     - hook id: show-cwd
     - duration: [TIME]
       [TEMP_DIR]/project3
-      ['project5/.pre-commit-config.yaml', '.pre-commit-config.yaml']
+      ['.pre-commit-config.yaml']
+
+
+    Summary: 2 passed, 0 failed, 0 skipped across 2 projects
+
 
     ----- stderr -----
     ");
@@ -143,7 +153,69 @@ fn basic_discovery() -> Result<()> {
     - hook id: show-cwd
     - duration: [TIME]
       [TEMP_DIR]/project3
-      ['project5/.pre-commit-config.yaml', '.pre-commit-config.yaml']
+      ['.pre-commit-config.yaml']
+
+
+    Summary: 2 passed, 0 failed, 0 skipped across 2 projects
+
+
+    ----- stderr -----
+    ");
+
+    Ok(())
+}
+
+/// `--cd` moves where the workspace is discovered from, but a relative `--config` is still
+/// resolved from the current directory.
+#[test]
+fn cd_with_relative_config() -> Result<()> {
+    let context = TestContext::new();
+    let cwd = context.work_dir();
+    context.init_project();
+
+    let config = |name: &str| {
+        indoc::formatdoc! {r"
+        repos:
+          - repo: local
+            hooks:
+            - id: echo
+              name: {name}
+              language: system
+              entry: echo
+              always_run: true
+              pass_filenames: false
+        "}
+    };
+    context.write_pre_commit_config(&config("Root"));
+    cwd.child("project2/.pre-commit-config.yaml")
+        .write_str(&config("Project2"))?;
+    context.git_add(".");
+
+    // Without `--config`, the workspace is discovered from the `--cd` directory.
+    cmd_snapshot!(context.filters(), context.run().arg("--cd").arg("project2"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    Project2.................................................................Passed
+
+    ----- stderr -----
+    ");
+
+    // `--config` is relative to the current directory, not to the `--cd` directory.
+    cmd_snapshot!(context.filters(), context.run().arg("--cd").arg("project2").arg("--config").arg(".pre-commit-config.yaml"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    Root.....................................................................Passed
+
+    ----- stderr -----
+    ");
+
+    cmd_snapshot!(context.filters(), context.run().arg("--cd").arg("project2").arg("--config").arg("project2/.pre-commit-config.yaml"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    Project2.................................................................Passed
 
     ----- stderr -----
     ");
@@ -302,16 +374,18 @@ fn run_with_selectors() -> Result<()> {
     - hook id: show-cwd
     - duration: [TIME]
       [TEMP_DIR]/project3
-      ['project5/.pre-commit-config.yaml', '.pre-commit-config.yaml']
+      ['.pre-commit-config.yaml']
 
     Running hooks for `.`:
     Show CWD.................................................................Passed
     - hook id: show-cwd
     - duration: [TIME]
       [TEMP_DIR]/
-      ['nested/project4/.pre-commit-config.yaml', '.pre-commit-config.yaml', 'project3/project5/.pre-commit-config.yaml', 'project2/.pre-commit-config.yaml']
-      [TEMP_DIR]/
-      ['project3/.pre-commit-config.yaml']
+      ['.pre-commit-config.yaml']
+
+
+    Summary: 4 passed, 0 failed, 0 skipped across 4 projects
+
 
     ----- stderr -----
     ");
@@ -332,9 +406,11 @@ fn run_with_selectors() -> Result<()> {
     - hook id: show-cwd
     - duration: [TIME]
       [TEMP_DIR]/
-      ['nested/project4/.pre-commit-config.yaml', '.pre-commit-config.yaml', 'project3/project5/.pre-commit-config.yaml', 'project2/.pre-commit-config.yaml']
-      [TEMP_DIR]/
-      ['project3/.pre-commit-config.yaml']
+      ['.pre-commit-config.yaml']
+
+
+    Summary: 2 passed, 0 failed, 0 skipped across 2 projects
+
 
     ----- stderr -----
     ");
@@ -369,16 +445,18 @@ fn run_with_selectors() -> Result<()> {
     - hook id: show-cwd
     - duration: [TIME]
       [TEMP_DIR]/project3
-      ['project5/.pre-commit-config.yaml', '.pre-commit-config.yaml']
+      ['.pre-commit-config.yaml']
 
     Running hooks for `.`:
     Show CWD.................................................................Passed
     - hook id: show-cwd
     - duration: [TIME]
       [TEMP_DIR]/
-      ['nested/project4/.pre-commit-config.yaml', '.pre-commit-config.yaml', 'project3/project5/.pre-commit-config.yaml', 'project2/.pre-commit-config.yaml']
-      [TEMP_DIR]/
-      ['project3/.pre-commit-config.yaml']
+      ['.pre-commit-config.yaml']
+
+
+    Summary: 5 passed, 0 failed, 0 skipped across 5 projects
+
 
     ----- stderr -----
     ");
@@ -405,9 +483,7 @@ fn run_with_selectors() -> Result<()> {
     - hook id: show-cwd
     - duration: [TIME]
       [TEMP_DIR]/
-      ['nested/project4/.pre-commit-config.yaml', '.pre-commit-config.yaml', 'project3/project5/.pre-commit-config.yaml', 'project2/.pre-commit-config.yaml']
-      [TEMP_DIR]/
-      ['project3/.pre-commit-config.yaml']
+      ['.pre-commit-config.yaml']
 
     ----- stderr -----
     ");
@@ -444,16 +520,18 @@ fn run_with_selectors() -> Result<()> {
     - hook id: show-cwd
     - duration: [TIME]
       [TEMP_DIR]/project3
-      ['project5/.pre-commit-config.yaml', '.pre-commit-config.yaml']
+      ['.pre-commit-config.yaml']
 
     Running hooks for `.`:
     Show CWD.................................................................Passed
     - hook id: show-cwd
     - duration: [TIME]
       [TEMP_DIR]/
-      ['nested/project4/.pre-commit-config.yaml', '.pre-commit-config.yaml', 'project3/project5/.pre-commit-config.yaml', 'project2/.pre-commit-config.yaml']
-      [TEMP_DIR]/
-      ['project3/.pre-commit-config.yaml']
+      ['.pre-commit-config.yaml']
+
+
+    Summary: 4 passed, 0 failed, 0 skipped across 4 projects
+
 
     ----- stderr -----
     warning: selector `--skip=nested:show-cwd` did not match any hooks
@@ -489,16 +567,18 @@ fn run_with_selectors() -> Result<()> {
     - hook id: show-cwd
     - duration: [TIME]
       [TEMP_DIR]/project3
-      ['project5/.pre-commit-config.yaml', '.pre-commit-config.yaml']
+      ['.pre-commit-config.yaml']
 
     Running hooks for `.`:
     Show CWD.................................................................Passed
     - hook id: show-cwd
     - duration: [TIME]
       [TEMP_DIR]/
-      ['nested/project4/.pre-commit-config.yaml', '.pre-commit-config.yaml', 'project3/project5/.pre-commit-config.yaml', 'project2/.pre-commit-config.yaml']
-      [TEMP_DIR]/
-      ['project3/.pre-commit-config.yaml']
+      ['.pre-commit-config.yaml']
+
+
+    Summary: 5 passed, 0 failed, 0 skipped across 5 projects
+
 
     ----- stderr -----
     warning: selector `--skip=non-exist` did not match any hooks
@@ -567,14 +647,18 @@ fn skips() -> Result<()> {
     - hook id: show-cwd
     - duration: [TIME]
       [TEMP_DIR]/project3
-      ['project4/.pre-commit-config.yaml', '.pre-commit-config.yaml']
+      ['.pre-commit-config.yaml']
 
     Running hooks for `.`:
     Show CWD.................................................................Passed
     - hook id: show-cwd
     - duration: [TIME]
       [TEMP_DIR]/
-      ['project2/.pre-commit-config.yaml', '.pre-commit-config.yaml', 'project3/project4/.pre-commit-config.yaml', 'project3/.pre-commit-config.yaml']
+      ['.pre-commit-config.yaml']
+
+
+    Summary: 3 passed, 0 failed, 0 skipped across 3 projects
+
 
     ----- stderr -----
     ");
@@ -596,14 +680,18 @@ fn skips() -> Result<()> {
     - hook id: show-cwd
     - duration: [TIME]
       [TEMP_DIR]/project3
-      ['project4/.pre-commit-config.yaml', '.pre-commit-config.yaml']
+      ['.pre-commit-config.yaml']
 
     Running hooks for `.`:
     Show CWD.................................................................Passed
     - hook id: show-cwd
     - duration: [TIME]
       [TEMP_DIR]/
-      ['project2/.pre-commit-config.yaml', '.pre-commit-config.yaml', 'project3/project4/.pre-commit-config.yaml', 'project3/.pre-commit-config.yaml']
+      ['.pre-commit-config.yaml']
+
+
+    Summary: 3 passed, 0 failed, 0 skipped across 3 projects
+
 
     ----- stderr -----
     ");
@@ -625,14 +713,18 @@ fn skips() -> Result<()> {
     - hook id: show-cwd
     - duration: [TIME]
       [TEMP_DIR]/project3
-      ['project4/.pre-commit-config.yaml', '.pre-commit-config.yaml']
+      ['.pre-commit-config.yaml']
 
     Running hooks for `.`:
     Show CWD.................................................................Passed
     - hook id: show-cwd
     - duration: [TIME]
       [TEMP_DIR]/
-      ['project2/.pre-commit-config.yaml', '.pre-commit-config.yaml', 'project3/project4/.pre-commit-config.yaml', 'project3/.pre-commit-config.yaml']
+      ['.pre-commit-config.yaml']
+
+
+    Summary: 3 passed, 0 failed, 0 skipped across 3 projects
+
 
     ----- stderr -----
     ");
@@ -654,14 +746,18 @@ fn skips() -> Result<()> {
     - hook id: show-cwd
     - duration: [TIME]
       [TEMP_DIR]/project3
-      ['project4/.pre-commit-config.yaml', '.pre-commit-config.yaml']
+      ['.pre-commit-config.yaml']
 
     Running hooks for `.`:
     Show CWD.................................................................Passed
     - hook id: show-cwd
     - duration: [TIME]
       [TEMP_DIR]/
-      ['project2/.pre-commit-config.yaml', '.pre-commit-config.yaml', 'project3/project4/.pre-commit-config.yaml', 'project3/.pre-commit-config.yaml']
+      ['.pre-commit-config.yaml']
+
+
+    Summary: 3 passed, 0 failed, 0 skipped across 3 projects
+
 
     ----- stderr -----
     ");
@@ -683,14 +779,18 @@ fn skips() -> Result<()> {
     - hook id: show-cwd
     - duration: [TIME]
       [TEMP_DIR]/project3
-      ['project4/.pre-commit-config.yaml', '.pre-commit-config.yaml']
+      ['.pre-commit-config.yaml']
 
     Running hooks for `.`:
     Show CWD.................................................................Passed
     - hook id: show-cwd
     - duration: [TIME]
       [TEMP_DIR]/
-      ['project2/.pre-commit-config.yaml', '.pre-commit-config.yaml', 'project3/project4/.pre-commit-config.yaml', 'project3/.pre-commit-config.yaml']
+      ['.pre-commit-config.yaml']
+
+
+    Summary: 3 passed, 0 failed, 0 skipped across 3 projects
+
 
     ----- stderr -----
     ");
@@ -704,7 +804,7 @@ fn skips() -> Result<()> {
     - hook id: show-cwd
     - duration: [TIME]
       [TEMP_DIR]/
-      ['project2/.pre-commit-config.yaml', '.pre-commit-config.yaml', 'project3/project4/.pre-commit-config.yaml', 'project3/.pre-commit-config.yaml']
+      ['.pre-commit-config.yaml']
 
     ----- stderr -----
     warning: selector `PREK_SKIP=non-exist-hook` did not match any hooks
@@ -712,3 +812,1566 @@ fn skips() -> Result<()> {
 
     Ok(())
 }
+
+/// Test `--per-project-status` writes the status of each project.
+#[test]
+fn per_project_status() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    let config = indoc! {r"
+    repos:
+      - repo: local
+        hooks:
+        - id: pass
+          name: Pass
+          language: system
+          entry: echo
+          always_run: true
+          pass_filenames: false
+    "};
+    context.setup_workspace(&["project2", "project3"], config)?;
+    context
+        .work_dir()
+        .child("project2/.pre-commit-config.yaml")
+        .write_str(indoc! {r"
+        repos:
+          - repo: local
+            hooks:
+            - id: fail
+              name: Fail
+              language: fail
+              entry: always fails
+              always_run: true
+        "})?;
+    context
+        .work_dir()
+        .child("project3/.pre-commit-config.yaml")
+        .write_str("repos: []\n")?;
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run().arg("--per-project-status").arg("status.json"), @"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+    Running hooks for `project2`:
+    Fail.....................................................................Failed
+    - hook id: fail
+    - exit code: 1
+      always fails
+
+      .pre-commit-config.yaml
+
+    Running hooks for `.`:
+    Pass.....................................................................Passed
+
+    Summary: 1 passed, 1 failed, 0 skipped across 2 projects
+
+    ----- stderr -----
+    ");
+
+    insta::assert_snapshot!(context.read("status.json"), @r#"
+    {
+      ".": "passed",
+      "project2": "failed",
+      "project3": "empty"
+    }
+    "#);
+
+    Ok(())
+}
+
+/// Test a project's `fail_fast` overrides the workspace default for the hooks of that project only.
+#[test]
+fn project_fail_fast() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    let config = |fail_fast: &str| {
+        indoc::formatdoc! {r"
+        {fail_fast}
+        repos:
+          - repo: local
+            hooks:
+            - id: fail
+              name: Fail
+              language: fail
+              entry: always fails
+              always_run: true
+              pass_filenames: false
+            - id: pass
+              name: Pass
+              language: system
+              entry: echo
+              always_run: true
+              pass_filenames: false
+        "}
+    };
+    // The workspace root config leaves `fail_fast` off, only `project2` turns it on.
+    context.setup_workspace(&["project2", "project3"], &config("fail_fast: false"))?;
+    context
+        .work_dir()
+        .child("project2/.pre-commit-config.yaml")
+        .write_str(&config("fail_fast: true"))?;
+    context
+        .work_dir()
+        .child("project3/.pre-commit-config.yaml")
+        .write_str(&config(""))?;
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run(), @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+    Running hooks for `project2`:
+    Fail.....................................................................Failed
+    - hook id: fail
+    - exit code: 1
+      always fails
+
+    Running hooks for `project3`:
+    Fail.....................................................................Failed
+    - hook id: fail
+    - exit code: 1
+      always fails
+    Pass.....................................................................Passed
+
+    Running hooks for `.`:
+    Fail.....................................................................Failed
+    - hook id: fail
+    - exit code: 1
+      always fails
+    Pass.....................................................................Passed
+
+    Summary: 2 passed, 3 failed, 0 skipped across 3 projects
+
+    ----- stderr -----
+    ");
+
+    Ok(())
+}
+
+/// Configs inside nested `.git` directories are not discovered as projects.
+#[test]
+fn skip_nested_git_dir() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    let config = indoc! {r"
+    repos:
+      - repo: local
+        hooks:
+        - id: pass
+          name: Pass
+          language: system
+          entry: echo
+          always_run: true
+          pass_filenames: false
+    "};
+    context.write_pre_commit_config(config);
+    context
+        .work_dir()
+        .child("vendor/lib/.git/.pre-commit-config.yaml")
+        .write_str(config)?;
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run().arg("--refresh-configs"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    Pass.....................................................................Passed
+
+    ----- stderr -----
+    ");
+
+    Ok(())
+}
+
+/// Test `--output-dir` collects the `output_artifacts` of each hook.
+#[test]
+fn output_dir() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    let config = indoc! {r"
+    repos:
+      - repo: local
+        hooks:
+        - id: report
+          name: Report
+          language: system
+          entry: sh -c 'mkdir -p reports && echo ok > reports/report.txt'
+          always_run: true
+          pass_filenames: false
+          output_artifacts: 'reports/*.txt'
+    "};
+    context.setup_workspace(&["project2"], config)?;
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run().arg("--output-dir").arg("artifacts"), @"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    Running hooks for `project2`:
+    Report...................................................................Passed
+
+    Running hooks for `.`:
+    Report...................................................................Passed
+
+    Summary: 2 passed, 0 failed, 0 skipped across 2 projects
+
+    ----- stderr -----
+    ");
+
+    context
+        .work_dir()
+        .child("artifacts/project2/report/reports/report.txt")
+        .assert("ok\n");
+    context
+        .work_dir()
+        .child("artifacts/report/reports/report.txt")
+        .assert("ok\n");
+    // Artifacts of the root project don't include those of nested projects.
+    context
+        .work_dir()
+        .child("artifacts/report/project2")
+        .assert(predicates::path::missing());
+
+    Ok(())
+}
+
+/// Test `--refresh-configs` ignores the cached workspace discovery results.
+#[test]
+fn refresh_configs() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    let config = indoc! {r"
+    repos:
+      - repo: local
+        hooks:
+        - id: echo
+          name: Echo
+          language: system
+          entry: echo
+          always_run: true
+          pass_filenames: false
+    "};
+    context.setup_workspace(&["project2"], config)?;
+    context.git_add(".");
+
+    // Populate the workspace cache.
+    cmd_snapshot!(context.filters(), context.run(), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    Running hooks for `project2`:
+    Echo.....................................................................Passed
+
+    Running hooks for `.`:
+    Echo.....................................................................Passed
+
+    Summary: 2 passed, 0 failed, 0 skipped across 2 projects
+
+    ----- stderr -----
+    ");
+
+    // A new project is not picked up while the cache is valid.
+    context
+        .work_dir()
+        .child("project3/.pre-commit-config.yaml")
+        .write_str(config)?;
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run(), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    Running hooks for `project2`:
+    Echo.....................................................................Passed
+
+    Running hooks for `.`:
+    Echo.....................................................................Passed
+
+    Summary: 2 passed, 0 failed, 0 skipped across 2 projects
+
+    ----- stderr -----
+    ");
+
+    cmd_snapshot!(context.filters(), context.run().arg("--refresh-configs"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    Running hooks for `project2`:
+    Echo.....................................................................Passed
+
+    Running hooks for `project3`:
+    Echo.....................................................................Passed
+
+    Running hooks for `.`:
+    Echo.....................................................................Passed
+
+    Summary: 3 passed, 0 failed, 0 skipped across 3 projects
+
+    ----- stderr -----
+    ");
+
+    Ok(())
+}
+
+/// `--project-order` runs the listed projects first, in the given order.
+#[test]
+fn project_order() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    let config = indoc::indoc! {r"
+    repos:
+      - repo: local
+        hooks:
+        - id: echo
+          name: Echo
+          language: system
+          entry: echo
+          always_run: true
+          pass_filenames: false
+    "};
+    context.setup_workspace(&["project2", "project3", "project3/project4"], config)?;
+    context.git_add(".");
+
+    context
+        .work_dir()
+        .child("order.txt")
+        .write_str("# Run the root first.\n.\n\nproject3/\n")?;
+
+    cmd_snapshot!(context.filters(), context.run().arg("--project-order").arg("order.txt"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    Running hooks for `.`:
+    Echo.....................................................................Passed
+
+    Running hooks for `project3`:
+    Echo.....................................................................Passed
+
+    Running hooks for `project3/project4`:
+    Echo.....................................................................Passed
+
+    Running hooks for `project2`:
+    Echo.....................................................................Passed
+
+    Summary: 4 passed, 0 failed, 0 skipped across 4 projects
+
+    ----- stderr -----
+    ");
+
+    context
+        .work_dir()
+        .child("order.txt")
+        .write_str("project3\nproject5\n")?;
+
+    cmd_snapshot!(context.filters(), context.run().arg("--project-order").arg("order.txt"), @r"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    error: Project `project5` listed in `order.txt` does not exist
+    ");
+
+    Ok(())
+}
+
+/// `--workspace-order root` runs the workspace root first, `deepest` runs nested projects first.
+#[test]
+fn workspace_order() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    let config = indoc::indoc! {r"
+    repos:
+      - repo: local
+        hooks:
+        - id: echo
+          name: Echo
+          language: system
+          entry: echo
+          always_run: true
+          pass_filenames: false
+    "};
+    context.setup_workspace(&["project2", "project3", "project3/project4"], config)?;
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run().arg("--workspace-order").arg("deepest"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    Running hooks for `project3/project4`:
+    Echo.....................................................................Passed
+
+    Running hooks for `project2`:
+    Echo.....................................................................Passed
+
+    Running hooks for `project3`:
+    Echo.....................................................................Passed
+
+    Running hooks for `.`:
+    Echo.....................................................................Passed
+
+    Summary: 4 passed, 0 failed, 0 skipped across 4 projects
+
+    ----- stderr -----
+    ");
+
+    cmd_snapshot!(context.filters(), context.run().arg("--workspace-order").arg("root"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    Running hooks for `.`:
+    Echo.....................................................................Passed
+
+    Running hooks for `project2`:
+    Echo.....................................................................Passed
+
+    Running hooks for `project3`:
+    Echo.....................................................................Passed
+
+    Running hooks for `project3/project4`:
+    Echo.....................................................................Passed
+
+    Summary: 4 passed, 0 failed, 0 skipped across 4 projects
+
+    ----- stderr -----
+    ");
+
+    Ok(())
+}
+
+/// A hook id in `SKIP` or `--skip` skips that hook in every project, and unknown ids only warn.
+#[test]
+fn skip_hook_id_across_projects() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    let config = indoc::indoc! {r"
+    repos:
+      - repo: local
+        hooks:
+        - id: slow
+          name: Slow
+          language: system
+          entry: echo
+          always_run: true
+          pass_filenames: false
+        - id: fast
+          name: Fast
+          language: system
+          entry: echo
+          always_run: true
+          pass_filenames: false
+    "};
+    context.setup_workspace(&["project2"], config)?;
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run().env(EnvVars::SKIP, "slow"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    Running hooks for `project2`:
+    Slow....................................................................Skipped
+    Fast.....................................................................Passed
+
+    Running hooks for `.`:
+    Slow....................................................................Skipped
+    Fast.....................................................................Passed
+
+    Summary: 2 passed, 0 failed, 2 skipped across 2 projects
+
+    ----- stderr -----
+    ");
+
+    cmd_snapshot!(context.filters(), context.run().arg("--skip").arg("slow").arg("--skip").arg("removed-hook"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    Running hooks for `project2`:
+    Slow....................................................................Skipped
+    Fast.....................................................................Passed
+
+    Running hooks for `.`:
+    Slow....................................................................Skipped
+    Fast.....................................................................Passed
+
+    Summary: 2 passed, 0 failed, 2 skipped across 2 projects
+
+    ----- stderr -----
+    warning: selector `--skip=removed-hook` did not match any hooks
+    ");
+
+    Ok(())
+}
+
+/// A project with `nested: false` owns its whole subtree, configs below it are not projects.
+#[test]
+fn nested_false() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    let config = indoc::indoc! {r"
+    repos:
+      - repo: local
+        hooks:
+        - id: show-files
+          name: Show files
+          language: system
+          entry: echo
+          require_serial: true
+          verbose: true
+    "};
+    context.setup_workspace(
+        &["project2", "project2/sub", "project2/sub/deep", "project3"],
+        config,
+    )?;
+    context
+        .work_dir()
+        .child("project2/.pre-commit-config.yaml")
+        .write_str(&format!("nested: false\n{config}"))?;
+    context
+        .work_dir()
+        .child("project2/sub/file.txt")
+        .write_str("text")?;
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run(), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    Running hooks for `project2`:
+    Show files...............................................................Passed
+    - hook id: show-files
+    - duration: [TIME]
+      sub/.pre-commit-config.yaml .pre-commit-config.yaml sub/file.txt sub/deep/.pre-commit-config.yaml
+
+    Running hooks for `project3`:
+    Show files...............................................................Passed
+    - hook id: show-files
+    - duration: [TIME]
+      .pre-commit-config.yaml
+
+    Running hooks for `.`:
+    Show files...............................................................Passed
+    - hook id: show-files
+    - duration: [TIME]
+      .pre-commit-config.yaml
+
+    Summary: 3 passed, 0 failed, 0 skipped across 3 projects
+
+    ----- stderr -----
+    ");
+
+    Ok(())
+}
+
+/// Files of nested projects are only passed to the parent project with `--include-nested-project-files`.
+#[test]
+fn include_nested_project_files() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    let config = indoc::indoc! {r"
+    repos:
+      - repo: local
+        hooks:
+        - id: show-files
+          name: Show files
+          language: system
+          entry: echo
+          files: \.txt$
+          verbose: true
+    "};
+    context.setup_workspace(&["project2"], config)?;
+    context.work_dir().child("file.txt").write_str("text")?;
+    context
+        .work_dir()
+        .child("project2/file.txt")
+        .write_str("text")?;
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run(), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    Running hooks for `project2`:
+    Show files...............................................................Passed
+    - hook id: show-files
+    - duration: [TIME]
+      file.txt
+
+    Running hooks for `.`:
+    Show files...............................................................Passed
+    - hook id: show-files
+    - duration: [TIME]
+      file.txt
+
+    Summary: 2 passed, 0 failed, 0 skipped across 2 projects
+
+    ----- stderr -----
+    ");
+
+    cmd_snapshot!(context.filters(), context.run().arg("--include-nested-project-files"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    Running hooks for `project2`:
+    Show files...............................................................Passed
+    - hook id: show-files
+    - duration: [TIME]
+      file.txt
+
+    Running hooks for `.`:
+    Show files...............................................................Passed
+    - hook id: show-files
+    - duration: [TIME]
+      project2/file.txt file.txt
+
+    Summary: 2 passed, 0 failed, 0 skipped across 2 projects
+
+    ----- stderr -----
+    ");
+
+    Ok(())
+}
+
+/// Discovery reports how many configs it found and how many directories it walked.
+#[test]
+fn discovery_stats() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    let config = indoc! {r"
+    repos:
+      - repo: local
+        hooks:
+        - id: echo
+          name: echo
+          language: system
+          entry: echo
+    "};
+    context.setup_workspace(
+        &[
+            "project2",
+            "project3",
+            "nested/project4",
+            "project3/project5",
+        ],
+        config,
+    )?;
+    context.git_add(".");
+
+    let output = context.run().arg("--refresh").arg("-vv").output()?;
+    assert!(output.status.success());
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let stats = stderr
+        .lines()
+        .find_map(|line| {
+            line.split_once("Workspace discovery: ")
+                .map(|(_, stats)| stats)
+        })
+        .expect("discovery stats should be logged");
+    let words: Vec<_> = stats.split_whitespace().collect();
+    assert_eq!(words[..4], ["found", "5", "configs", "(0"]);
+    let walked = words
+        .iter()
+        .position(|word| *word == "walked")
+        .and_then(|idx| words[idx + 1].parse::<usize>().ok())
+        .expect("walked directories should be logged");
+    assert!(walked >= 5, "walked {walked} directories");
+
+    Ok(())
+}
+
+/// Directories excluded by `.prek-workspace.yaml` are not searched for projects.
+#[test]
+fn workspace_manifest() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    let config = indoc::indoc! {r"
+    repos:
+      - repo: local
+        hooks:
+        - id: echo
+          name: Echo
+          language: system
+          entry: echo
+          always_run: true
+          pass_filenames: false
+    "};
+    context.setup_workspace(
+        &["project2", "project3", "project3/project4", "project5"],
+        config,
+    )?;
+    context
+        .work_dir()
+        .child(".prek-workspace.yaml")
+        .write_str(indoc::indoc! {r"
+        exclude:
+          - project2
+          - project3/project4
+        "})?;
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run(), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    Running hooks for `project3`:
+    Echo.....................................................................Passed
+
+    Running hooks for `project5`:
+    Echo.....................................................................Passed
+
+    Running hooks for `.`:
+    Echo.....................................................................Passed
+
+    Summary: 3 passed, 0 failed, 0 skipped across 3 projects
+
+    ----- stderr -----
+    ");
+
+    cmd_snapshot!(context.filters(), context.run().arg("--skip").arg("project5/"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    Running hooks for `project3`:
+    Echo.....................................................................Passed
+
+    Running hooks for `.`:
+    Echo.....................................................................Passed
+
+    Summary: 2 passed, 0 failed, 0 skipped across 2 projects
+
+    ----- stderr -----
+    ");
+
+    Ok(())
+}
+
+/// Directories ignored by `.prekignore` at the workspace root are not searched for projects.
+#[test]
+fn prekignore() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    let config = indoc::indoc! {r"
+    repos:
+      - repo: local
+        hooks:
+        - id: echo
+          name: Echo
+          language: system
+          entry: echo
+          always_run: true
+          pass_filenames: false
+    "};
+    context.setup_workspace(&["project2", "project3/project4", "vendor/lib"], config)?;
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run(), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    Running hooks for `project3/project4`:
+    Echo.....................................................................Passed
+
+    Running hooks for `vendor/lib`:
+    Echo.....................................................................Passed
+
+    Running hooks for `project2`:
+    Echo.....................................................................Passed
+
+    Running hooks for `.`:
+    Echo.....................................................................Passed
+
+    Summary: 4 passed, 0 failed, 0 skipped across 4 projects
+
+    ----- stderr -----
+    ");
+
+    // Changing `.prekignore` invalidates the cached discovery.
+    context
+        .work_dir()
+        .child(".prekignore")
+        .write_str("vendor/\nproject4\n")?;
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run(), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    Running hooks for `project2`:
+    Echo.....................................................................Passed
+
+    Running hooks for `.`:
+    Echo.....................................................................Passed
+
+    Summary: 2 passed, 0 failed, 0 skipped across 2 projects
+
+    ----- stderr -----
+    ");
+
+    cmd_snapshot!(context.filters(), context.run().arg("project2/"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    Running hooks for `project2`:
+    Echo.....................................................................Passed
+
+    ----- stderr -----
+    ");
+
+    Ok(())
+}
+
+/// A project requiring a newer pre-commit than prek is compatible with fails the run.
+/// Unquoted short versions are accepted too, with a warning as they are read as numbers.
+#[test]
+fn minimum_pre_commit_version() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    let config = |version: &str| {
+        indoc::formatdoc! {r"
+        minimum_pre_commit_version: {version}
+        repos:
+          - repo: local
+            hooks:
+            - id: echo
+              name: Echo
+              language: system
+              entry: echo
+              always_run: true
+              pass_filenames: false
+        "}
+    };
+    context.setup_workspace(&["project2", "project3"], &config("3.2"))?;
+    context
+        .work_dir()
+        .child("project3/.pre-commit-config.yaml")
+        .write_str(&config("'99.0'"))?;
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run(), @r"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    warning: `minimum_pre_commit_version: 3.2` is not quoted and is read as a number, which drops trailing zeros (`2.10` becomes `2.1`). Quote the version to keep it as written.
+    error: Failed to parse `project3/.pre-commit-config.yaml`
+      caused by: Required minimum pre-commit version `99.0` is greater than pre-commit `4.3.0` that prek is compatible with. Please consider updating prek.
+    ");
+
+    Ok(())
+}
+
+/// Projects with `inherit: true` inherit the defaults of their parent project, and their own
+/// values win over the inherited ones.
+#[test]
+fn inherit_parent_defaults() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    let cwd = context.work_dir();
+    cwd.child(".pre-commit-config.yaml")
+        .write_str(indoc::indoc! {r"
+        default_stages: [manual]
+        exclude: \.skip$
+        repos: []
+    "})?;
+    let child = |inherit: bool, stages: &str| {
+        indoc::formatdoc! {r"
+        inherit: {inherit}
+        repos:
+          - repo: local
+            hooks:
+            - id: show
+              name: Show
+              language: system
+              entry: echo
+              verbose: true
+            - id: pre-commit-show
+              name: Pre-commit Show
+              language: system
+              entry: echo
+              verbose: true
+              stages: [{stages}]
+        "}
+    };
+    cwd.child("inherits/.pre-commit-config.yaml")
+        .write_str(&child(true, "pre-commit"))?;
+    cwd.child("inherits/file.txt").write_str("file")?;
+    cwd.child("inherits/file.skip").write_str("file")?;
+    cwd.child("independent/.pre-commit-config.yaml")
+        .write_str(&child(false, "pre-commit"))?;
+    cwd.child("independent/file.txt").write_str("file")?;
+    cwd.child("independent/file.skip").write_str("file")?;
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run().arg("--all-files"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    Running hooks for `independent`:
+    Show.....................................................................Passed
+    - hook id: show
+    - duration: [TIME]
+      file.skip .pre-commit-config.yaml file.txt
+    Pre-commit Show..........................................................Passed
+    - hook id: pre-commit-show
+    - duration: [TIME]
+      file.skip .pre-commit-config.yaml file.txt
+
+    Running hooks for `inherits`:
+    Pre-commit Show..........................................................Passed
+    - hook id: pre-commit-show
+    - duration: [TIME]
+      file.txt .pre-commit-config.yaml
+
+    Summary: 3 passed, 0 failed, 0 skipped across 2 projects
+
+    ----- stderr -----
+    ");
+
+    cmd_snapshot!(context.filters(), context.run().arg("--all-files").arg("--hook-stage").arg("manual"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    Running hooks for `independent`:
+    Show.....................................................................Passed
+    - hook id: show
+    - duration: [TIME]
+      file.skip .pre-commit-config.yaml file.txt
+
+    Running hooks for `inherits`:
+    Show.....................................................................Passed
+    - hook id: show
+    - duration: [TIME]
+      file.txt .pre-commit-config.yaml
+
+    Summary: 2 passed, 0 failed, 0 skipped across 2 projects
+
+    ----- stderr -----
+    ");
+
+    Ok(())
+}
+
+/// The top-level `files` and `exclude` of a project apply to all its hooks, relative to the project.
+#[test]
+fn top_level_exclude() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    let config = indoc! {r"
+    exclude: ^generated/
+    repos:
+      - repo: local
+        hooks:
+        - id: first
+          name: First
+          language: system
+          entry: echo
+          verbose: true
+        - id: second
+          name: Second
+          language: system
+          entry: echo
+          files: \.txt$
+          verbose: true
+    "};
+    context.setup_workspace(&["project2"], config)?;
+    for path in [
+        "src/a.txt",
+        "generated/b.txt",
+        "project2/src/a.txt",
+        "project2/generated/b.txt",
+    ] {
+        context.work_dir().child(path).write_str("")?;
+    }
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run().arg("--all-files"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    Running hooks for `project2`:
+    First....................................................................Passed
+    - hook id: first
+    - duration: [TIME]
+      src/a.txt .pre-commit-config.yaml
+    Second...................................................................Passed
+    - hook id: second
+    - duration: [TIME]
+      src/a.txt
+
+    Running hooks for `.`:
+    First....................................................................Passed
+    - hook id: first
+    - duration: [TIME]
+      src/a.txt .pre-commit-config.yaml
+    Second...................................................................Passed
+    - hook id: second
+    - duration: [TIME]
+      src/a.txt
+
+    Summary: 4 passed, 0 failed, 0 skipped across 2 projects
+
+    ----- stderr -----
+    ");
+
+    // An invalid pattern names itself and the config file.
+    context
+        .work_dir()
+        .child("project2/.pre-commit-config.yaml")
+        .write_str(&config.replace("^generated/", "'[a-'"))?;
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run().arg("--all-files"), @r"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    error: Failed to parse `project2/.pre-commit-config.yaml`
+      caused by: invalid regex `[a-`: Parsing error at position 3: Invalid character class
+    ");
+
+    Ok(())
+}
+
+/// All invalid configs of a workspace are reported at once.
+#[test]
+fn invalid_configs() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    let config = indoc! {r"
+    repos:
+      - repo: local
+        hooks:
+        - id: echo
+          name: Echo
+          language: system
+          entry: echo
+    "};
+    context.setup_workspace(&["project2", "project3", "project4"], config)?;
+    let cwd = context.work_dir();
+    cwd.child("project2/.pre-commit-config.yaml")
+        .write_str("repos: [")?;
+    cwd.child("project4/.pre-commit-config.yaml")
+        .write_str("repos:\n  - repo: local\n    hooks: 1\n")?;
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run(), @r"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    error: Found 2 invalid configs:
+      - Failed to parse `project2/.pre-commit-config.yaml`: did not find expected node content at line 2 column 1, while parsing a flow node
+      - Failed to parse `project4/.pre-commit-config.yaml`: repos: Invalid local repo: invalid type: integer `1`, expected a sequence at line 2 column 3
+    ");
+
+    // A single invalid config reads as before.
+    cwd.child("project4/.pre-commit-config.yaml")
+        .write_str(config)?;
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run(), @r"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    error: Failed to parse `project2/.pre-commit-config.yaml`
+      caused by: did not find expected node content at line 2 column 1, while parsing a flow node
+    ");
+
+    Ok(())
+}
+
+/// `--skip-invalid-configs` leaves out the projects with an invalid config, and runs the others.
+#[test]
+fn skip_invalid_configs() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    let config = indoc! {r"
+    repos:
+      - repo: local
+        hooks:
+        - id: echo
+          name: Echo
+          language: system
+          entry: echo
+          always_run: true
+          pass_filenames: false
+    "};
+    context.setup_workspace(&["project2", "project3", "project4"], config)?;
+    let cwd = context.work_dir();
+    cwd.child("project2/.pre-commit-config.yaml")
+        .write_str("repos: [")?;
+    cwd.child("project4/.pre-commit-config.yaml")
+        .write_str("repos:\n  - repo: local\n    hooks: 1\n")?;
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run().arg("--skip-invalid-configs"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    Running hooks for `project3`:
+    Echo.....................................................................Passed
+
+    Running hooks for `.`:
+    Echo.....................................................................Passed
+
+    Summary: 2 passed, 0 failed, 0 skipped across 2 projects
+
+    ----- stderr -----
+    warning: Skipping project with an invalid config: Failed to parse `project2/.pre-commit-config.yaml`: did not find expected node content at line 2 column 1, while parsing a flow node
+    warning: Skipping project with an invalid config: Failed to parse `project4/.pre-commit-config.yaml`: repos: Invalid local repo: invalid type: integer `1`, expected a sequence at line 2 column 3
+    ");
+
+    // Skipped configs are not cached, strict mode still reports them.
+    cmd_snapshot!(context.filters(), context.run(), @r"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    error: Found 2 invalid configs:
+      - Failed to parse `project2/.pre-commit-config.yaml`: did not find expected node content at line 2 column 1, while parsing a flow node
+      - Failed to parse `project4/.pre-commit-config.yaml`: repos: Invalid local repo: invalid type: integer `1`, expected a sequence at line 2 column 3
+    ");
+
+    Ok(())
+}
+
+/// Projects in symlinked directories are only discovered with `--follow-symlinks`.
+#[cfg(unix)]
+#[test]
+fn follow_symlinks() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    let config = indoc! {r"
+    repos:
+      - repo: local
+        hooks:
+        - id: show-cwd
+          name: Show CWD
+          language: system
+          entry: pwd
+          always_run: true
+          pass_filenames: false
+          verbose: true
+    "};
+    context.setup_workspace(&[], config)?;
+
+    // A shared directory outside of the repository, symlinked twice into it.
+    let shared = context.home_dir().child("shared-tooling");
+    shared.child(".pre-commit-config.yaml").write_str(config)?;
+    std::os::unix::fs::symlink(shared.path(), context.work_dir().join("tooling"))?;
+    std::os::unix::fs::symlink(shared.path(), context.work_dir().join("tooling-alias"))?;
+    // A symlink cycle.
+    std::os::unix::fs::symlink(shared.path(), shared.join("self"))?;
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run(), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    Show CWD.................................................................Passed
+    - hook id: show-cwd
+    - duration: [TIME]
+      [TEMP_DIR]/
+
+    ----- stderr -----
+    ");
+
+    cmd_snapshot!(context.filters(), context.run().arg("--follow-symlinks"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    Running hooks for `tooling`:
+    Show CWD.................................................................Passed
+    - hook id: show-cwd
+    - duration: [TIME]
+      [HOME]/shared-tooling
+
+    Running hooks for `.`:
+    Show CWD.................................................................Passed
+    - hook id: show-cwd
+    - duration: [TIME]
+      [TEMP_DIR]/
+
+    Summary: 2 passed, 0 failed, 0 skipped across 2 projects
+
+    ----- stderr -----
+    ");
+
+    // The workspace manifest can enable it as well.
+    context
+        .work_dir()
+        .child(".prek-workspace.yaml")
+        .write_str("follow_symlinks: true\n")?;
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run(), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    Running hooks for `tooling`:
+    Show CWD.................................................................Passed
+    - hook id: show-cwd
+    - duration: [TIME]
+      [HOME]/shared-tooling
+
+    Running hooks for `.`:
+    Show CWD.................................................................Passed
+    - hook id: show-cwd
+    - duration: [TIME]
+      [TEMP_DIR]/
+
+    Summary: 2 passed, 0 failed, 0 skipped across 2 projects
+
+    ----- stderr -----
+    ");
+
+    Ok(())
+}
+
+/// Projects in directories ignored by git are only discovered with `--include-ignored`.
+#[test]
+fn include_ignored() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    let config = indoc! {r"
+    repos:
+      - repo: local
+        hooks:
+        - id: echo
+          name: Echo
+          language: system
+          entry: echo
+          always_run: true
+          pass_filenames: false
+    "};
+    context.setup_workspace(&["project2", "node_modules/tool"], config)?;
+    context
+        .work_dir()
+        .child(".gitignore")
+        .write_str("node_modules/\n")?;
+    context.git_add(".");
+    Command::new("git")
+        .args(["add", "--force", "node_modules"])
+        .current_dir(context.work_dir())
+        .assert()
+        .success();
+
+    cmd_snapshot!(context.filters(), context.run(), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    Running hooks for `project2`:
+    Echo.....................................................................Passed
+
+    Running hooks for `.`:
+    Echo.....................................................................Passed
+
+    Summary: 2 passed, 0 failed, 0 skipped across 2 projects
+
+    ----- stderr -----
+    ");
+
+    cmd_snapshot!(context.filters(), context.run().arg("--include-ignored"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    Running hooks for `node_modules/tool`:
+    Echo.....................................................................Passed
+
+    Running hooks for `project2`:
+    Echo.....................................................................Passed
+
+    Running hooks for `.`:
+    Echo.....................................................................Passed
+
+    Summary: 3 passed, 0 failed, 0 skipped across 3 projects
+
+    ----- stderr -----
+    ");
+
+    Ok(())
+}
+
+/// Projects whose config file is a symlink to another project's config file are distinct projects.
+#[cfg(unix)]
+#[test]
+fn symlinked_config_file() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    let config = indoc! {r"
+    repos:
+      - repo: local
+        hooks:
+        - id: show-cwd
+          name: Show CWD
+          language: system
+          entry: pwd
+          always_run: true
+          pass_filenames: false
+          verbose: true
+    "};
+    context.setup_workspace(&["project1"], config)?;
+    context.work_dir().child("project2").create_dir_all()?;
+    std::os::unix::fs::symlink(
+        "../project1/.pre-commit-config.yaml",
+        context.work_dir().join("project2/.pre-commit-config.yaml"),
+    )?;
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run().arg("--follow-symlinks"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    Running hooks for `project1`:
+    Show CWD.................................................................Passed
+    - hook id: show-cwd
+    - duration: [TIME]
+      [TEMP_DIR]/project1
+
+    Running hooks for `project2`:
+    Show CWD.................................................................Passed
+    - hook id: show-cwd
+    - duration: [TIME]
+      [TEMP_DIR]/project2
+
+    Running hooks for `.`:
+    Show CWD.................................................................Passed
+    - hook id: show-cwd
+    - duration: [TIME]
+      [TEMP_DIR]/
+
+    Summary: 3 passed, 0 failed, 0 skipped across 3 projects
+
+    ----- stderr -----
+    ");
+
+    Ok(())
+}
+
+#[test]
+fn select_projects() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    let config = indoc! {r"
+    repos:
+      - repo: local
+        hooks:
+        - id: echo
+          name: echo
+          language: system
+          entry: echo
+          verbose: true
+    "};
+
+    context.setup_workspace(&["project2", "nested/project3"], config)?;
+    context.git_add(".");
+
+    // Only the given projects run, relative to the workspace root.
+    cmd_snapshot!(context.filters(), context.run().arg("--project").arg("nested/project3").arg("--project").arg("."), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    Running hooks for `nested/project3`:
+    echo.....................................................................Passed
+    - hook id: echo
+    - duration: [TIME]
+      .pre-commit-config.yaml
+
+    Running hooks for `.`:
+    echo.....................................................................Passed
+    - hook id: echo
+    - duration: [TIME]
+      .pre-commit-config.yaml
+
+    Summary: 2 passed, 0 failed, 0 skipped across 2 projects
+
+    ----- stderr -----
+    ");
+
+    // Composes with selectors, and is resolved against the workspace root.
+    cmd_snapshot!(context.filters(), context.run().current_dir(context.work_dir().child("nested")).arg("--project").arg("project2").arg("--project").arg("nested/project3").arg("--skip").arg("project3/"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    Running hooks for `project2`:
+    echo.....................................................................Passed
+    - hook id: echo
+    - duration: [TIME]
+      .pre-commit-config.yaml
+
+    ----- stderr -----
+    ");
+
+    cmd_snapshot!(context.filters(), context.run().arg("--project").arg("project3"), @r"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    error: Project `project3` not found in the workspace, available projects are: `.`, `nested/project3`, `project2`
+    ");
+
+    Ok(())
+}
+
+/// A hook id selects the hook in every project that defines it.
+#[test]
+fn select_hook_id_across_projects() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    let with_ruff = indoc! {r"
+    repos:
+      - repo: local
+        hooks:
+        - id: ruff
+          name: ruff
+          language: system
+          entry: echo
+        - id: other
+          name: other
+          language: system
+          entry: echo
+    "};
+    let without_ruff = indoc! {r"
+    repos:
+      - repo: local
+        hooks:
+        - id: other
+          name: other
+          language: system
+          entry: echo
+    "};
+
+    context.setup_workspace(&["project2", "project3"], with_ruff)?;
+    context
+        .work_dir()
+        .child("project3")
+        .child(".pre-commit-config.yaml")
+        .write_str(without_ruff)?;
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run().arg("ruff"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    Running hooks for `project2`:
+    ruff.....................................................................Passed
+
+    Running hooks for `.`:
+    ruff.....................................................................Passed
+
+    Summary: 2 passed, 0 failed, 0 skipped across 2 projects
+
+    ----- stderr -----
+    ");
+
+    // It composes with `--project` and `--all-files`.
+    cmd_snapshot!(context.filters(), context.run().arg("ruff").arg("--project").arg("project2").arg("--all-files"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    Running hooks for `project2`:
+    ruff.....................................................................Passed
+
+    ----- stderr -----
+    ");
+
+    cmd_snapshot!(context.filters(), context.run().arg("rufff"), @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    warning: selector `rufff` did not match any hooks
+    error: No hooks found after filtering with the given selectors
+    hint: Available hooks are:
+      `.`: `other`, `ruff`
+      `project2`: `other`, `ruff`
+      `project3`: `other`
+    ");
+
+    Ok(())
+}
+
+/// `files` and `exclude` of a nested project match the paths relative to the project,
+/// the same paths that are passed to the hook.
+#[test]
+fn nested_project_file_patterns() -> Result<()> {
+    let context = TestContext::new();
+    context.init_project();
+
+    let config = indoc! {r"
+    repos:
+      - repo: local
+        hooks:
+        - id: echo
+          name: echo
+          language: system
+          entry: echo
+          files: ^src/
+          exclude: ^src/generated/
+          verbose: true
+    "};
+    context.setup_workspace(&["frontend"], config)?;
+
+    let cwd = context.work_dir();
+    cwd.child("src/root.txt").write_str("root")?;
+    cwd.child("frontend/src/app.txt").write_str("app")?;
+    cwd.child("frontend/src/generated/gen.txt")
+        .write_str("gen")?;
+    cwd.child("frontend/lib/src/lib.txt").write_str("lib")?;
+    context.git_add(".");
+
+    cmd_snapshot!(context.filters(), context.run(), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    Running hooks for `frontend`:
+    echo.....................................................................Passed
+    - hook id: echo
+    - duration: [TIME]
+      src/app.txt
+
+    Running hooks for `.`:
+    echo.....................................................................Passed
+    - hook id: echo
+    - duration: [TIME]
+      src/root.txt
+
+    Summary: 2 passed, 0 failed, 0 skipped across 2 projects
+
+    ----- stderr -----
+    ");
+
+    Ok(())
+}